@@ -0,0 +1,228 @@
+//! Random generation of types for property-based testing.
+//!
+//! The [Arbitrary] trait generates random, valid instances of the logical and physical stream
+//! types of this crate from a [`rand::Rng`], such that lowering invariants can be checked for
+//! many different types, both in this crate and downstream. The `size` parameter bounds the
+//! generated values, e.g. the nesting depth of logical types and the number of fields.
+//!
+//! # Example
+//!
+//! ```
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use tydi::arbitrary::Arbitrary;
+//! use tydi::logical::LogicalType;
+//!
+//! let mut rng = StdRng::seed_from_u64(0);
+//! for _ in 0..100 {
+//!     let typ = LogicalType::arbitrary(&mut rng, 4);
+//!     for (_, stream) in typ.synthesize().streams() {
+//!         assert_eq!(stream.bit_count(), stream.signal_list().bit_count());
+//!     }
+//! }
+//! ```
+
+use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity, Union};
+use crate::physical::{Complexity, PhysicalStream};
+use crate::{Name, PositiveReal};
+use rand::Rng;
+use std::convert::TryFrom;
+
+/// Trait for types of which random instances can be generated.
+pub trait Arbitrary: Sized {
+    /// Returns a random instance of Self, bounded by `size`.
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, size: usize) -> Self;
+}
+
+impl Arbitrary for Direction {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, _size: usize) -> Self {
+        if rng.gen_bool(0.75) {
+            Direction::Forward
+        } else {
+            Direction::Reverse
+        }
+    }
+}
+
+impl Arbitrary for Synchronicity {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, _size: usize) -> Self {
+        match rng.gen_range(0..4) {
+            0 => Synchronicity::Sync,
+            1 => Synchronicity::Flatten,
+            2 => Synchronicity::Desync,
+            _ => Synchronicity::FlatDesync,
+        }
+    }
+}
+
+impl Arbitrary for Complexity {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, _size: usize) -> Self {
+        let major = rng.gen_range(1..=8);
+        if rng.gen_bool(0.25) {
+            Complexity::new(vec![major, rng.gen_range(0..4)]).unwrap()
+        } else {
+            Complexity::new_major(major)
+        }
+    }
+}
+
+/// Returns a random throughput, which is a multiple of a quarter up to `size`.
+fn throughput<R: Rng + ?Sized>(rng: &mut R, size: usize) -> PositiveReal {
+    PositiveReal::new(rng.gen_range(1..=4 * size.max(1)) as f64 / 4.0).unwrap()
+}
+
+/// Returns random fields named `f0`, `f1`, etc.
+fn fields<R: Rng + ?Sized>(
+    rng: &mut R,
+    size: usize,
+    element_only: bool,
+) -> Vec<(Name, LogicalType)> {
+    (0..rng.gen_range(1..=size.max(1)))
+        .map(|index| {
+            let name = Name::try_from(format!("f{}", index)).unwrap();
+            let typ = if element_only {
+                element(rng, size / 2)
+            } else {
+                LogicalType::arbitrary(rng, size / 2)
+            };
+            (name, typ)
+        })
+        .collect()
+}
+
+/// Returns a random logical type that consists of element-manipulating nodes only.
+fn element<R: Rng + ?Sized>(rng: &mut R, size: usize) -> LogicalType {
+    match rng.gen_range(0..if size == 0 { 2 } else { 4 }) {
+        0 => LogicalType::Null,
+        1 => LogicalType::try_new_bits(rng.gen_range(1..=16)).unwrap(),
+        2 => Group::try_new(fields(rng, size, true)).unwrap().into(),
+        _ => Union::try_new(fields(rng, size, true)).unwrap().into(),
+    }
+}
+
+impl Arbitrary for Stream {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, size: usize) -> Self {
+        let data = LogicalType::arbitrary(rng, size / 2);
+        let user = if rng.gen_bool(0.25) {
+            Some(element(rng, size / 2))
+        } else {
+            None
+        };
+        let throughput = throughput(rng, size);
+        let dimensionality = rng.gen_range(0..=3);
+        let synchronicity = Synchronicity::arbitrary(rng, size);
+        let complexity = Complexity::arbitrary(rng, size);
+        let direction = Direction::arbitrary(rng, size);
+        let keep = rng.gen_bool(0.25);
+        let stream = |keep| {
+            Stream::try_new(
+                data.clone(),
+                throughput,
+                dimensionality,
+                synchronicity,
+                complexity.clone(),
+                direction,
+                user.clone(),
+                keep,
+            )
+        };
+        // Keep streams of which the dimensionality would be lost otherwise, which is illegal.
+        stream(keep).or_else(|_| stream(true)).unwrap()
+    }
+}
+
+impl Arbitrary for LogicalType {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, size: usize) -> Self {
+        match rng.gen_range(0..if size == 0 { 2 } else { 5 }) {
+            0 => LogicalType::Null,
+            1 => LogicalType::try_new_bits(rng.gen_range(1..=16)).unwrap(),
+            2 => Group::try_new(fields(rng, size, false)).unwrap().into(),
+            3 => Union::try_new(fields(rng, size, false)).unwrap().into(),
+            _ => Stream::arbitrary(rng, size).into(),
+        }
+    }
+}
+
+impl Arbitrary for PhysicalStream {
+    fn arbitrary<R: Rng + ?Sized>(rng: &mut R, size: usize) -> Self {
+        let fields = |rng: &mut R, prefix: &str| {
+            (0..rng.gen_range(0..=size))
+                .map(|index| (format!("{}{}", prefix, index), rng.gen_range(1..=16)))
+                .collect::<Vec<_>>()
+        };
+        let element_fields = fields(rng, "e");
+        let user = fields(rng, "u");
+        PhysicalStream::try_new(
+            element_fields
+                .iter()
+                .map(|(n, w)| (n.as_str(), *w))
+                .collect::<Vec<_>>(),
+            rng.gen_range(1..=size.max(1)),
+            rng.gen_range(0..=3),
+            Complexity::arbitrary(rng, size),
+            user.iter()
+                .map(|(n, w)| (n.as_str(), *w))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PathName;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn physical_bit_count() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let stream = PhysicalStream::arbitrary(&mut rng, 4);
+            assert_eq!(stream.bit_count(), stream.signal_list().bit_count());
+        }
+    }
+
+    #[test]
+    fn split_idempotence() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let typ = LogicalType::arbitrary(&mut rng, 6);
+            let split = typ.split_streams();
+            assert!(split.signal().is_element_only());
+            assert_eq!(split.signal().split_streams().signal(), split.signal());
+            for (_, stream) in split.streams() {
+                assert_eq!(
+                    stream.split_streams().stream(&PathName::new_empty()),
+                    Some(stream)
+                );
+            }
+            assert_eq!(typ.synthesize(), typ.synthesize());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parser")]
+    fn layout_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let typ = LogicalType::arbitrary(&mut rng, 6);
+            let input = typ.to_string();
+            let (rest, parsed) = crate::parser::nom::logical_stream_type(input.as_str()).unwrap();
+            assert_eq!(rest, "");
+            // Parsing keeps the order of fields, and thus the layout of elements. Maps of fields
+            // compare equal regardless of their order, so compare the order explicitly.
+            assert_eq!(parsed.to_string(), input);
+            let layout = |typ: &LogicalType| {
+                typ.synthesize()
+                    .streams()
+                    .map(|(path, stream)| {
+                        let fields = stream.element_fields().iter().map(|(p, _)| p.clone());
+                        (path.clone(), fields.collect::<Vec<_>>())
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(layout(&parsed), layout(&typ));
+        }
+    }
+}
@@ -3,18 +3,34 @@
 //! The Command-Line Interface binary is enabled by the `cli` feature flag.
 
 use log::{debug, info, LevelFilter};
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tydi::experimental::{Feature, Features};
+use tydi::generator::chisel::{ChiselBackEnd, ChiselConfig};
+use tydi::generator::config::{
+    parse_lanes, parse_union, Configure, GenerationConfig, CONFIG_FILE_NAME,
+};
+#[cfg(feature = "firrtl")]
+use tydi::generator::firrtl::{FirrtlBackEnd, FirrtlConfig};
+use tydi::generator::header::HeaderConfig;
+use tydi::generator::registry::Registry;
 use tydi::generator::vhdl::{VHDLBackEnd, VHDLConfig};
-use tydi::generator::GenerateProject;
-use tydi::UniquelyNamedBuilder;
-use tydi::{Logger, Result};
+use tydi::generator::{GenerateProject, Lowered};
+use tydi::logical::{FieldOrder, Rounding, SynthesisOptions, UnionLowering};
+use tydi::{Context, Error, Logger, Result};
+use tydi::{Name, PathName, Positive, UniquelyNamedBuilder};
 
 use structopt::StructOpt;
+use tydi::design::diff::{diff_libraries, Compatibility};
 use tydi::design::{Library, Project};
 
 static LOGGER: Logger = Logger;
 
+/// The interval at which watch mode checks the inputs for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
 /// Back-end options.
 #[derive(Debug, StructOpt)]
 #[allow(clippy::upper_case_acronyms)]
@@ -22,7 +38,18 @@ enum TargetOpt {
     /// Generate VHDL sources.
     VHDL(VHDLConfig),
     /// Generate Chisel sources.
-    Chisel,
+    Chisel(ChiselConfig),
+    /// Generate FIRRTL circuits.
+    #[cfg(feature = "firrtl")]
+    Firrtl(FirrtlConfig),
+    /// Generate sources with a registered back-end, with its default configuration.
+    Backend(BackendOpt),
+}
+
+#[derive(Debug, StructOpt)]
+struct BackendOpt {
+    /// Name of the back-end.
+    name: String,
 }
 
 #[derive(Debug, StructOpt)]
@@ -44,15 +71,228 @@ struct GenerateOpts {
     )]
     output: Option<PathBuf>,
 
+    /// Write all generated files, even if their contents did not change.
+    #[structopt(long)]
+    force: bool,
+
+    /// Write a manifest of the generated files, with the streamlets they are generated from,
+    /// to manifest.json in the project directory.
+    #[structopt(long)]
+    manifest: bool,
+
+    /// Maximum total bit count of a streamlet interface.
+    #[structopt(long)]
+    max_width: Option<u32>,
+
+    /// Rounding strategy to convert the throughput of streams to element lanes.
+    /// Possible options: up, down, nearest. Default = up.
+    #[structopt(long)]
+    rounding: Option<Rounding>,
+
+    /// Maximum number of element lanes of streams without a number of lanes of their own.
+    #[structopt(long)]
+    max_lanes: Option<Positive>,
+
+    #[structopt(
+        long,
+        number_of_values = 1,
+        parse(try_from_str = parse_lanes),
+        help = "Number of element lanes of a stream, regardless of its throughput, as\n\
+                <library>.<streamlet>.<interface>[.<path>]=<lanes>. Can be used multiple times."
+    )]
+    lanes: Vec<(PathName, Positive)>,
+
+    /// Lowering of Unions without a lowering of their own: overlay, to overlay all variants in
+    /// the element of a single stream, or streams, to give every variant a stream of its own.
+    /// Default = overlay.
+    #[structopt(long)]
+    union_lowering: Option<UnionLowering>,
+
+    #[structopt(
+        long,
+        number_of_values = 1,
+        parse(try_from_str = parse_union),
+        help = "Lowering of a Union, as <library>.<streamlet>.<interface>[.<path>]=<lowering>.\n\
+                Can be used multiple times."
+    )]
+    union: Vec<(PathName, UnionLowering)>,
+
+    /// Order of the fields of Groups and Unions, which determines the layout of elements:
+    /// declaration, to keep the order in which fields are declared, or name, to sort fields by
+    /// name. Default = declaration.
+    #[structopt(long)]
+    field_order: Option<FieldOrder>,
+
+    /// Move the user signals of streams to child streams of their own, named user, instead of
+    /// widening the physical streams of their parents.
+    #[structopt(long)]
+    split_user: bool,
+
+    /// Disable --split-user, e.g. when it is enabled in the configuration file.
+    #[structopt(long, conflicts_with = "split_user")]
+    no_split_user: bool,
+
+    #[structopt(flatten)]
+    header: HeaderConfig,
+
+    #[structopt(
+        long,
+        help = "Configuration file with back-end options. Options given on the command line\n\
+                take precedence. If not supplied, tydi.toml in the current directory is used,\n\
+                if it exists."
+    )]
+    config: Option<PathBuf>,
+
+    /// Keep running, and generate sources again whenever an input file or the configuration
+    /// file changes. Unchanged files are not rewritten, and the files that changed are listed.
+    #[structopt(long)]
+    watch: bool,
+
     #[structopt(subcommand)]
     target: TargetOpt,
 }
 
+#[derive(Debug, StructOpt)]
+struct InitOpts {
+    /// Name of the project to create.
+    name: String,
+
+    #[structopt(
+        short,
+        help = "Directory to create the project in. It must not exist or be empty.\n\
+                If not supplied, a directory with the name of the project is created."
+    )]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct DiffOpts {
+    /// Streamlet Definition File of the old version of a library.
+    old: PathBuf,
+
+    /// Streamlet Definition File of the new version of a library.
+    new: PathBuf,
+
+    /// Exit with an error if the interfaces differ.
+    #[structopt(long)]
+    check: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckCompatOpts {
+    /// Streamlet Definition File of the old version of a library.
+    old: PathBuf,
+
+    /// Streamlet Definition File of the new version of a library.
+    new: PathBuf,
+}
+
 /// Top-level CLI commands
 #[derive(Debug, StructOpt)]
+#[allow(clippy::large_enum_variant)]
 enum Command {
     /// Generate HDL output from Streamlet Definition Files.
     Generate(GenerateOpts),
+    /// Create a new project with an example Streamlet Definition File and configuration.
+    Init(InitOpts),
+    /// Report the differences between the interfaces of two versions of a library.
+    Diff(DiffOpts),
+    /// Check whether a new version of a library is compatible with hardware that uses the old
+    /// version. Exits with an error if the new version is breaking.
+    CheckCompat(CheckCompatOpts),
+}
+
+/// Example Streamlet Definition File of new projects.
+const INIT_SDF: &str = "/// A stream of bytes, grouped into strings.
+Type text = Stream<Bits<8>, d=1>
+
+/// Converts strings to upper case.
+Streamlet to_upper (
+  /// The strings to convert.
+  input : in text,
+  /// The converted strings.
+  output : out text
+)
+";
+
+/// Example configuration file of new projects.
+const INIT_CONFIG: &str =
+    "# Options of tydi generate. Options given on the command line take precedence.
+
+[synthesis]
+rounding = \"up\"
+
+[vhdl]
+abstraction = \"canon\"
+output = \"out\"
+
+[chisel]
+output = \"out\"
+";
+
+/// Returns the Makefile of a new project.
+fn init_makefile(name: &str) -> String {
+    format!(
+        "NAME = {}
+
+.PHONY: all vhdl chisel clean
+
+all: vhdl
+
+vhdl:
+\ttydi generate $(NAME) vhdl
+
+chisel:
+\ttydi generate $(NAME) chisel
+
+clean:
+\trm -rf out
+",
+        name
+    )
+}
+
+/// Returns the README of a new project.
+fn init_readme(name: &str) -> String {
+    format!(
+        "# {}
+
+A Tydi project. Streamlets are declared in the Streamlet Definition Files (`*.sdf`) in this
+directory, and back-end options are set in `{}`.
+
+Run `make` to generate VHDL sources in `out/`, or `make chisel` to generate Chisel sources.
+",
+        name, CONFIG_FILE_NAME
+    )
+}
+
+/// Create a new project from options.
+fn init(opts: InitOpts) -> Result<()> {
+    let name = Name::try_new(opts.name)?.to_string();
+    let dir = match opts.output {
+        Some(path) => path,
+        None => std::env::current_dir()?.join(name.as_str()),
+    };
+    if dir.exists() && std::fs::read_dir(dir.as_path())?.next().is_some() {
+        return Err(Error::InvalidArgument(format!(
+            "{} already exists and is not empty",
+            dir.display()
+        )));
+    }
+    std::fs::create_dir_all(dir.as_path())?;
+
+    for (file, contents) in [
+        ("example.sdf", INIT_SDF.to_string()),
+        (CONFIG_FILE_NAME, INIT_CONFIG.to_string()),
+        ("Makefile", init_makefile(name.as_str())),
+        ("README.md", init_readme(name.as_str())),
+    ] {
+        let path = dir.join(file);
+        std::fs::write(path.as_path(), contents)?;
+        info!("Wrote {}.", path.display());
+    }
+    info!("Created project {} in {}.", name, dir.display());
+    Ok(())
 }
 
 #[derive(Debug, StructOpt)]
@@ -63,6 +303,9 @@ pub struct Opt {
     /// Enable debug-level logging.
     #[structopt(short, long)]
     debug: bool,
+    /// Enable an experimental feature. Can be used multiple times.
+    #[structopt(long = "enable-feature", number_of_values = 1)]
+    enable_feature: Vec<Feature>,
     #[structopt(subcommand)]
     cmd: Command,
 }
@@ -77,14 +320,91 @@ fn list_all_sdf(path: &Path) -> Result<Vec<PathBuf>> {
     Ok(sdf_files)
 }
 
-/// Generate sources from options.
-fn generate(opts: GenerateOpts) -> Result<()> {
+/// Returns the path of the configuration file given in the options, or of the default
+/// configuration file.
+fn config_path(opts: &GenerateOpts) -> Result<PathBuf> {
+    match &opts.config {
+        Some(path) => Ok(path.clone()),
+        None => Ok(std::env::current_dir()?.join(CONFIG_FILE_NAME)),
+    }
+}
+
+/// Load the configuration file given in the options, or the default configuration file if it
+/// exists.
+fn load_config(opts: &GenerateOpts) -> Result<Option<GenerationConfig>> {
+    let path = config_path(opts)?;
+    if opts.config.is_none() && !path.exists() {
+        return Ok(None);
+    }
+    info!("Loading configuration from {}...", path.display());
+    GenerationConfig::from_file(path).map(Some)
+}
+
+/// Returns the input files given in the options, or all .sdf files in the current directory.
+fn input_files(opts: &GenerateOpts) -> Result<Vec<PathBuf>> {
+    match &opts.inputs {
+        Some(inputs) => Ok(inputs.clone()),
+        None => list_all_sdf(std::env::current_dir()?.as_path()),
+    }
+}
+
+/// Returns the configuration of a back-end, with the options given on the command line merged
+/// into those of the configuration file, if any.
+fn configure<T: Configure>(file: Option<T>, cli: T) -> T {
+    match file {
+        Some(mut config) => {
+            config.merge(cli);
+            config
+        }
+        None => cli,
+    }
+}
+
+/// Returns the synthesis options given on the command line.
+fn synthesis_options(opts: &GenerateOpts) -> SynthesisOptions {
+    let mut options = SynthesisOptions::new().with_rounding(opts.rounding.unwrap_or_default());
+    if let Some(max_lanes) = opts.max_lanes {
+        options = options.with_max_lanes(max_lanes);
+    }
+    for (path, lanes) in &opts.lanes {
+        options = options.with_lanes(path.clone(), *lanes);
+    }
+    if let Some(lowering) = opts.union_lowering {
+        options = options.with_union_lowering(lowering);
+    }
+    for (path, lowering) in &opts.union {
+        options = options.with_union_lowering_at(path.clone(), *lowering);
+    }
+    if let Some(order) = opts.field_order {
+        options = options.with_field_order(order);
+    }
+    if opts.split_user {
+        options = options.with_split_user(true);
+    }
+    options
+}
+
+/// Generate sources from options. Returns the directory of the generated project.
+fn generate(opts: &GenerateOpts, features: &Features) -> Result<PathBuf> {
+    let file = load_config(opts)?;
+    let mut synthesis = configure(
+        file.as_ref().map(|c| c.synthesis.clone()),
+        synthesis_options(opts),
+    );
+    if opts.no_split_user {
+        synthesis = synthesis.with_split_user(false);
+    }
+    if synthesis.union_lowering() == UnionLowering::Streams
+        || synthesis
+            .unions()
+            .any(|(_, lowering)| *lowering == UnionLowering::Streams)
+    {
+        features.require(Feature::UnionStreams)?;
+    }
+    let header = configure(file.as_ref().map(|c| c.header.clone()), opts.header.clone());
+
     info!("Loading Streamlet Definition Files...");
-    // Obtain all input files from options.
-    // If no option is given, get all .sdf files in the current path.
-    let input_files = opts
-        .inputs
-        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+    let input_files = input_files(opts)?;
 
     let input_file_names: Vec<&str> = input_files.iter().filter_map(|pb| pb.to_str()).collect();
     debug!("Inputs: {}", input_file_names.join(", "));
@@ -92,32 +412,220 @@ fn generate(opts: GenerateOpts) -> Result<()> {
     // Build up a set of uniquely named libraries.
     let mut lib_builder = UniquelyNamedBuilder::new();
     for i in input_files {
-        lib_builder.add_item(Library::from_file(i.as_path())?);
+        lib_builder.add_item(
+            Library::from_file(i.as_path())
+                .with_context(|| format!("Failed to load {}", i.display()))?,
+        );
     }
 
     // Construct the project from the libraries.
-    let project = Project::from_builder(opts.name.try_into()?, lib_builder)?;
+    let mut project = Project::from_builder(opts.name.as_str().try_into()?, lib_builder)?;
+    project.resolve_throughput(&synthesis)?;
+
+    project.check_modes()?;
+    let lowered = Lowered::with_max_width(&project, opts.max_width)?;
 
     info!("Generating sources...");
-    match opts.target {
+    let output = |configured: Option<&Path>| -> Result<PathBuf> {
+        match opts.output.as_deref().or(configured) {
+            Some(path) => Ok(path.to_path_buf()),
+            None => Ok(std::env::current_dir()?),
+        }
+    };
+    let path = match &opts.target {
         TargetOpt::VHDL(cfg) => {
-            let vhdl: VHDLBackEnd = cfg.into();
-            vhdl.generate(
-                &project,
-                opts.output.unwrap_or(std::env::current_dir()?).as_path(),
+            let cfg = configure(file.map(|c| c.vhdl), cfg.clone());
+            let path = output(cfg.output())?;
+            let vhdl = VHDLBackEnd::from(cfg)
+                .with_force(opts.force)
+                .with_manifest(opts.manifest)
+                .with_features(features.clone())
+                .with_header(header);
+            vhdl.generate_lowered(&lowered, path.as_path())?;
+            path
+        }
+        TargetOpt::Chisel(cfg) => {
+            let cfg = configure(file.map(|c| c.chisel), cfg.clone());
+            let path = output(cfg.output())?;
+            let chisel = ChiselBackEnd::from(cfg)
+                .with_force(opts.force)
+                .with_manifest(opts.manifest)
+                .with_features(features.clone())
+                .with_header(header);
+            chisel.generate_lowered(&lowered, path.as_path())?;
+            path
+        }
+        #[cfg(feature = "firrtl")]
+        TargetOpt::Firrtl(cfg) => {
+            features.require(Feature::Firrtl)?;
+            let cfg = configure(file.map(|c| c.firrtl), cfg.clone());
+            let path = output(cfg.output())?;
+            let firrtl = FirrtlBackEnd::from(cfg)
+                .with_force(opts.force)
+                .with_manifest(opts.manifest)
+                .with_features(features.clone())
+                .with_header(header);
+            firrtl.generate_lowered(&lowered, path.as_path())?;
+            path
+        }
+        TargetOpt::Backend(opt) => {
+            if opt.name == "firrtl" {
+                features.require(Feature::Firrtl)?;
+            }
+            let config = GenerationConfig {
+                header,
+                ..file.unwrap_or_default()
+            };
+            let path = output(match opt.name.as_str() {
+                "vhdl" => config.vhdl.output(),
+                "chisel" => config.chisel.output(),
+                #[cfg(feature = "firrtl")]
+                "firrtl" => config.firrtl.output(),
+                _ => None,
+            })?;
+            Registry::configured(&config).generate_lowered(
+                opt.name.as_str(),
+                &lowered,
+                path.as_path(),
             )?;
+            path
         }
-        TargetOpt::Chisel => {}
-    }
+    };
     info!("Done.");
+    Ok(path.join(opts.name.as_str()))
+}
+
+/// Returns the modification times of `paths` that exist.
+fn modification_times(paths: &[PathBuf]) -> BTreeMap<PathBuf, SystemTime> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+            Some((path.clone(), modified))
+        })
+        .collect()
+}
+
+/// Returns the files in `dir` and its subdirectories, if it exists.
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.is_dir() {
+                files.extend(list_files(path.as_path()));
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Generate sources from options, and generate them again whenever the input files or the
+/// configuration file change, until the process is interrupted. Errors are reported, after
+/// which the inputs are watched for a fix.
+fn watch(opts: &GenerateOpts, features: &Features) -> Result<()> {
+    let watched = || -> Result<BTreeMap<PathBuf, SystemTime>> {
+        let mut paths = input_files(opts)?;
+        paths.push(config_path(opts)?);
+        Ok(modification_times(&paths))
+    };
+    // The directory of the generated project, once known, to list the files that changed.
+    let mut project_dir: Option<PathBuf> = None;
+    loop {
+        let inputs = watched()?;
+        let previous = project_dir
+            .as_deref()
+            .map(|dir| modification_times(&list_files(dir)));
+        match generate(opts, features) {
+            Ok(dir) => {
+                let outputs = modification_times(&list_files(dir.as_path()));
+                match previous {
+                    Some(previous) => {
+                        // Unchanged files are not rewritten, so they keep their modification
+                        // time.
+                        let changed = outputs
+                            .iter()
+                            .filter(|(path, modified)| previous.get(*path) != Some(*modified))
+                            .map(|(path, _)| path)
+                            .collect::<Vec<_>>();
+                        for path in &changed {
+                            println!("  changed: {}", path.display());
+                        }
+                        println!(
+                            "{} file(s) changed, {} unchanged.",
+                            changed.len(),
+                            outputs.len() - changed.len()
+                        );
+                    }
+                    None => println!("{} file(s) generated.", outputs.len()),
+                }
+                project_dir = Some(dir);
+            }
+            Err(e) => print_error(&e),
+        }
+        info!("Watching for changes...");
+        while watched()? == inputs {
+            std::thread::sleep(WATCH_INTERVAL);
+        }
+    }
+}
+
+/// Report interface differences from options.
+fn diff(opts: DiffOpts) -> Result<()> {
+    let load = |path: &Path| {
+        Library::from_file(path).with_context(|| format!("Failed to load {}", path.display()))
+    };
+    let differences = diff_libraries(&load(&opts.old)?, &load(&opts.new)?);
+    for difference in &differences {
+        println!("{}", difference);
+    }
+    if differences.is_empty() {
+        info!("No interface differences.");
+    } else if opts.check {
+        return Err(Error::InvalidArgument(format!(
+            "{} interface difference(s)",
+            differences.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Check the compatibility of two versions of a library from options.
+fn check_compat(opts: CheckCompatOpts) -> Result<()> {
+    let load = |path: &Path| {
+        Library::from_file(path).with_context(|| format!("Failed to load {}", path.display()))
+    };
+    let differences = diff_libraries(&load(&opts.old)?, &load(&opts.new)?);
+    for difference in &differences {
+        println!(
+            "{}: {}",
+            if difference.is_breaking() {
+                "breaking"
+            } else {
+                "compatible"
+            },
+            difference
+        );
+    }
+    let compatibility = Compatibility::of(&differences);
+    println!("{}", compatibility);
+    if compatibility == Compatibility::Breaking {
+        return Err(Error::InvalidArgument(format!(
+            "{} breaking interface change(s)",
+            differences.iter().filter(|d| d.is_breaking()).count()
+        )));
+    }
     Ok(())
 }
 
 /// Internal main function wrapped with CLI main function.
 /// Useful for tests.
 pub fn internal_main(options: Opt) -> Result<()> {
-    // Set up logger.
-    log::set_logger(&LOGGER)?;
+    // Set up logger, unless a previous run in this process already did.
+    if log::set_logger(&LOGGER).is_err() {
+        debug!("Logger already set up.");
+    }
     if options.verbose {
         log::set_max_level(LevelFilter::Info);
     }
@@ -126,8 +634,35 @@ pub fn internal_main(options: Opt) -> Result<()> {
         debug!("Debug-level logging enabled.");
     }
 
+    let features: Features = options.enable_feature.into_iter().collect();
+    for feature in features.iter() {
+        info!("Enabled experimental feature: {}", feature);
+    }
+
     match options.cmd {
-        Command::Generate(gen_opts) => generate(gen_opts),
+        Command::Generate(gen_opts) if gen_opts.watch => watch(&gen_opts, &features),
+        Command::Generate(gen_opts) => generate(&gen_opts, &features).map(|_| ()),
+        Command::Init(init_opts) => init(init_opts),
+        Command::Diff(diff_opts) => diff(diff_opts),
+        Command::CheckCompat(compat_opts) => check_compat(compat_opts),
+    }
+}
+
+/// Print an error with its causes to stderr.
+fn print_error(e: &Error) {
+    eprintln!("Error: {}", e);
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        eprintln!("  Caused by: {}", cause);
+        source = cause.source();
+    }
+}
+
+/// CLI main function.
+fn main() {
+    if let Err(e) = internal_main(Opt::from_args()) {
+        print_error(&e);
+        std::process::exit(1);
     }
 }
 
@@ -156,9 +691,46 @@ mod tests {
         std::fs::remove_dir_all(tmpdir.path())?;
         Ok(())
     }
-}
 
-/// CLI main function.
-fn main() -> Result<()> {
-    internal_main(Opt::from_args())
+    #[test]
+    fn init() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let project = tmpdir.path().join("proj");
+        let opts = |args: Vec<&str>| Opt::from_iter_safe(args).unwrap();
+        internal_main(opts(vec![
+            "tydi",
+            "init",
+            "proj",
+            "-o",
+            project.to_str().unwrap(),
+        ]))?;
+        std::fs::metadata(project.join("Makefile"))?;
+        std::fs::metadata(project.join("README.md"))?;
+
+        // The example project can be generated.
+        internal_main(opts(vec![
+            "tydi",
+            "generate",
+            "-i",
+            project.join("example.sdf").to_str().unwrap(),
+            "-o",
+            project.join("out").to_str().unwrap(),
+            "--config",
+            project.join(CONFIG_FILE_NAME).to_str().unwrap(),
+            "proj",
+            "vhdl",
+        ]))?;
+        std::fs::metadata(project.join("out/proj/example_pkg.gen.vhd"))?;
+
+        // Existing projects are not overwritten.
+        assert!(internal_main(opts(vec![
+            "tydi",
+            "init",
+            "proj",
+            "-o",
+            project.to_str().unwrap(),
+        ]))
+        .is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,432 @@
+//! Interface differences.
+//!
+//! Compares two versions of a library or streamlet at the physical level, and reports the
+//! differences of their interfaces: added and removed streamlets, interfaces, streams and
+//! signals, and changes of modes, widths and physical stream parameters. This helps reviewing
+//! changes to hardware interfaces, since every difference requires changes to the hardware that
+//! uses them.
+//!
+//! Differences are classified by their [`Compatibility`], such that releases of a library can
+//! follow semantic versioning: only adding streamlets is compatible with users of a previous
+//! version, since every other change alters the ports that existing instances must connect.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::design::diff::{diff_streamlets, Change, Compatibility};
+//!
+//! let old = tydi::parser::nom::streamlet("Streamlet x (a : in Stream<Bits<8>>)").unwrap().1;
+//! let new = tydi::parser::nom::streamlet("Streamlet x (a : in Stream<Bits<16>>)").unwrap().1;
+//! let diff = diff_streamlets(&old, &new);
+//! assert_eq!(diff.len(), 1);
+//! assert_eq!(diff[0].to_string(), "x.a: width of data changed from 8 to 16");
+//! assert_eq!(Compatibility::of(&diff), Compatibility::Breaking);
+//! ```
+//!
+//! [`Compatibility`]: enum.Compatibility.html
+
+use crate::design::{Library, Mode, Streamlet};
+use crate::physical::{Fields, PhysicalStream};
+use crate::traits::Identify;
+use crate::{NonNegative, PathName};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A change of an interface.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A streamlet was added.
+    AddedStreamlet,
+    /// A streamlet was removed.
+    RemovedStreamlet,
+    /// An interface was added.
+    AddedInterface,
+    /// An interface was removed.
+    RemovedInterface,
+    /// The mode of an interface changed.
+    Mode(Mode, Mode),
+    /// A physical stream was added.
+    AddedStream,
+    /// A physical stream was removed.
+    RemovedStream,
+    /// The number of element lanes of a physical stream changed.
+    ElementLanes(NonNegative, NonNegative),
+    /// The dimensionality of a physical stream changed.
+    Dimensionality(NonNegative, NonNegative),
+    /// The complexity of a physical stream changed.
+    Complexity(String, String),
+    /// A signal with the given identifier and width was added.
+    AddedSignal(String, NonNegative),
+    /// A signal with the given identifier and width was removed.
+    RemovedSignal(String, NonNegative),
+    /// The width of the signal with the given identifier changed.
+    SignalWidth(String, NonNegative, NonNegative),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::AddedStreamlet => write!(f, "streamlet added"),
+            Change::RemovedStreamlet => write!(f, "streamlet removed"),
+            Change::AddedInterface => write!(f, "interface added"),
+            Change::RemovedInterface => write!(f, "interface removed"),
+            Change::Mode(old, new) => write!(f, "mode changed from {} to {}", old, new),
+            Change::AddedStream => write!(f, "stream added"),
+            Change::RemovedStream => write!(f, "stream removed"),
+            Change::ElementLanes(old, new) => {
+                write!(f, "element lanes changed from {} to {}", old, new)
+            }
+            Change::Dimensionality(old, new) => {
+                write!(f, "dimensionality changed from {} to {}", old, new)
+            }
+            Change::Complexity(old, new) => {
+                write!(f, "complexity changed from {} to {}", old, new)
+            }
+            Change::AddedSignal(name, width) => {
+                write!(f, "signal {} added with width {}", name, width)
+            }
+            Change::RemovedSignal(name, width) => {
+                write!(f, "signal {} removed with width {}", name, width)
+            }
+            Change::SignalWidth(name, old, new) => {
+                write!(f, "width of {} changed from {} to {}", name, old, new)
+            }
+        }
+    }
+}
+
+impl Change {
+    /// Returns true if this change breaks hardware that uses the previous version of the
+    /// interface. Only added streamlets are not breaking.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, Change::AddedStreamlet)
+    }
+}
+
+/// The compatibility of two versions of a library or streamlet, in terms of semantic versioning.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    /// The interfaces are identical. At most a patch release is required.
+    Identical,
+    /// The new version only extends the old version. At least a minor release is required.
+    Compatible,
+    /// The new version breaks users of the old version. A major release is required.
+    Breaking,
+}
+
+impl Compatibility {
+    /// Returns the compatibility of two versions with the given differences.
+    pub fn of(differences: &[Difference]) -> Self {
+        differences
+            .iter()
+            .map(|d| {
+                if d.is_breaking() {
+                    Compatibility::Breaking
+                } else {
+                    Compatibility::Compatible
+                }
+            })
+            .max()
+            .unwrap_or(Compatibility::Identical)
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Identical => write!(f, "identical"),
+            Compatibility::Compatible => write!(f, "compatible"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+/// A change of an interface, at the path of the streamlet, interface or stream that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    path: Vec<String>,
+    change: Change,
+}
+
+impl Difference {
+    /// Returns the path of the streamlet, interface or stream that changed, starting with the
+    /// identifier of the streamlet.
+    pub fn path(&self) -> &[String] {
+        self.path.as_slice()
+    }
+
+    /// Returns the change.
+    pub fn change(&self) -> &Change {
+        &self.change
+    }
+
+    /// Returns true if the change breaks hardware that uses the previous version of the
+    /// interface.
+    pub fn is_breaking(&self) -> bool {
+        self.change.is_breaking()
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.join("."), self.change)
+    }
+}
+
+/// Returns the path of a stream or signal within an interface.
+fn extend(prefix: &[String], path: &PathName) -> Vec<String> {
+    prefix
+        .iter()
+        .cloned()
+        .chain(path.into_iter().map(|n| n.to_string()))
+        .collect()
+}
+
+/// Compares the signals with the given identifiers and widths, in order.
+fn diff_signals(
+    path: &[String],
+    old: Vec<(String, NonNegative)>,
+    new: Vec<(String, NonNegative)>,
+    result: &mut Vec<Difference>,
+) {
+    let mut push = |change| {
+        result.push(Difference {
+            path: path.to_vec(),
+            change,
+        })
+    };
+    for (name, width) in &old {
+        match new.iter().find(|(n, _)| n == name) {
+            None => push(Change::RemovedSignal(name.clone(), *width)),
+            Some((_, w)) if w != width => push(Change::SignalWidth(name.clone(), *width, *w)),
+            Some(_) => {}
+        }
+    }
+    for (name, width) in &new {
+        if !old.iter().any(|(n, _)| n == name) {
+            push(Change::AddedSignal(name.clone(), *width));
+        }
+    }
+}
+
+/// Compares two physical streams at the same path.
+fn diff_streams(
+    path: Vec<String>,
+    old: &PhysicalStream,
+    new: &PhysicalStream,
+    result: &mut Vec<Difference>,
+) {
+    if old.element_lanes() != new.element_lanes() {
+        result.push(Difference {
+            path: path.clone(),
+            change: Change::ElementLanes(old.element_lanes().get(), new.element_lanes().get()),
+        });
+    }
+    if old.dimensionality() != new.dimensionality() {
+        result.push(Difference {
+            path: path.clone(),
+            change: Change::Dimensionality(old.dimensionality(), new.dimensionality()),
+        });
+    }
+    if old.complexity() != new.complexity() {
+        result.push(Difference {
+            path: path.clone(),
+            change: Change::Complexity(old.complexity().to_string(), new.complexity().to_string()),
+        });
+    }
+    let signals = |stream: &PhysicalStream| {
+        stream
+            .signal_list()
+            .into_iter()
+            .map(|s| (s.identifier().to_string(), s.bit_count()))
+            .collect()
+    };
+    diff_signals(&path, signals(old), signals(new), result);
+}
+
+/// Returns the differences between the interfaces of two versions of a streamlet.
+pub fn diff_streamlets(old: &Streamlet, new: &Streamlet) -> Vec<Difference> {
+    let mut result = vec![];
+    for interface in old.interfaces() {
+        let path = vec![
+            old.identifier().to_string(),
+            interface.identifier().to_string(),
+        ];
+        let other = match new
+            .interfaces()
+            .find(|i| i.identifier() == interface.identifier())
+        {
+            Some(other) => other,
+            None => {
+                result.push(Difference {
+                    path,
+                    change: Change::RemovedInterface,
+                });
+                continue;
+            }
+        };
+        if interface.mode() != other.mode() {
+            result.push(Difference {
+                path: path.clone(),
+                change: Change::Mode(interface.mode(), other.mode()),
+            });
+        }
+
+        let (old_synth, new_synth) = (interface.typ().synthesize(), other.typ().synthesize());
+        // Signals that are not part of a stream are named after their path, or after the
+        // interface if they have none.
+        let signals = |fields: &Fields| {
+            fields
+                .iter()
+                .map(|(p, w)| {
+                    let name = if p.is_empty() {
+                        interface.identifier().to_string()
+                    } else {
+                        p.join(".")
+                    };
+                    (name, w.get())
+                })
+                .collect()
+        };
+        diff_signals(
+            &path,
+            signals(old_synth.signal_fields()),
+            signals(new_synth.signal_fields()),
+            &mut result,
+        );
+
+        let old_streams = old_synth.streams().collect::<BTreeMap<_, _>>();
+        let new_streams = new_synth.streams().collect::<BTreeMap<_, _>>();
+        for (stream_path, stream) in old_synth.streams() {
+            match new_streams.get(stream_path) {
+                Some(other) => diff_streams(extend(&path, stream_path), stream, other, &mut result),
+                None => result.push(Difference {
+                    path: extend(&path, stream_path),
+                    change: Change::RemovedStream,
+                }),
+            }
+        }
+        for (stream_path, _) in new_synth.streams() {
+            if !old_streams.contains_key(stream_path) {
+                result.push(Difference {
+                    path: extend(&path, stream_path),
+                    change: Change::AddedStream,
+                });
+            }
+        }
+    }
+    for interface in new.interfaces() {
+        if !old
+            .interfaces()
+            .any(|i| i.identifier() == interface.identifier())
+        {
+            result.push(Difference {
+                path: vec![
+                    new.identifier().to_string(),
+                    interface.identifier().to_string(),
+                ],
+                change: Change::AddedInterface,
+            });
+        }
+    }
+    result
+}
+
+/// Returns the differences between the interfaces of the streamlets of two versions of a
+/// library. The identifiers of the libraries themselves are not compared.
+pub fn diff_libraries(old: &Library, new: &Library) -> Vec<Difference> {
+    let mut result = vec![];
+    let (old_streamlets, new_streamlets) = (old.streamlets(), new.streamlets());
+    for streamlet in &old_streamlets {
+        match new_streamlets
+            .iter()
+            .find(|s| s.identifier() == streamlet.identifier())
+        {
+            Some(other) => result.extend(diff_streamlets(streamlet, other)),
+            None => result.push(Difference {
+                path: vec![streamlet.identifier().to_string()],
+                change: Change::RemovedStreamlet,
+            }),
+        }
+    }
+    for streamlet in &new_streamlets {
+        if !old_streamlets
+            .iter()
+            .any(|s| s.identifier() == streamlet.identifier())
+        {
+            result.push(Difference {
+                path: vec![streamlet.identifier().to_string()],
+                change: Change::AddedStreamlet,
+            });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Name, Result, UniquelyNamedBuilder};
+
+    fn library(streamlets: &[&str]) -> Result<Library> {
+        Library::from_builder(
+            Name::try_new("lib")?,
+            UniquelyNamedBuilder::new().with_items(
+                streamlets
+                    .iter()
+                    .map(|s| crate::parser::nom::streamlet(s).unwrap().1),
+            ),
+        )
+    }
+
+    #[test]
+    fn libraries() -> Result<()> {
+        let old = library(&[
+            "Streamlet x (a : in Stream<Bits<8>, d=1>, b : out Bits<2>)",
+            "Streamlet y (a : in Bits<1>)",
+        ])?;
+        let new = library(&[
+            "Streamlet x (a : in Stream<Bits<8>, t=2, c=7>, b : in Bits<4>, c : out Bits<1>)",
+            "Streamlet z (a : in Bits<1>)",
+        ])?;
+        assert_eq!(
+            diff_libraries(&old, &new)
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "x.a: element lanes changed from 1 to 2",
+                "x.a: dimensionality changed from 1 to 0",
+                "x.a: complexity changed from 4 to 7",
+                "x.a: width of data changed from 8 to 16",
+                "x.a: signal last removed with width 1",
+                "x.a: width of strb changed from 1 to 2",
+                "x.a: signal stai added with width 1",
+                "x.a: signal endi added with width 1",
+                "x.b: mode changed from out to in",
+                "x.b: width of b changed from 2 to 4",
+                "x.c: interface added",
+                "y: streamlet removed",
+                "z: streamlet added",
+            ]
+        );
+        assert!(diff_libraries(&old, &old).is_empty());
+
+        assert_eq!(Compatibility::of(&[]), Compatibility::Identical);
+        assert_eq!(
+            Compatibility::of(&diff_libraries(
+                &new,
+                &library(&["Streamlet z (a : in Bits<1>)"])?
+            )),
+            Compatibility::Breaking
+        );
+        assert_eq!(
+            Compatibility::of(&diff_libraries(
+                &library(&["Streamlet z (a : in Bits<1>)"])?,
+                &new
+            )),
+            Compatibility::Compatible
+        );
+        Ok(())
+    }
+}
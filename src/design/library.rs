@@ -4,10 +4,12 @@
 //! output (e.g. a package in VHDL).
 
 use crate::design::Streamlet;
-use crate::error::Error::{FileIOError, ParsingError};
-use crate::parser::nom::list_of_streamlets;
+use crate::error::Error::FileIOError;
+use crate::logical::{LogicalType, SynthesisOptions};
+use crate::parser::nom::list_of_declarations_recovering;
 use crate::traits::Identify;
-use crate::{Name, Result, UniquelyNamedBuilder};
+use crate::{Error, Name, PathName, Result, UniquelyNamedBuilder};
+use indexmap::IndexMap;
 use log::debug;
 use std::path::Path;
 
@@ -16,6 +18,7 @@ use std::path::Path;
 pub struct Library {
     name: Name,
     streamlets: Vec<Streamlet>,
+    named_types: IndexMap<Name, LogicalType>,
 }
 
 impl crate::traits::Identify for Library {
@@ -34,9 +37,97 @@ impl Library {
         Ok(Library {
             name,
             streamlets: builder.finish()?,
+            named_types: IndexMap::new(),
         })
     }
 
+    /// Return this library with the given named types declared. Returns an error if a type with
+    /// the same name was already declared, or if an interface refers to a named type that is not
+    /// declared or does not match the declaration.
+    pub fn with_named_types(
+        mut self,
+        types: impl IntoIterator<Item = (Name, LogicalType)>,
+    ) -> Result<Self> {
+        for (name, typ) in types {
+            if self.named_types.contains_key(&name) {
+                return Err(Error::UnexpectedDuplicate);
+            }
+            self.named_types.insert(name, typ);
+        }
+        for streamlet in &self.streamlets {
+            for interface in streamlet.interfaces() {
+                if let Some(name) = interface.type_name() {
+                    match self.named_types.get(name) {
+                        Some(typ) if typ == &interface.typ() => {}
+                        Some(_) => {
+                            return Err(Error::InterfaceError(format!(
+                            "Type of interface {} of streamlet {} does not match named type {}.",
+                            interface.identifier(),
+                            streamlet.identifier(),
+                            name
+                        )))
+                        }
+                        None => {
+                            return Err(Error::InterfaceError(format!(
+                                "Interface {} of streamlet {} refers to undeclared type {}.",
+                                interface.identifier(),
+                                streamlet.identifier(),
+                                name
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Return an iterator over the named types declared in this library.
+    pub fn named_types(&self) -> impl Iterator<Item = (&Name, &LogicalType)> {
+        self.named_types.iter()
+    }
+
+    /// Return the named type with the given name, if it is declared in this library.
+    pub fn named_type(&self, name: &Name) -> Option<&LogicalType> {
+        self.named_types.get(name)
+    }
+
+    /// Resolve the throughput of the streams of all streamlets and named types of this library,
+    /// such that they are lowered to the element lanes selected by `options`. The paths of the
+    /// streams in `options` start with the names of their streamlet and interface.
+    ///
+    /// Named types are resolved together with the interfaces that still refer to them. Returns
+    /// an error if those interfaces resolve to a different type than their named type, since
+    /// they would no longer share its types. The library is left unchanged on errors.
+    pub fn resolve_throughput(&mut self, options: &SynthesisOptions) -> Result<()> {
+        let mut streamlets = self.streamlets.clone();
+        for streamlet in streamlets.iter_mut() {
+            // Streamlet identifiers are valid names.
+            let name = PathName::try_new(vec![streamlet.identifier()]).unwrap();
+            streamlet.resolve_throughput(&options.scoped(&name));
+        }
+        let options = options.without_lanes();
+        let mut named_types = self.named_types.clone();
+        for (name, typ) in named_types.iter_mut() {
+            *typ = typ.resolve_throughput(&options);
+            for streamlet in &streamlets {
+                for interface in streamlet.interfaces() {
+                    if interface.type_name() == Some(name) && &interface.typ() != typ {
+                        return Err(Error::InterfaceError(format!(
+                            "Type of interface {} of streamlet {} resolves to a different type than named type {}.",
+                            interface.identifier(),
+                            streamlet.identifier(),
+                            name
+                        )));
+                    }
+                }
+            }
+        }
+        self.streamlets = streamlets;
+        self.named_types = named_types;
+        Ok(())
+    }
+
     /// Construct a Library from a Streamlet Definition File.
     pub fn from_file(path: &Path) -> Result<Self> {
         if path.is_dir() {
@@ -51,13 +142,11 @@ impl Library {
                 path.to_str()
                     .ok_or_else(|| FileIOError("Invalid path.".to_string()))?
             );
-            let streamlets: Vec<Streamlet> = list_of_streamlets(
-                std::fs::read_to_string(&path)
-                    .map_err(|e| FileIOError(e.to_string()))?
-                    .as_str(),
-            )
-            .map_err(|e| ParsingError(e.to_string()))?
-            .1;
+            let (types, streamlets, diagnostics) =
+                list_of_declarations_recovering(std::fs::read_to_string(path)?.as_str());
+            if !diagnostics.is_empty() {
+                return Err(Error::Parse(diagnostics));
+            }
             debug!("Parsed streamlets: {}", {
                 let sln: Vec<&str> = streamlets.iter().map(|s| s.identifier()).collect();
                 sln.join(", ")
@@ -70,7 +159,8 @@ impl Library {
                         .unwrap(),
                 )?,
                 UniquelyNamedBuilder::new().with_items(streamlets),
-            )
+            )?
+            .with_named_types(types)
         }
     }
 }
@@ -78,6 +168,7 @@ impl Library {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::Positive;
 
     #[test]
     pub(crate) fn test_library() -> Result<()> {
@@ -91,6 +182,51 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn resolve_named_types() -> Result<()> {
+        let (types, streamlets, diagnostics) = list_of_declarations_recovering(concat!(
+            "Type pixels = Stream<Bits<8>, t=2.0>\n",
+            "Streamlet a ( x: in pixels, y: out pixels )\n",
+            "Streamlet b ( x: in pixels )",
+        ));
+        assert!(diagnostics.is_empty());
+        let mut builder = UniquelyNamedBuilder::new();
+        for streamlet in streamlets {
+            builder.add_item(streamlet);
+        }
+        let mut lib =
+            Library::from_builder(Name::try_new("lib")?, builder)?.with_named_types(types)?;
+        let pixels = Name::try_new("pixels")?;
+
+        // Interfaces that share a named type, but get different lanes of their own, no longer
+        // refer to it, so their types are not shared with other interfaces.
+        let options = SynthesisOptions::default()
+            .with_lanes(
+                PathName::try_new(vec!["a", "x"])?,
+                Positive::new(4).unwrap(),
+            )
+            .with_lanes(
+                PathName::try_new(vec!["a", "y"])?,
+                Positive::new(1).unwrap(),
+            );
+        lib.resolve_throughput(&options)?;
+        let lanes = |typ: LogicalType| typ.synthesize().streams().next().unwrap().1.element_lanes();
+        let streamlets = lib.streamlets();
+        let interfaces = streamlets
+            .iter()
+            .flat_map(|s| s.interfaces().map(move |i| (s.identifier(), i)))
+            .collect::<Vec<_>>();
+        let (ax, ay, bx) = (interfaces[0].1, interfaces[1].1, interfaces[2].1);
+        assert_eq!(interfaces[2].0, "b");
+        assert_eq!((ax.type_name(), ay.type_name()), (None, None));
+        assert_eq!(lanes(ax.typ()).get(), 4);
+        assert_eq!(lanes(ay.typ()).get(), 1);
+        assert_eq!(bx.type_name(), Some(&pixels));
+        assert_eq!(lib.named_type(&pixels), Some(&bx.typ()));
+        assert_eq!(lanes(bx.typ()).get(), 2);
+        Ok(())
+    }
+
     /// Libraries that can be used for testing purposes throughout the crate.
     pub(crate) mod libs {
         use super::*;
@@ -99,6 +235,7 @@ pub mod tests {
             Library {
                 name: Name::try_new("lib").unwrap(),
                 streamlets: vec![],
+                named_types: IndexMap::new(),
             }
         }
     }
@@ -1,6 +1,7 @@
 //! Constructs that are used to generate hardware designs, that are not
 //! part of the specification (yet).
 
+pub mod diff;
 pub mod library;
 pub mod project;
 pub mod streamlet;
@@ -1,7 +1,8 @@
-use crate::design::Library;
+use crate::design::{Library, Streamlet};
+use crate::logical::{LogicalType, SynthesisOptions};
 use crate::util::UniquelyNamedBuilder;
-use crate::Result;
-use crate::{Identify, Name};
+use crate::{Error, Result};
+use crate::{Identify, Name, NonNegative, PathName};
 
 /// A collection of Streamlets.
 #[derive(Clone, Debug, PartialEq)]
@@ -29,6 +30,109 @@ impl Project {
     pub fn libraries(&self) -> impl Iterator<Item = &Library> {
         self.libraries.iter()
     }
+
+    /// Resolve the throughput of the streams of all libraries of this project, such that they are
+    /// lowered to the element lanes selected by `options`. The paths of the streams in `options`
+    /// start with the names of their library, streamlet and interface.
+    ///
+    /// Returns an error if `options` selects the number of lanes of a stream that does not
+    /// exist, or the lowering of a Union that does not exist. The project is left unchanged on
+    /// errors.
+    pub fn resolve_throughput(&mut self, options: &SynthesisOptions) -> Result<()> {
+        for (path, _) in options.lanes() {
+            if !self.exists(path, |typ, path| {
+                typ.split_streams()
+                    .stream(&PathName::new(path.iter().cloned()))
+                    .is_some()
+            }) {
+                return Err(Error::InvalidArgument(format!(
+                    "cannot select the element lanes of {}, there is no such stream",
+                    dotted(path)
+                )));
+            }
+        }
+        for (path, _) in options.unions() {
+            if !self.exists(path, |typ, path| typ.has_union_at(path)) {
+                return Err(Error::InvalidArgument(format!(
+                    "cannot select the lowering of {}, there is no such Union",
+                    dotted(path)
+                )));
+            }
+        }
+        let mut libraries = self.libraries.clone();
+        for lib in libraries.iter_mut() {
+            // Library identifiers are valid names.
+            let name = PathName::try_new(vec![lib.identifier()]).unwrap();
+            lib.resolve_throughput(&options.scoped(&name))?;
+        }
+        self.libraries = libraries;
+        Ok(())
+    }
+
+    /// Returns true if `path` starts with the names of a library, streamlet and interface of this
+    /// project, and `f` returns true for the type of that interface and the rest of the path.
+    fn exists(&self, path: &PathName, f: impl Fn(&LogicalType, &[Name]) -> bool) -> bool {
+        let names = path.as_ref();
+        names.len() >= 3
+            && self
+                .libraries()
+                .filter(|lib| lib.identifier() == &*names[0])
+                .flat_map(|lib| lib.streamlets())
+                .filter(|streamlet| streamlet.identifier() == &*names[1])
+                .any(|streamlet| {
+                    streamlet.interfaces().any(|interface| {
+                        interface.identifier() == &*names[2] && f(&interface.typ(), &names[3..])
+                    })
+                })
+    }
+
+    /// Check that the directions of the physical streams of all streamlets in this project agree
+    /// with the modes of their interfaces.
+    ///
+    /// Returns an error listing every interface of which all streams flow against its mode.
+    pub fn check_modes(&self) -> Result<()> {
+        let violations = self.violations(|s| s.mode_violations());
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InterfaceError(violations.join("; ")))
+        }
+    }
+
+    /// Check the bit counts of the interfaces of all streamlets in this project.
+    ///
+    /// Returns an error listing every physical stream of which the bit count overflows, and
+    /// every interface of which the total bit count exceeds `max_width`, if given.
+    pub fn check_widths(&self, max_width: Option<NonNegative>) -> Result<()> {
+        let violations = self.violations(|s| s.width_violations(max_width));
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::WidthError(violations.join("; ")))
+        }
+    }
+
+    /// Returns the violations of all streamlets in this project, prefixed with their library.
+    fn violations(&self, f: impl Fn(&Streamlet) -> Vec<String>) -> Vec<String> {
+        self.libraries()
+            .flat_map(|lib| {
+                lib.streamlets()
+                    .iter()
+                    .flat_map(&f)
+                    .map(|v| format!("{}.{}", lib.identifier(), v))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Returns the names of `path` joined by periods, like the paths of the command line.
+fn dotted(path: &PathName) -> String {
+    path.as_ref()
+        .iter()
+        .map(|n| &**n)
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 #[cfg(test)]
@@ -46,4 +150,63 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn resolve_throughput_paths() -> Result<()> {
+        use crate::logical::UnionLowering;
+        use crate::Positive;
+
+        let streamlet = crate::parser::nom::streamlet(
+            "Streamlet s (a : in Stream<Group<u: Union<p: Bits<8>, q: Bits<4>>, b: Bits<1>>>)",
+        )
+        .unwrap()
+        .1;
+        let project = Project::from_builder(
+            Name::try_new("proj")?,
+            UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                Name::try_new("lib")?,
+                UniquelyNamedBuilder::new().with_items(vec![streamlet]),
+            )?]),
+        )?;
+        let path = |names: &[&str]| PathName::try_new(names.to_vec());
+        let resolve = |options: SynthesisOptions| project.clone().resolve_throughput(&options);
+
+        resolve(
+            SynthesisOptions::new()
+                .with_lanes(path(&["lib", "s", "a"])?, Positive::new(2).unwrap()),
+        )?;
+        assert_eq!(
+            resolve(SynthesisOptions::new().with_lanes(
+                path(&["lib", "s", "a", "u"])?,
+                Positive::new(2).unwrap()
+            ))
+            .unwrap_err()
+            .to_string(),
+            "Invalid argument: cannot select the element lanes of lib.s.a.u, there is no such stream"
+        );
+
+        resolve(
+            SynthesisOptions::new()
+                .with_union_lowering_at(path(&["lib", "s", "a", "u"])?, UnionLowering::Streams),
+        )?;
+        for names in [
+            &["lib", "s", "a", "v"][..],
+            &["lib", "s", "a", "b"],
+            &["lib", "s", "a"],
+        ] {
+            assert_eq!(
+                resolve(
+                    SynthesisOptions::new()
+                        .with_union_lowering_at(path(names)?, UnionLowering::Streams)
+                )
+                .unwrap_err()
+                .to_string(),
+                format!(
+                    "Invalid argument: cannot select the lowering of {}, there is no such Union",
+                    names.join(".")
+                )
+            );
+        }
+        Ok(())
+    }
 }
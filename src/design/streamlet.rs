@@ -2,11 +2,14 @@
 //!
 //! A streamlet is a component where every [Interface] has a [LogicalType].
 
-use crate::logical::LogicalType;
-use crate::traits::Identify;
-use crate::util::UniquelyNamedBuilder;
-use crate::{Document, Error, Name, Result};
-use std::convert::TryInto;
+use crate::logical::{Direction, LogicalType, SynthesisOptions};
+use crate::physical::{checked_sum, PhysicalStream};
+use crate::traits::{Identify, Reverse, Reversed};
+use crate::util::{FnvHasher, IndexMap, UniquelyNamedBuilder};
+use crate::{Document, Error, Name, NonNegative, PathName, Result};
+use std::convert::{TryFrom, TryInto};
+use std::fmt::Write;
+use std::hash::Hasher;
 use std::str::FromStr;
 
 /// Streamlet interface mode.
@@ -33,6 +36,24 @@ impl FromStr for Mode {
     }
 }
 
+impl Reverse for Mode {
+    fn reverse(&mut self) {
+        *self = match self {
+            Mode::In => Mode::Out,
+            Mode::Out => Mode::In,
+        };
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::In => write!(f, "in"),
+            Mode::Out => write!(f, "out"),
+        }
+    }
+}
+
 /// A Streamlet interface.
 ///
 /// The names "clk" and "rst" are reserved.
@@ -46,6 +67,10 @@ pub struct Interface {
     typ: LogicalType,
     /// The documentation string of the interface, if any.
     doc: Option<String>,
+    /// The name of the library-level named type of the interface, if it refers to one.
+    type_name: Option<Name>,
+    /// User-defined metadata of the interface.
+    metadata: IndexMap<String, String>,
 }
 
 impl Interface {
@@ -58,6 +83,56 @@ impl Interface {
     pub fn typ(&self) -> LogicalType {
         self.typ.clone()
     }
+
+    /// Return the name of the named type of the interface, if it refers to one.
+    pub fn type_name(&self) -> Option<&Name> {
+        self.type_name.as_ref()
+    }
+
+    /// Return an iterator over the user-defined metadata of the interface, as key-value pairs
+    /// in the order in which they were added.
+    pub fn metadata(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.metadata.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Return this interface with a user-defined metadata entry added, replacing the value of
+    /// an existing entry with the same key. See [Streamlet::with_metadata].
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Return the effective mode of every physical stream of the interface.
+    ///
+    /// Streams flow in the direction of the interface mode, unless their direction is reversed
+    /// with respect to the interface. For example, a stream with `r=Reverse` nested in an `in`
+    /// interface flows out of the streamlet.
+    pub fn stream_modes(&self) -> Vec<(PathName, Mode)> {
+        self.typ
+            .split_streams()
+            .streams()
+            .map(|(path, stream)| match stream {
+                LogicalType::Stream(stream) => (
+                    path.clone(),
+                    match stream.direction() {
+                        Direction::Forward => self.mode,
+                        Direction::Reverse => self.mode.reversed(),
+                    },
+                ),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    /// Return the effective mode of the physical stream at `path`, or the interface mode if
+    /// there is no such stream.
+    pub(crate) fn stream_mode(&self, path: &PathName) -> Mode {
+        self.stream_modes()
+            .into_iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, mode)| mode)
+            .unwrap_or(self.mode)
+    }
 }
 
 impl Identify for Interface {
@@ -109,6 +184,8 @@ impl Interface {
                 mode,
                 typ: t,
                 doc: doc.map(|d| d.to_string()),
+                type_name: None,
+                metadata: IndexMap::default(),
             }),
         }
     }
@@ -117,6 +194,24 @@ impl Interface {
         self.doc = Some(doc.into());
         self
     }
+
+    /// Return this interface, referring to the library-level named type with the given name.
+    ///
+    /// Back-ends use this name for the types they generate for this interface, such that
+    /// interfaces of different streamlets that refer to the same named type share those types.
+    pub fn with_type_name(mut self, name: Name) -> Self {
+        self.type_name = Some(name);
+        self
+    }
+}
+
+impl TryFrom<(Name, Mode, LogicalType)> for Interface {
+    type Error = Error;
+
+    /// Try to construct a new, undocumented interface from its name, mode and type.
+    fn try_from((name, mode, typ): (Name, Mode, LogicalType)) -> Result<Self> {
+        Interface::try_new(name, mode, typ, None)
+    }
 }
 
 impl Document for Interface {
@@ -136,6 +231,8 @@ pub struct Streamlet {
     doc: Option<String>,
     /// Placeholder for future implementation of the streamlet. If this is None, it is a primitive.
     implementation: Option<()>,
+    /// User-defined metadata of the streamlet.
+    metadata: IndexMap<String, String>,
 }
 
 impl Streamlet {
@@ -144,6 +241,43 @@ impl Streamlet {
         self.interfaces.iter()
     }
 
+    /// Return an iterator over the physical streams of all interfaces of this Streamlet, after
+    /// lowering their types with [`LogicalType::synthesize`]. Every item consists of the name of
+    /// the interface, the path of the stream within the interface, the physical stream and its
+    /// effective mode, as returned by [`Interface::stream_modes`].
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::design::Mode;
+    ///
+    /// let (_, streamlet) = tydi::parser::nom::streamlet(
+    ///     "Streamlet x (a : in Stream<Group<b: Bits<8>, c: Stream<Bits<1>, r=Reverse>>>)",
+    /// ).unwrap();
+    /// let streams = streamlet.physical_streams().collect::<Vec<_>>();
+    /// assert_eq!(streams.len(), 2);
+    /// let (interface, path, stream, mode) = &streams[1];
+    /// assert_eq!(interface.to_string(), "a");
+    /// assert_eq!(path.to_string(), "c");
+    /// assert_eq!(stream.data_bit_count(), 1);
+    /// assert_eq!(*mode, Mode::Out);
+    /// ```
+    ///
+    /// [`LogicalType::synthesize`]: ../logical/enum.LogicalType.html#method.synthesize
+    /// [`Interface::stream_modes`]: ./struct.Interface.html#method.stream_modes
+    pub fn physical_streams(
+        &self,
+    ) -> impl Iterator<Item = (&Name, PathName, PhysicalStream, Mode)> + '_ {
+        self.interfaces.iter().flat_map(|interface| {
+            let synth = interface.typ.synthesize();
+            interface
+                .stream_modes()
+                .into_iter()
+                .zip(synth.streams().map(|(_, stream)| stream.clone()))
+                .map(|((path, mode), stream)| (&interface.name, path, stream, mode))
+                .collect::<Vec<_>>()
+        })
+    }
+
     /// Construct a new streamlet from an interface builder that makes sure all interface names
     /// are unique.
     ///
@@ -179,14 +313,289 @@ impl Streamlet {
             interfaces: builder.finish()?,
             doc: doc.map(|d| d.to_string()),
             implementation: None,
+            metadata: IndexMap::default(),
         })
     }
 
+    /// Try to construct a new, undocumented streamlet with the given interfaces. Returns an error
+    /// if the name is invalid or if the names of the interfaces are not unique.
+    ///
+    /// # Example
+    /// ```
+    /// use std::convert::TryInto;
+    /// use tydi::design::{Interface, Mode, Streamlet};
+    /// use tydi::logical::LogicalType;
+    /// use tydi::Name;
+    ///
+    /// let interface: Interface = (Name::try_new("a")?, Mode::In, LogicalType::try_new_bits(8)?)
+    ///     .try_into()?;
+    /// let streamlet = Streamlet::try_new("x", vec![interface.clone()])?;
+    /// assert_eq!(streamlet.interfaces().count(), 1);
+    /// assert!(Streamlet::try_new("x", vec![interface.clone(), interface]).is_err());
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    pub fn try_new(
+        name: impl TryInto<Name, Error = impl Into<Box<dyn std::error::Error>>>,
+        interfaces: impl IntoIterator<Item = Interface>,
+    ) -> Result<Self> {
+        let name = name
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.into().to_string()))?;
+        Streamlet::from_builder(name, interfaces.into_iter().collect(), None)
+    }
+
+    /// Returns a fingerprint of the interface of this streamlet: a 64-bit hash of its name and
+    /// the names, modes and types of its interfaces, that is stable across runs and platforms.
+    /// Documentation does not affect the fingerprint, such that build systems only regenerate
+    /// the sources that depend on a streamlet when its interface changes.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        write!(hasher, "{}(", self.name).expect("hashing cannot fail");
+        for interface in &self.interfaces {
+            write!(
+                hasher,
+                "{}: {} {};",
+                interface.name, interface.mode, interface.typ
+            )
+            .expect("hashing cannot fail");
+        }
+        hasher.finish()
+    }
+
+    /// Return this streamlet with a different name.
+    pub fn with_name(mut self, name: Name) -> Self {
+        self.name = name;
+        self
+    }
+
     /// Return this streamlet with documentation added.
     pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
         self.doc = Some(doc.into());
         self
     }
+
+    /// Return an iterator over the user-defined metadata of the streamlet, as key-value pairs in
+    /// the order in which they were added.
+    pub fn metadata(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.metadata.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Return this streamlet with a user-defined metadata entry added, replacing the value of an
+    /// existing entry with the same key.
+    ///
+    /// Metadata is preserved when lowering streamlets to components. Back-ends emit the entries
+    /// they recognize and ignore all others:
+    /// - The VHDL back-end emits entries with keys of the form `vhdl.<attribute>` as string
+    ///   attributes of the entity, or of the ports of an interface.
+    /// - The Chisel back-end applies `dontTouch` to the IO of a module, or to the ports of an
+    ///   interface, with a `chisel.dont_touch` entry.
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::design::Streamlet;
+    ///
+    /// let (_, streamlet) = tydi::parser::nom::streamlet("Streamlet x (a : in Null)").unwrap();
+    /// let streamlet = streamlet
+    ///     .with_metadata("vendor", "tudelft")
+    ///     .with_metadata("vhdl.keep_hierarchy", "yes");
+    /// assert_eq!(
+    ///     streamlet.metadata().collect::<Vec<_>>(),
+    ///     vec![("vendor", "tudelft"), ("vhdl.keep_hierarchy", "yes")]
+    /// );
+    /// ```
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Rename the interface named `from` to `to`.
+    ///
+    /// Returns an error if there is no interface named `from`, or if `to` is reserved or the name
+    /// of another interface. The streamlet is left unchanged on errors.
+    pub fn rename_interface(
+        &mut self,
+        from: &str,
+        to: impl TryInto<Name, Error = impl Into<Box<dyn std::error::Error>>>,
+    ) -> Result<()> {
+        if !self.interfaces().any(|i| i.identifier() == from) {
+            return Err(Error::InterfaceError(format!(
+                "{} has no interface named {}",
+                self.identifier(),
+                from
+            )));
+        }
+        let to: Name = to
+            .try_into()
+            .map_err(|e| Error::InterfaceError(e.into().to_string()))?;
+        self.rename_interfaces(|name| {
+            if name == from {
+                to.to_string()
+            } else {
+                name.to_string()
+            }
+        })
+    }
+
+    /// Prefix the names of all interfaces of this streamlet with `prefix` and an underscore,
+    /// e.g. to avoid collisions when wrapping third-party streamlets.
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::design::Streamlet;
+    /// use tydi::Identify;
+    ///
+    /// let (_, mut streamlet) =
+    ///     tydi::parser::nom::streamlet("Streamlet x (a : in Null, b : out Null)").unwrap();
+    /// streamlet.prefix_interfaces("ext")?;
+    /// assert_eq!(
+    ///     streamlet.interfaces().map(|i| i.identifier()).collect::<Vec<_>>(),
+    ///     vec!["ext_a", "ext_b"]
+    /// );
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    pub fn prefix_interfaces(&mut self, prefix: &str) -> Result<()> {
+        self.rename_interfaces(|name| format!("{}_{}", prefix, name))
+    }
+
+    /// Rename all interfaces of this streamlet, using `f` to derive their new names.
+    ///
+    /// Returns an error if a new name is invalid or reserved, or if multiple interfaces would get
+    /// the same name. The streamlet is left unchanged on errors.
+    pub fn rename_interfaces(&mut self, f: impl Fn(&Name) -> String) -> Result<()> {
+        let mut interfaces = Vec::with_capacity(self.interfaces.len());
+        for interface in &self.interfaces {
+            let renamed = Interface {
+                name: Name::try_new(f(&interface.name))
+                    .map_err(|e| Error::InterfaceError(e.to_string()))?,
+                ..interface.clone()
+            };
+            if ["clk", "rst"].contains(&renamed.identifier()) {
+                return Err(Error::InterfaceError(format!(
+                    "Name {} forbidden.",
+                    renamed.identifier()
+                )));
+            }
+            if let Some(other) = interfaces
+                .iter()
+                .position(|i: &Interface| i.name == renamed.name)
+            {
+                return Err(Error::InterfaceError(format!(
+                    "{}: renaming {} and {} results in duplicate interface {}",
+                    self.identifier(),
+                    self.interfaces[other].identifier(),
+                    interface.identifier(),
+                    renamed.identifier()
+                )));
+            }
+            interfaces.push(renamed);
+        }
+        self.interfaces = interfaces;
+        Ok(())
+    }
+
+    /// Resolve the throughput of the streams of all interfaces of this streamlet, such that they
+    /// are lowered to the element lanes selected by `options`, see
+    /// [LogicalType::resolve_throughput]. The paths of the streams in `options` start with the
+    /// name of their interface.
+    ///
+    /// Interfaces with streams that have a number of lanes or a Union lowering of their own no
+    /// longer refer to their named type, since their type no longer matches it.
+    pub fn resolve_throughput(&mut self, options: &SynthesisOptions) {
+        for interface in self.interfaces.iter_mut() {
+            let options = options.scoped(&PathName::from(interface.name.clone()));
+            if options.lanes().next().is_some() || options.unions().next().is_some() {
+                interface.type_name = None;
+            }
+            interface.typ = interface.typ.resolve_throughput(&options);
+        }
+    }
+
+    /// Check the bit counts of the interfaces of this streamlet.
+    ///
+    /// Returns an error listing every physical stream of which the bit count overflows, and
+    /// every interface of which the total bit count (including valid and ready signals) exceeds
+    /// `max_width`, if given.
+    pub fn check_widths(&self, max_width: Option<NonNegative>) -> Result<()> {
+        let violations = self.width_violations(max_width);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::WidthError(violations.join("; ")))
+        }
+    }
+
+    /// Check that the directions of the physical streams of this streamlet agree with the modes
+    /// of their interfaces.
+    ///
+    /// Returns an error listing every interface of which all physical streams flow against the
+    /// interface mode, and which has no signals outside of streams. Such an interface is almost
+    /// certainly declared with the wrong mode, since it behaves exactly like an interface of the
+    /// opposite mode.
+    pub fn check_modes(&self) -> Result<()> {
+        let violations = self.mode_violations();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InterfaceError(violations.join("; ")))
+        }
+    }
+
+    /// Returns a description of every mode contradiction of the interfaces of this streamlet.
+    pub(crate) fn mode_violations(&self) -> Vec<String> {
+        self.interfaces()
+            .filter(|interface| {
+                let modes = interface.stream_modes();
+                !modes.is_empty()
+                    && interface.typ.split_streams().signal().is_null()
+                    && modes.iter().all(|(_, mode)| *mode != interface.mode())
+            })
+            .map(|interface| {
+                format!(
+                    "{}.{}: declared {}, but all of its streams flow {}",
+                    self.identifier(),
+                    interface.identifier(),
+                    interface.mode(),
+                    interface.mode().reversed()
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a description of every width violation of the interfaces of this streamlet.
+    pub(crate) fn width_violations(&self, max_width: Option<NonNegative>) -> Vec<String> {
+        let mut result = Vec::new();
+        for interface in self.interfaces() {
+            let name = format!("{}.{}", self.identifier(), interface.identifier());
+            let synth = interface.typ.synthesize();
+            let mut counts: Vec<NonNegative> = synth.signals().map(|(_, b)| b.get()).collect();
+            let mut overflow = false;
+            for (path, stream) in synth.streams() {
+                match stream.checked_bit_count() {
+                    Some(count) => counts.extend(vec![count, 2]),
+                    None => {
+                        overflow = true;
+                        result.push(if path.is_empty() {
+                            format!("{}: bit count overflows", name)
+                        } else {
+                            format!("{}.{}: bit count overflows", name, path)
+                        });
+                    }
+                }
+            }
+            if overflow {
+                continue;
+            }
+            match (checked_sum(counts), max_width) {
+                (None, _) => result.push(format!("{}: total bit count overflows", name)),
+                (Some(total), Some(max)) if total > max => result.push(format!(
+                    "{}: total bit count {} exceeds the maximum of {}",
+                    name, total, max
+                )),
+                _ => (),
+            }
+        }
+        result
+    }
 }
 
 impl Document for Streamlet {
@@ -221,4 +630,114 @@ pub mod tests {
             .unwrap()
         }
     }
+
+    #[test]
+    fn stream_modes() -> Result<()> {
+        let streamlet = crate::parser::nom::streamlet(
+            "Streamlet x (a : in Stream<Group<b: Bits<1>, c: Stream<Bits<1>, r=Reverse>, d: Stream<Group<e: Stream<Bits<1>, r=Reverse>, f: Bits<1>>, r=Reverse>>>)",
+        )
+        .unwrap()
+        .1;
+        let a = streamlet.interfaces().next().unwrap();
+        assert_eq!(
+            a.stream_modes(),
+            vec![
+                (PathName::new_empty(), Mode::In),
+                (PathName::try_new(vec!["c"])?, Mode::Out),
+                (PathName::try_new(vec!["d"])?, Mode::Out),
+                (PathName::try_new(vec!["d", "e"])?, Mode::In),
+            ]
+        );
+        assert!(streamlet.check_modes().is_ok());
+
+        let streamlet = crate::parser::nom::streamlet(
+            "Streamlet x (a : in Stream<Bits<1>, r=Reverse>, b : out Group<c: Bits<1>, d: Stream<Bits<1>, r=Reverse>>)",
+        )
+        .unwrap()
+        .1;
+        assert_eq!(
+            streamlet.check_modes().unwrap_err().to_string(),
+            "Interface error: x.a: declared in, but all of its streams flow out"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rename_interfaces() -> Result<()> {
+        let mut streamlet = streamlets::nulls_streamlet("x");
+        streamlet.rename_interface("a", "c")?;
+        assert_eq!(
+            streamlet
+                .interfaces()
+                .map(|i| i.identifier())
+                .collect::<Vec<_>>(),
+            vec!["c", "b"]
+        );
+        assert_eq!(
+            streamlet.rename_interface("c", "b"),
+            Err(Error::InterfaceError(
+                "x: renaming c and b results in duplicate interface b".to_string()
+            ))
+        );
+        assert_eq!(
+            streamlet.rename_interface("a", "d"),
+            Err(Error::InterfaceError(
+                "x has no interface named a".to_string()
+            ))
+        );
+        assert!(streamlet.rename_interface("c", "clk").is_err());
+        assert!(streamlet.prefix_interfaces("_").is_err());
+        assert_eq!(streamlet, {
+            let mut s = streamlets::nulls_streamlet("x");
+            s.rename_interface("a", "c")?;
+            s
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn check_widths() -> Result<()> {
+        let streamlet = crate::parser::nom::streamlet(
+            "Streamlet x (a : in Stream<Bits<8>>, b : out Stream<Group<c: Bits<8>, d: Stream<Bits<4>, t=2>>>)",
+        )
+        .unwrap()
+        .1;
+        assert!(streamlet.check_widths(None).is_ok());
+        assert!(streamlet.check_widths(Some(20)).is_ok());
+        assert_eq!(
+            streamlet.check_widths(Some(10)).unwrap_err().to_string(),
+            "Width error: x.b: total bit count 20 exceeds the maximum of 10"
+        );
+
+        let streamlet = crate::parser::nom::streamlet(
+            "Streamlet x (a : in Stream<Bits<4294967295>, t=2>, b : in Group<c: Bits<4294967295>, d: Bits<1>>)",
+        )
+        .unwrap()
+        .1;
+        assert_eq!(
+            streamlet.check_widths(None).unwrap_err().to_string(),
+            "Width error: x.a: bit count overflows; x.b: total bit count overflows"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fingerprint() -> Result<()> {
+        let streamlet = |src: &str| crate::parser::nom::streamlet(src).unwrap().1;
+        let a = streamlet("Streamlet x (a : in Bits<8>, b : out Stream<Bits<1>>)");
+        assert_eq!(
+            a.fingerprint(),
+            streamlet("/// doc\nStreamlet x (/// doc\na : in Bits<8>, b : out Stream<Bits<1>>)")
+                .fingerprint()
+        );
+        assert_ne!(
+            a.fingerprint(),
+            streamlet("Streamlet x (a : in Bits<8>, b : in Stream<Bits<1>>)").fingerprint()
+        );
+        assert_ne!(
+            a.fingerprint(),
+            streamlet("Streamlet y (a : in Bits<8>, b : out Stream<Bits<1>>)").fingerprint()
+        );
+        Ok(())
+    }
 }
@@ -1,6 +1,15 @@
 //! Error variants.
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::{error, fmt, result};
 use log::SetLoggerError;
-use std::{error, fmt, result};
+
+#[cfg(feature = "parser")]
+use crate::parser::nom::Diagnostic;
+#[cfg(feature = "parser")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// Result type with [`Error`] variants.
 ///
@@ -28,6 +37,65 @@ pub enum Error {
     BackEndError(String),
     /// Forbidden interface name.
     InterfaceError(String),
+    /// Invalid configuration.
+    ConfigError(String),
+    /// Use of an experimental feature that is not enabled.
+    FeatureDisabled(String),
+    /// Bit counts that overflow or exceed a configured limit.
+    WidthError(String),
+    /// An error with a description of what was being done when the source error occurred.
+    Context(String, Box<Error>),
+    /// An I/O error, which is the source of this error.
+    #[cfg(feature = "std")]
+    Io(IoError),
+    /// Syntax errors in a Streamlet Definition File, of which the first is the source of this
+    /// error.
+    #[cfg(feature = "parser")]
+    Parse(Vec<Diagnostic>),
+}
+
+/// A shared [`std::io::Error`], such that [`Error`] can be cloned and compared. I/O errors
+/// compare equal if their kinds and descriptions are equal.
+///
+/// [`Error`]: ./enum.Error.html
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct IoError(Arc<std::io::Error>);
+
+#[cfg(feature = "std")]
+impl IoError {
+    /// Returns the underlying I/O error.
+    pub fn get_ref(&self) -> &std::io::Error {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Error {
+    /// Returns the innermost source of this error, skipping all context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::{Context, Error, Result};
+    ///
+    /// let result: Result<()> = Err(Error::UnexpectedDuplicate);
+    /// let err = result.context("adding streamlet").unwrap_err();
+    /// assert_eq!(err.to_string(), "adding streamlet");
+    /// assert_eq!(err.root_cause(), &Error::UnexpectedDuplicate);
+    /// ```
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context(_, source) => source.root_cause(),
+            _ => self,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -43,11 +111,68 @@ impl fmt::Display for Error {
             Error::InvalidTarget(ref msg) => write!(f, "Invalid target: {}", msg),
             Error::BackEndError(ref msg) => write!(f, "Back-end error: {}", msg),
             Error::InterfaceError(ref msg) => write!(f, "Interface error: {}", msg),
+            Error::ConfigError(ref msg) => write!(f, "Configuration error: {}", msg),
+            Error::FeatureDisabled(ref msg) => {
+                write!(f, "Experimental feature disabled: {}", msg)
+            }
+            Error::WidthError(ref msg) => write!(f, "Width error: {}", msg),
+            Error::Context(ref msg, _) => write!(f, "{}", msg),
+            #[cfg(feature = "std")]
+            Error::Io(ref e) => write!(f, "File I/O error: {}", e.get_ref()),
+            #[cfg(feature = "parser")]
+            Error::Parse(ref diagnostics) => {
+                write!(f, "Parsing error: ")?;
+                for (index, diagnostic) in diagnostics.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Context(_, source) => Some(source.as_ref()),
+            #[cfg(feature = "std")]
+            Error::Io(e) => Some(e.get_ref()),
+            #[cfg(feature = "parser")]
+            Error::Parse(diagnostics) => diagnostics
+                .first()
+                .map(|d| d as &(dyn error::Error + 'static)),
+            _ => None,
         }
     }
 }
 
-impl error::Error for Error {}
+/// Extension trait to add context to the error of a [`Result`].
+///
+/// [`Result`]: ./type.Result.html
+pub trait Context<T> {
+    /// Wrap the error, if any, in an [`Error::Context`] with the given description.
+    ///
+    /// [`Error::Context`]: ./enum.Error.html#variant.Context
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    /// Wrap the error, if any, in an [`Error::Context`] with a lazily evaluated description.
+    ///
+    /// [`Error::Context`]: ./enum.Error.html#variant.Context
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T>;
+}
+
+impl<T> Context<T> for Result<T> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|e| Error::Context(context.into(), Box::new(e)))
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|e| Error::Context(context(), Box::new(e)))
+    }
+}
 
 impl From<Box<dyn error::Error>> for Error {
     fn from(error: Box<dyn error::Error>) -> Self {
@@ -59,9 +184,10 @@ impl From<Box<dyn error::Error>> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
-        Error::FileIOError(e.to_string())
+        Error::Io(IoError(Arc::new(e)))
     }
 }
 
@@ -82,4 +208,36 @@ mod tests {
         assert_eq!(a.to_string(), "Invalid argument: test");
         assert_eq!(b.to_string(), "Unexpected duplicate");
     }
+
+    #[test]
+    fn context() {
+        use core::error::Error as _;
+
+        let err = Err::<(), _>(Error::ParsingError("x".to_string()))
+            .context("parsing a.sdf")
+            .with_context(|| "loading project".to_string())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "loading project");
+        assert_eq!(err.source().unwrap().to_string(), "parsing a.sdf");
+        assert_eq!(
+            err.source().unwrap().source().unwrap().to_string(),
+            "Parsing error: x"
+        );
+        assert_eq!(err.root_cause(), &Error::ParsingError("x".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn io_source() {
+        use core::error::Error as _;
+
+        let err = Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "gone"));
+        assert_eq!(err.to_string(), "File I/O error: gone");
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert_eq!(err.clone(), err);
+    }
 }
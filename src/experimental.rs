@@ -0,0 +1,184 @@
+//! Experimental features.
+//!
+//! Subsystems that are still under development are registered here as a [Feature]. They are
+//! disabled by default, and have to be enabled explicitly for every run through a set of
+//! [Features], e.g. using the `--enable-feature` option of the command-line interface.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::experimental::{Feature, Features};
+//!
+//! let mut features = Features::default();
+//! assert!(features.require(Feature::Firrtl).is_err());
+//!
+//! features.enable("firrtl".parse()?);
+//! assert!(features.require(Feature::Firrtl).is_ok());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::util::suggest;
+use crate::{Error, Result};
+use log::warn;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// An experimental feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    /// The FIRRTL back-end.
+    Firrtl,
+    /// Lowering the variants of Unions to child streams.
+    UnionStreams,
+}
+
+impl Feature {
+    /// All experimental features.
+    pub const ALL: &'static [Feature] = &[Feature::Firrtl, Feature::UnionStreams];
+
+    /// Returns the name used to enable this feature.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::Firrtl => "firrtl",
+            Feature::UnionStreams => "union-streams",
+        }
+    }
+
+    /// Returns a short description of this feature.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Feature::Firrtl => "FIRRTL back-end",
+            Feature::UnionStreams => "Lowering unions to streams",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let names = Feature::ALL.iter().map(|f| f.name());
+        Feature::ALL
+            .iter()
+            .find(|f| f.name() == input)
+            .copied()
+            .ok_or_else(|| {
+                Error::InvalidArgument(match suggest(input, names.clone()) {
+                    Some(s) => format!(
+                        "unknown experimental feature \"{}\", did you mean \"{}\"?",
+                        input, s
+                    ),
+                    None => format!(
+                        "unknown experimental feature \"{}\", expected one of: {}",
+                        input,
+                        names.collect::<Vec<_>>().join(", ")
+                    ),
+                })
+            })
+    }
+}
+
+/// A set of enabled experimental features.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Features(BTreeSet<Feature>);
+
+impl Features {
+    /// Enable a feature.
+    pub fn enable(&mut self, feature: Feature) {
+        self.0.insert(feature);
+    }
+
+    /// Return this set with the feature enabled.
+    pub fn with(mut self, feature: Feature) -> Self {
+        self.enable(feature);
+        self
+    }
+
+    /// Returns true if the feature is enabled.
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.0.contains(&feature)
+    }
+
+    /// Returns an iterator over the enabled features, in a stable order.
+    pub fn iter(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Check whether a feature may be used. Logs a warning when it is enabled, and returns an
+    /// error explaining how to enable it otherwise.
+    pub fn require(&self, feature: Feature) -> Result<()> {
+        if self.is_enabled(feature) {
+            warn!(
+                "Using experimental feature \"{}\" ({}). Its output may change in future versions.",
+                feature,
+                feature.description()
+            );
+            Ok(())
+        } else {
+            Err(Error::FeatureDisabled(format!(
+                "{} is experimental, enable it with --enable-feature {}",
+                feature.description(),
+                feature
+            )))
+        }
+    }
+}
+
+impl std::iter::FromIterator<Feature> for Features {
+    fn from_iter<I: IntoIterator<Item = Feature>>(iter: I) -> Self {
+        Features(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_names() -> Result<()> {
+        for feature in Feature::ALL {
+            assert_eq!(&feature.name().parse::<Feature>()?, feature);
+        }
+        assert_eq!(
+            "firtl".parse::<Feature>().unwrap_err().to_string(),
+            "Invalid argument: unknown experimental feature \"firtl\", did you mean \"firrtl\"?"
+        );
+        assert_eq!(
+            "verilog".parse::<Feature>().unwrap_err().to_string(),
+            "Invalid argument: unknown experimental feature \"verilog\", expected one of: firrtl, union-streams"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn features() {
+        let features = Features::default();
+        assert!(!features.is_enabled(Feature::Firrtl));
+        assert_eq!(
+            features.require(Feature::UnionStreams).unwrap_err().to_string(),
+            "Experimental feature disabled: Lowering unions to streams is experimental, enable it with --enable-feature union-streams"
+        );
+
+        let features: Features = vec![Feature::UnionStreams, Feature::Firrtl, Feature::Firrtl]
+            .into_iter()
+            .collect();
+        assert!(features.require(Feature::Firrtl).is_ok());
+        assert_eq!(
+            features.iter().collect::<Vec<_>>(),
+            vec![Feature::Firrtl, Feature::UnionStreams]
+        );
+        assert_eq!(
+            Features::default()
+                .with(Feature::UnionStreams)
+                .with(Feature::Firrtl),
+            features
+        );
+    }
+}
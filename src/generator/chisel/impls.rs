@@ -0,0 +1,375 @@
+//! Implementations of Chisel traits for common representation.
+
+use crate::error::Error::BackEndError;
+use crate::generator::chisel::{Analyze, ChiselResult, ChiselType, Declare, IsDecoupled};
+use crate::generator::common::{Component, Field, Mode, Package, Port, Record, ResetStyle, Type};
+use crate::traits::Identify;
+use crate::{Document, Result};
+use std::collections::HashMap;
+
+/// Scala keywords, which cannot be used as identifiers without escaping them.
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "case",
+    "catch",
+    "class",
+    "def",
+    "do",
+    "else",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "for",
+    "forSome",
+    "if",
+    "implicit",
+    "import",
+    "lazy",
+    "match",
+    "new",
+    "null",
+    "object",
+    "override",
+    "package",
+    "private",
+    "protected",
+    "return",
+    "sealed",
+    "super",
+    "this",
+    "throw",
+    "trait",
+    "try",
+    "true",
+    "type",
+    "val",
+    "var",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Returns a Scala identifier for a name, escaping it with backticks if it is a keyword.
+pub(super) fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns a Scaladoc comment for a documentation string, indented with `indent`.
+fn scaladoc(doc: &str, indent: &str) -> String {
+    let mut result = format!("{}/**\n", indent);
+    for line in doc.lines() {
+        result.push_str(format!("{} *{}\n", indent, line).as_str());
+    }
+    result.push_str(format!("{} */\n", indent).as_str());
+    result
+}
+
+/// Returns true if a field is the valid or ready field of a decoupled record.
+pub(super) fn is_handshake(field: &Field) -> bool {
+    match field.identifier() {
+        "valid" => !field.is_reversed() && field.typ() == &Type::Bit,
+        "ready" => field.is_reversed() && field.typ() == &Type::Bit,
+        _ => false,
+    }
+}
+
+impl IsDecoupled for Record {
+    fn is_decoupled(&self) -> bool {
+        self.fields().filter(|f| is_handshake(f)).count() == 2
+    }
+}
+
+impl IsDecoupled for Type {
+    fn is_decoupled(&self) -> bool {
+        match self {
+            Type::Record(rec) => rec.is_decoupled(),
+            _ => false,
+        }
+    }
+}
+
+impl ChiselType for Type {
+    fn chisel_type(&self) -> ChiselResult {
+        match self {
+            Type::Bit => Ok("Bool()".to_string()),
+            Type::BitVec { width } => {
+                Ok(format!("UInt({}.W)", if *width == 0 { 1 } else { *width }))
+            }
+            Type::Record(rec) => {
+                if rec.is_decoupled() {
+                    Ok(format!("Decoupled(new {})", identifier(rec.identifier())))
+                } else {
+                    Ok(format!("new {}", identifier(rec.identifier())))
+                }
+            }
+        }
+    }
+}
+
+impl Declare for Record {
+    /// Declare the Bundle class of this record. For decoupled records, only the payload is
+    /// declared, since the valid and ready bits are part of the DecoupledIO bundle.
+    fn declare(&self) -> ChiselResult {
+        let decoupled = self.is_decoupled();
+        let mut result = format!(
+            "class {} extends Bundle {{\n",
+            identifier(self.identifier())
+        );
+        for field in self.fields().filter(|f| !(decoupled && is_handshake(f))) {
+            let typ = field.typ().chisel_type()?;
+            result.push_str(
+                format!(
+                    "  val {} = {}\n",
+                    identifier(field.identifier()),
+                    if field.is_reversed() {
+                        format!("Flipped({})", typ)
+                    } else {
+                        typ
+                    }
+                )
+                .as_str(),
+            );
+        }
+        result.push('}');
+        Ok(result)
+    }
+}
+
+impl Analyze for Type {
+    fn list_record_types(&self) -> Vec<Type> {
+        match self {
+            Type::Record(rec) => {
+                let mut result: Vec<Type> = rec
+                    .fields()
+                    .flat_map(|f| f.typ().list_record_types())
+                    .collect();
+                result.push(self.clone());
+                result
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl Analyze for Component {
+    fn list_record_types(&self) -> Vec<Type> {
+        self.ports()
+            .iter()
+            .flat_map(|p| p.typ().list_record_types())
+            .collect()
+    }
+}
+
+impl Declare for Port {
+    fn declare(&self) -> ChiselResult {
+        let mut result = String::new();
+        if let Some(doc) = self.doc() {
+            result.push_str(scaladoc(doc.as_str(), "    ").as_str());
+        }
+        let typ = self.typ().chisel_type()?;
+        result.push_str(
+            format!(
+                "    val {} = {}",
+                identifier(self.identifier()),
+                match (self.typ(), self.mode()) {
+                    (Type::Record(_), Mode::Out) => typ,
+                    (Type::Record(_), Mode::In) => format!("Flipped({})", typ),
+                    (_, Mode::Out) => format!("Output({})", typ),
+                    (_, Mode::In) => format!("Input({})", typ),
+                }
+            )
+            .as_str(),
+        );
+        Ok(result)
+    }
+}
+
+/// Returns true if the given attributes request Chisel to keep the signals they apply to.
+fn dont_touch(attributes: &[(String, String)]) -> bool {
+    attributes.iter().any(|(key, _)| key == "chisel.dont_touch")
+}
+
+impl Declare for Component {
+    fn declare(&self) -> ChiselResult {
+        let mut result = String::new();
+        if let Some(doc) = self.doc() {
+            result.push_str(scaladoc(doc.as_str(), "").as_str());
+        }
+        result.push_str(
+            format!(
+                "abstract class {} extends Module{} {{\n",
+                identifier(self.identifier()),
+                match self.clock_reset().style() {
+                    ResetStyle::Sync => "",
+                    ResetStyle::Async => " with RequireAsyncReset",
+                }
+            )
+            .as_str(),
+        );
+        result.push_str("  val io = IO(new Bundle {\n");
+        for port in self.ports() {
+            // Chisel modules have an implicit clock and reset.
+            if self.clock_reset().is_clock_or_reset(port) {
+                continue;
+            }
+            result.push_str(port.declare()?.as_str());
+            result.push('\n');
+        }
+        result.push_str("  })\n");
+        if dont_touch(self.attributes()) {
+            result.push_str("  dontTouch(io)\n");
+        }
+        for port in self.ports() {
+            if dont_touch(port.attributes()) && !self.clock_reset().is_clock_or_reset(port) {
+                result.push_str(
+                    format!("  dontTouch(io.{})\n", identifier(port.identifier())).as_str(),
+                );
+            }
+        }
+        result.push('}');
+        Ok(result)
+    }
+}
+
+impl Declare for Package {
+    fn declare(&self) -> ChiselResult {
+        let mut result = String::new();
+        result.push_str(format!("package {}\n\n", identifier(self.identifier.as_str())).as_str());
+        result.push_str("import chisel3._\nimport chisel3.util._\n\n");
+
+        // Declare every bundle only once, and produce an error if different records have the
+        // same identifier.
+        let mut type_ids = HashMap::<String, Type>::new();
+        for c in &self.components {
+            for r in c.list_record_types() {
+                if let Type::Record(rec) = &r {
+                    match type_ids.get(rec.identifier()) {
+                        None => {
+                            type_ids.insert(rec.identifier().to_string(), r.clone());
+                            result.push_str(format!("{}\n\n", rec.declare()?).as_str());
+                        }
+                        Some(already_defined_type) => {
+                            if &r != already_defined_type {
+                                return Err(BackEndError(format!(
+                                    "Type name conflict: {}",
+                                    rec.identifier()
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let components = self
+            .components
+            .iter()
+            .map(|c| c.declare())
+            .collect::<Result<Vec<_>>>()?;
+        result.push_str(components.join("\n\n").as_str());
+        result.push('\n');
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generator::common::test::*;
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(identifier("a"), "a");
+        assert_eq!(identifier("type"), "`type`");
+    }
+
+    #[test]
+    fn prim_type_decl() {
+        assert_eq!(Type::Bit.chisel_type().unwrap(), "Bool()");
+        assert_eq!(Type::bitvec(8).chisel_type().unwrap(), "UInt(8.W)");
+    }
+
+    #[test]
+    fn decoupled() {
+        let stream = Type::record(
+            "s",
+            vec![
+                Field::new("valid", Type::Bit, false),
+                Field::new("ready", Type::Bit, true),
+                Field::new("data", Type::bitvec(8), false),
+            ],
+        );
+        assert!(stream.is_decoupled());
+        assert!(!records::rec_rev("r").is_decoupled());
+        assert_eq!(stream.chisel_type().unwrap(), "Decoupled(new s)");
+        match stream {
+            Type::Record(rec) => assert_eq!(
+                rec.declare().unwrap(),
+                "class s extends Bundle {\n  val data = UInt(8.W)\n}"
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn comp_decl() {
+        let c = test_comp().with_doc(" My awesome\n Component".to_string());
+        assert_eq!(
+            c.declare().unwrap(),
+            "/**
+ * My awesome
+ * Component
+ */
+abstract class test_comp extends Module {
+  val io = IO(new Bundle {
+    val a = Flipped(new a)
+    val b = new b
+  })
+}"
+        );
+    }
+
+    #[test]
+    fn dont_touch() {
+        let c = Component::new(
+            "test_comp",
+            vec![],
+            vec![
+                Port::new("a", Mode::In, Type::Bit)
+                    .with_attributes(vec![("chisel.dont_touch", "true")]),
+                Port::new("b", Mode::Out, Type::Bit),
+            ],
+            None,
+        );
+        assert!(c.declare().unwrap().ends_with("  })\n  dontTouch(io.a)\n}"));
+    }
+
+    #[test]
+    fn record_decl() {
+        match records::rec_rev("rec") {
+            Type::Record(rec) => assert_eq!(
+                rec.declare().unwrap(),
+                "class rec extends Bundle {
+  val c = UInt(42.W)
+  val d = Flipped(UInt(1337.W))
+}"
+            ),
+            _ => unreachable!(),
+        }
+        match records::rec_rev_nested("rec") {
+            Type::Record(rec) => assert_eq!(
+                rec.declare().unwrap(),
+                "class rec extends Bundle {
+  val a = new rec_a
+  val b = new rec_b
+}"
+            ),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,41 +1,454 @@
 //! Chisel back-end.
+//!
+//! This module contains functionality to convert hardware defined in the common hardware
+//! representation to Chisel source files.
+//!
+//! The Chisel back-end always generates the user-friendly (fancy) representation of streamlets.
+//! Streams are represented using [`DecoupledIO`] bundles, records using nested `Bundle` classes,
+//! and streamlets as abstract modules that the user can extend to implement them. The clock and
+//! reset of a streamlet are the implicit clock and reset of the Chisel module, which is
+//! synchronous unless the module mixes in `RequireAsyncReset`.
+//!
+//! Unlike VHDL, Chisel supports bundles with members of opposite directions, so types are not
+//! split into downstream and upstream parts. Reversed record fields are wrapped in `Flipped`, and
+//! reversed streams, e.g. the responses of request/response streams, become `DecoupledIO` ports
+//! that flow against the direction of their interface.
+//!
+//! [`DecoupledIO`]: https://www.chisel-lang.org/api/latest/chisel3/util/DecoupledIO.html
 
-use crate::design::Project;
-use crate::generator::GenerateProject;
+use crate::design::{Project, Streamlet};
+use crate::experimental::Features;
+use crate::generator::common::{ClockReset, IdentifierStyle, ResetStyle, Type};
+use crate::generator::config::{expect_identifier_style, merge_flag, Configure, Value};
+use crate::generator::header::HeaderConfig;
+use crate::generator::manifest::Manifest;
+use crate::generator::{pruned_streams_comment, write_if_changed, GenerateProject, Lowered};
+use crate::traits::Identify;
 use crate::Result;
-use std::path::Path;
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+mod impls;
+pub mod sbt;
+pub mod spec;
 
 /// Chisel back-end code generation result
-#[allow(dead_code)]
 type ChiselResult = Result<String>;
 
+/// Generate trait for Chisel declarations.
+pub trait Declare {
+    /// Generate a Chisel declaration from self.
+    fn declare(&self) -> ChiselResult;
+}
+
+/// Generate trait for Chisel types.
+pub trait ChiselType {
+    /// Generate the Chisel type expression of self.
+    fn chisel_type(&self) -> ChiselResult;
+}
+
+/// Trait to detect types that can be represented with a Chisel [`DecoupledIO`] bundle.
+///
+/// [`DecoupledIO`]: https://www.chisel-lang.org/api/latest/chisel3/util/DecoupledIO.html
+pub trait IsDecoupled {
+    /// Returns true if self has a valid and a (reversed) ready bit.
+    fn is_decoupled(&self) -> bool;
+}
+
+/// Analyze trait for Chisel objects.
+pub trait Analyze {
+    /// List all record types used, such that nested records appear before the records that use
+    /// them.
+    fn list_record_types(&self) -> Vec<Type>;
+}
+
 /// Chisel back-end configuration parameters.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
 pub struct ChiselConfig {
-    /// An optional suffix appended to generated files.
-    /// The suffix is added as follows: <filename>.<suffix>.scala
-    #[allow(dead_code)]
+    /// Suffix of generated files. Default = "gen", such that
+    /// generated files are named <name>.gen.scala.
+    #[cfg_attr(feature = "cli", structopt(short, long = "suffix"))]
     gen_suffix: Option<String>,
+
+    /// List the streams that do not result in ports because they are null in a comment at the
+    /// top of every file.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    pruned_streams: bool,
+
+    /// Disable --pruned-streams, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "pruned_streams"))]
+    no_pruned_streams: bool,
+
+    /// Use an asynchronous reset, rather than a synchronous reset. Chisel modules always use
+    /// their implicit, active-high reset.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    async_reset: bool,
+
+    /// Disable --async-reset, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "async_reset"))]
+    no_async_reset: bool,
+
+    /// Generate a minimal sbt project around the Scala sources, with a build.sbt that depends on
+    /// Chisel and a Main object that elaborates all modules. The Scala sources are then written
+    /// to src/main/scala.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    sbt: bool,
+
+    /// Disable --sbt, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "sbt"))]
+    no_sbt: bool,
+
+    /// Generate a ChiselTest spec skeleton for every module, which performs a transfer on every
+    /// stream of the module. Specs are written to <library>/<module>_spec.gen.scala, in
+    /// src/test/scala if an sbt project is generated.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    tests: bool,
+
+    /// Disable --tests, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "tests"))]
+    no_tests: bool,
+
+    /// Style of the identifiers of ports and bundle fields.
+    /// Possible options: preserve, snake, camel. Default = preserve.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    identifier_style: Option<IdentifierStyle>,
+
+    /// Output directory, if not given on the command line. Only set from configuration files.
+    #[cfg_attr(feature = "cli", structopt(skip))]
+    output: Option<PathBuf>,
+}
+
+impl ChiselConfig {
+    /// Returns the suffix of generated files, if any.
+    pub fn suffix(&self) -> Option<&str> {
+        self.gen_suffix.as_deref()
+    }
+
+    /// Returns whether pruned null streams are listed in the generated files.
+    pub fn pruned_streams(&self) -> bool {
+        self.pruned_streams
+    }
+
+    /// Returns whether an sbt project is generated around the Scala sources.
+    pub fn sbt(&self) -> bool {
+        self.sbt
+    }
+
+    /// Returns whether ChiselTest specs are generated for the modules.
+    pub fn tests(&self) -> bool {
+        self.tests
+    }
+
+    /// Returns the clock and reset of the generated modules.
+    pub fn clock_reset(&self) -> ClockReset {
+        if self.async_reset {
+            ClockReset::default().with_style(ResetStyle::Async)
+        } else {
+            ClockReset::default()
+        }
+    }
+
+    /// Returns the style of the identifiers of ports and bundle fields.
+    pub fn identifier_style(&self) -> IdentifierStyle {
+        self.identifier_style.unwrap_or_default()
+    }
+
+    /// Returns the configured output directory, if any.
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
 }
 
 impl Default for ChiselConfig {
     fn default() -> Self {
         ChiselConfig {
             gen_suffix: Some("gen".to_string()),
+            pruned_streams: false,
+            no_pruned_streams: false,
+            async_reset: false,
+            no_async_reset: false,
+            sbt: false,
+            no_sbt: false,
+            tests: false,
+            no_tests: false,
+            identifier_style: None,
+            output: None,
+        }
+    }
+}
+
+impl Configure for ChiselConfig {
+    const SECTION: &'static str = "chisel";
+    const KEYS: &'static [&'static str] = &[
+        "suffix",
+        "pruned_streams",
+        "async_reset",
+        "sbt",
+        "tests",
+        "identifier_style",
+        "output",
+    ];
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<()> {
+        match key {
+            "suffix" => {
+                let suffix = value.expect_str(key)?;
+                self.gen_suffix = if suffix.is_empty() {
+                    None
+                } else {
+                    Some(suffix.to_string())
+                };
+            }
+            "pruned_streams" => self.pruned_streams = value.expect_bool(key)?,
+            "async_reset" => self.async_reset = value.expect_bool(key)?,
+            "sbt" => self.sbt = value.expect_bool(key)?,
+            "tests" => self.tests = value.expect_bool(key)?,
+            "identifier_style" => {
+                self.identifier_style = Some(expect_identifier_style(key, value)?)
+            }
+            "output" => self.output = Some(PathBuf::from(value.expect_str(key)?)),
+            _ => unreachable!(),
         }
+        Ok(())
+    }
+
+    fn merge(&mut self, overrides: Self) {
+        self.gen_suffix = overrides.gen_suffix.or_else(|| self.gen_suffix.take());
+        merge_flag(
+            &mut self.pruned_streams,
+            overrides.pruned_streams,
+            overrides.no_pruned_streams,
+        );
+        merge_flag(
+            &mut self.async_reset,
+            overrides.async_reset,
+            overrides.no_async_reset,
+        );
+        merge_flag(&mut self.sbt, overrides.sbt, overrides.no_sbt);
+        merge_flag(&mut self.tests, overrides.tests, overrides.no_tests);
+        self.identifier_style = overrides.identifier_style.or(self.identifier_style);
+        self.output = overrides.output.or_else(|| self.output.take());
     }
 }
 
-/// A configurable VHDL back-end entry point.
+/// A configurable Chisel back-end entry point.
 #[derive(Default)]
-#[allow(dead_code)]
 pub struct ChiselBackEnd {
-    /// Configuration for the VHDL back-end.
+    /// Configuration for the Chisel back-end.
     config: ChiselConfig,
+    /// Whether to write files that have not changed.
+    force: bool,
+    /// Whether to write a manifest of the generated files.
+    manifest: bool,
+    /// The experimental features that are listed in the manifest.
+    features: Features,
+    /// The header of generated files.
+    header: HeaderConfig,
+}
+
+impl ChiselBackEnd {
+    pub fn config(&self) -> &ChiselConfig {
+        &self.config
+    }
+
+    /// Return this back-end that writes all files, even if their contents did not change.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Return this back-end that writes a [manifest](crate::generator::manifest) of the
+    /// generated files to the project directory.
+    pub fn with_manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Return this back-end that lists the enabled experimental `features` in its manifest.
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Return this back-end that starts generated files with a [header](crate::generator::header).
+    pub fn with_header(mut self, header: HeaderConfig) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl From<ChiselConfig> for ChiselBackEnd {
+    fn from(config: ChiselConfig) -> Self {
+        ChiselBackEnd {
+            config,
+            force: false,
+            manifest: false,
+            features: Features::default(),
+            header: HeaderConfig::default(),
+        }
+    }
 }
 
-#[allow(unused_variables)]
 impl GenerateProject for ChiselBackEnd {
     fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
-        unimplemented!();
+        self.generate_lowered(&Lowered::new(project)?, path)
+    }
+
+    fn generate_lowered(&self, lowered: &Lowered, path: impl AsRef<Path>) -> Result<()> {
+        let project = lowered.project();
+        // Create the project directory.
+        let mut dir = path.as_ref().to_path_buf();
+        dir.push(project.identifier());
+        std::fs::create_dir_all(dir.as_path())?;
+
+        let mut changed = 0;
+        let mut manifest = Manifest::new(project, dir.as_path()).with_features(&self.features);
+        let mut write =
+            |path: PathBuf, contents: String, sources: Vec<(&str, &Streamlet)>| -> Result<()> {
+                let contents = self.header.apply(project.identifier(), "//", contents);
+                manifest.add(path.as_path(), contents.as_bytes(), sources);
+                if write_if_changed(path.as_path(), contents, self.force)? {
+                    info!("Wrote {}.", path.as_path().to_str().unwrap_or(""));
+                    changed += 1;
+                } else {
+                    debug!(
+                        "Skipped {}, unchanged.",
+                        path.as_path().to_str().unwrap_or("")
+                    );
+                }
+                Ok(())
+            };
+
+        let sources = if self.config.sbt() {
+            write(
+                dir.join("build.sbt"),
+                sbt::declare_build(project, self.config.tests()),
+                vec![],
+            )?;
+            let sources = dir.join(sbt::SOURCE_DIR);
+            std::fs::create_dir_all(sources.as_path())?;
+            sources
+        } else {
+            dir.clone()
+        };
+        let tests = if self.config.sbt() {
+            dir.join(spec::TEST_DIR)
+        } else {
+            dir
+        };
+
+        let mut packages = vec![];
+        for lib in project.libraries() {
+            let package = lowered
+                .fancy_only(lib.identifier())
+                .with_clock_reset(&self.config.clock_reset())?
+                .with_identifier_style(self.config.identifier_style())?;
+
+            let mut file = sources.clone();
+            file.push(lib.identifier());
+            file.set_extension(match self.config.suffix() {
+                None => "scala".to_string(),
+                Some(s) => format!("{}.scala", s),
+            });
+
+            let mut contents = package.declare()?;
+            if self.config.pruned_streams() {
+                if let Some(comment) = pruned_streams_comment(lib, "//") {
+                    contents = format!("{}\n{}", comment, contents);
+                }
+            }
+
+            let sources = lowered
+                .streamlets(lib.identifier())
+                .map(|s| (lib.identifier(), s))
+                .collect();
+            write(file, contents, sources)?;
+
+            if self.config.tests() {
+                let lib_dir = tests.join(lib.identifier());
+                std::fs::create_dir_all(lib_dir.as_path())?;
+                for component in &package.components {
+                    let mut file = lib_dir.join(spec::spec_identifier(component));
+                    file.set_extension(match self.config.suffix() {
+                        None => "scala".to_string(),
+                        Some(s) => format!("{}.scala", s),
+                    });
+                    let sources = lowered
+                        .streamlet(lib.identifier(), component.identifier())
+                        .map(|s| (lib.identifier(), s))
+                        .into_iter()
+                        .collect();
+                    write(file, spec::declare_spec(&package, component), sources)?;
+                }
+            }
+            packages.push(package);
+        }
+
+        if self.config.sbt() {
+            let all = project
+                .libraries()
+                .flat_map(|lib| {
+                    lowered
+                        .streamlets(lib.identifier())
+                        .map(move |s| (lib.identifier(), s))
+                })
+                .collect();
+            write(
+                sources.join("Main.scala"),
+                sbt::declare_main(&packages),
+                all,
+            )?;
+        }
+
+        if self.manifest && manifest.write(self.force)? {
+            info!("Wrote {}.", manifest.path().display());
+        }
+
+        info!("{} file(s) changed.", changed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn backend() -> Result<()> {
+        let c = ChiselBackEnd::default();
+
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("__test");
+
+        c.generate(&crate::design::project::tests::proj::empty_proj(), &path)?;
+
+        // Check if files were correctly generated.
+        assert!(fs::metadata(path.join("proj/lib.gen.scala")).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sbt() -> Result<()> {
+        let c = ChiselBackEnd::from(ChiselConfig {
+            sbt: true,
+            tests: true,
+            ..Default::default()
+        });
+
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("__test");
+
+        c.generate(&crate::design::project::tests::proj::empty_proj(), &path)?;
+
+        assert!(fs::metadata(path.join("proj/build.sbt")).is_ok());
+        assert!(fs::metadata(path.join("proj/src/main/scala/lib.gen.scala")).is_ok());
+        assert!(fs::metadata(path.join("proj/src/main/scala/Main.scala")).is_ok());
+
+        Ok(())
     }
 }
@@ -0,0 +1,99 @@
+//! sbt project scaffolding.
+//!
+//! Generates a minimal sbt project around the generated Scala sources, such that they can be
+//! compiled with `sbt compile` right away. The project consists of a `build.sbt` that depends on
+//! Chisel, and a `Main` object that elaborates every generated module to Verilog with
+//! `sbt run`. Since the generated modules are abstract, `Main` elaborates them with their ports
+//! left unconnected.
+
+use crate::design::Project;
+use crate::generator::chisel::impls::identifier;
+use crate::generator::chisel::spec::CHISELTEST_VERSION;
+use crate::generator::common::Package;
+use crate::traits::Identify;
+
+/// The Scala version of generated sbt projects.
+pub const SCALA_VERSION: &str = "2.13.8";
+
+/// The Chisel version of generated sbt projects.
+pub const CHISEL_VERSION: &str = "3.5.4";
+
+/// The directory of the Scala sources within an sbt project.
+pub const SOURCE_DIR: &str = "src/main/scala";
+
+/// Returns the `build.sbt` of a project. With `tests`, the project depends on ChiselTest to run
+/// the generated [specs](../spec/index.html).
+pub fn declare_build(project: &Project, tests: bool) -> String {
+    let mut result = format!(
+        "name := \"{}\"
+scalaVersion := \"{s}\"
+
+libraryDependencies += \"edu.berkeley.cs\" %% \"chisel3\" % \"{c}\"
+addCompilerPlugin(\"edu.berkeley.cs\" % \"chisel3-plugin\" % \"{c}\" cross CrossVersion.full)
+",
+        project.identifier(),
+        s = SCALA_VERSION,
+        c = CHISEL_VERSION
+    );
+    if tests {
+        result.push_str(
+            format!(
+                "libraryDependencies += \"edu.berkeley.cs\" %% \"chiseltest\" % \"{}\" % \"test\"\n",
+                CHISELTEST_VERSION
+            )
+            .as_str(),
+        );
+    }
+    result
+}
+
+/// Returns a Scala source with a `Main` object that elaborates all components of `packages`.
+pub fn declare_main(packages: &[Package]) -> String {
+    let mut result = String::new();
+    result.push_str("import chisel3._\nimport chisel3.stage.ChiselStage\n\n");
+    result.push_str(
+        "/**\n * Elaborates the generated modules, with their ports left unconnected.\n */\n",
+    );
+    result.push_str("object Main extends App {\n");
+    for package in packages {
+        for component in &package.components {
+            result.push_str(
+                format!(
+                    "  (new ChiselStage).emitVerilog(new {}.{} {{ io <> DontCare }}, args)\n",
+                    identifier(package.identifier.as_str()),
+                    identifier(component.identifier())
+                )
+                .as_str(),
+            );
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::Component;
+
+    #[test]
+    fn main() {
+        let package = Package {
+            identifier: "lib".to_string(),
+            components: vec![Component::new("type", vec![], vec![], None)],
+        };
+        assert_eq!(
+            declare_main(&[package]),
+            "import chisel3._
+import chisel3.stage.ChiselStage
+
+/**
+ * Elaborates the generated modules, with their ports left unconnected.
+ */
+object Main extends App {
+  (new ChiselStage).emitVerilog(new lib.`type` { io <> DontCare }, args)
+}
+"
+        );
+    }
+}
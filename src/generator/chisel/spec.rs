@@ -0,0 +1,212 @@
+//! ChiselTest spec skeletons.
+//!
+//! Generates a [ChiselTest] spec for every generated module, to start testing an implementation
+//! of the module from. A spec extends the abstract module with its ports left unconnected, which
+//! is to be replaced by the implementation, and performs a single transfer on every stream: it
+//! pokes the valid signal and payload of every input stream, and the ready signal of every
+//! output stream, with zeros. The signals that the module drives are expected in comments, to be
+//! completed by the user.
+//!
+//! [ChiselTest]: https://github.com/ucb-bar/chiseltest
+
+use crate::generator::chisel::impls::{identifier, is_handshake};
+use crate::generator::chisel::IsDecoupled;
+use crate::generator::common::{Component, Mode, Package, Type};
+use crate::traits::Identify;
+
+/// The ChiselTest version of generated sbt projects with specs.
+pub const CHISELTEST_VERSION: &str = "0.5.4";
+
+/// The directory of the test sources within an sbt project.
+pub const TEST_DIR: &str = "src/test/scala";
+
+/// Returns the identifier of the spec of a component.
+pub fn spec_identifier(component: &Component) -> String {
+    format!("{}_spec", component.identifier())
+}
+
+/// Returns the zero literal of a Chisel type.
+fn zero(typ: &Type) -> &'static str {
+    match typ {
+        Type::Bit => "false.B",
+        _ => "0.U",
+    }
+}
+
+/// Returns the statements that poke the signals that are driven by the test, and expect the
+/// signals that are driven by the module in comments, of the leaves of `typ` at `path`. The
+/// signals of `typ` are driven by the test if `driven` is true, apart from reversed fields.
+fn leaves(path: &str, typ: &Type, driven: bool, result: &mut Vec<String>) {
+    for (fields, typ, reversed) in typ.flatten(vec![], false) {
+        let signal = fields
+            .iter()
+            .map(|field| format!(".{}", identifier(field)))
+            .collect::<String>();
+        if driven != reversed {
+            result.push(format!("{}{}.poke({})", path, signal, zero(&typ)));
+        } else {
+            result.push(format!("// {}{}.expect({})", path, signal, zero(&typ)));
+        }
+    }
+}
+
+/// Returns the ChiselTest spec of `component`, in the Scala package of `package`.
+pub fn declare_spec(package: &Package, component: &Component) -> String {
+    let mut transfer = vec![];
+    let mut release = vec![];
+    for port in component.ports() {
+        // The implicit clock and reset are driven by ChiselTest.
+        if component.clock_reset().is_clock_or_reset(port) {
+            continue;
+        }
+        let path = format!("c.io.{}", identifier(port.identifier()));
+        let input = port.mode() == Mode::In;
+        transfer.push(format!(
+            "// {}: {}",
+            port.identifier(),
+            if input { "in" } else { "out" }
+        ));
+        match port.typ() {
+            Type::Record(rec) if rec.is_decoupled() => {
+                let (valid, ready) = (format!("{}.valid", path), format!("{}.ready", path));
+                let payload = Type::record(
+                    rec.identifier(),
+                    rec.fields().filter(|f| !is_handshake(f)).cloned().collect(),
+                );
+                if input {
+                    transfer.push(format!("{}.poke(true.B)", valid));
+                    leaves(
+                        format!("{}.bits", path).as_str(),
+                        &payload,
+                        true,
+                        &mut transfer,
+                    );
+                    transfer.push(format!("// {}.expect(true.B)", ready));
+                    release.push(format!("{}.poke(false.B)", valid));
+                } else {
+                    transfer.push(format!("{}.poke(true.B)", ready));
+                    transfer.push(format!("// {}.expect(true.B)", valid));
+                    leaves(
+                        format!("{}.bits", path).as_str(),
+                        &payload,
+                        false,
+                        &mut transfer,
+                    );
+                    release.push(format!("{}.poke(false.B)", ready));
+                }
+            }
+            typ => leaves(path.as_str(), &typ, input, &mut transfer),
+        }
+    }
+
+    let module = format!(
+        "{}.{}",
+        identifier(package.identifier.as_str()),
+        identifier(component.identifier())
+    );
+    let mut result = String::new();
+    result.push_str(format!("package {}\n\n", identifier(package.identifier.as_str())).as_str());
+    result.push_str("import chisel3._\nimport chiseltest._\n");
+    result.push_str("import org.scalatest.flatspec.AnyFlatSpec\n\n");
+    result.push_str(
+        format!(
+            "/**\n * Test of {m}. Replace the unconnected ports by an implementation of {m}, and\n * complete the expectations.\n */\n",
+            m = module
+        )
+        .as_str(),
+    );
+    result.push_str(
+        format!(
+            "class {} extends AnyFlatSpec with ChiselScalatestTester {{\n",
+            identifier(spec_identifier(component).as_str())
+        )
+        .as_str(),
+    );
+    result.push_str(format!("  behavior of \"{}\"\n\n", component.identifier()).as_str());
+    result.push_str("  it should \"transfer an element on every stream\" in {\n");
+    result.push_str(
+        format!(
+            "    test(new {} {{ io <> DontCare }}) {{ c =>\n",
+            identifier(component.identifier())
+        )
+        .as_str(),
+    );
+    for statement in &transfer {
+        result.push_str(format!("      {}\n", statement).as_str());
+    }
+    result.push_str("      c.clock.step()\n");
+    for statement in &release {
+        result.push_str(format!("      {}\n", statement).as_str());
+    }
+    result.push_str("    }\n  }\n}\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::{Field, Port};
+
+    #[test]
+    fn spec() {
+        let stream = |name: &str, width| {
+            Type::record(
+                name,
+                vec![
+                    Field::new("valid", Type::Bit, false),
+                    Field::new("ready", Type::Bit, true),
+                    Field::new("data", Type::bitvec(width), false),
+                ],
+            )
+        };
+        let component = Component::new(
+            "x",
+            vec![],
+            vec![
+                Port::new("a", Mode::In, stream("x_a", 8)),
+                Port::new("b", Mode::Out, stream("x_b", 4)),
+                Port::new("type", Mode::In, Type::Bit),
+            ],
+            None,
+        );
+        let package = Package {
+            identifier: "lib".to_string(),
+            components: vec![component.clone()],
+        };
+        assert_eq!(
+            declare_spec(&package, &component),
+            "package lib
+
+import chisel3._
+import chiseltest._
+import org.scalatest.flatspec.AnyFlatSpec
+
+/**
+ * Test of lib.x. Replace the unconnected ports by an implementation of lib.x, and
+ * complete the expectations.
+ */
+class x_spec extends AnyFlatSpec with ChiselScalatestTester {
+  behavior of \"x\"
+
+  it should \"transfer an element on every stream\" in {
+    test(new x { io <> DontCare }) { c =>
+      // a: in
+      c.io.a.valid.poke(true.B)
+      c.io.a.bits.data.poke(0.U)
+      // c.io.a.ready.expect(true.B)
+      // b: out
+      c.io.b.ready.poke(true.B)
+      // c.io.b.valid.expect(true.B)
+      // c.io.b.bits.data.expect(0.U)
+      // type: in
+      c.io.`type`.poke(false.B)
+      c.clock.step()
+      c.io.a.valid.poke(false.B)
+      c.io.b.ready.poke(false.B)
+    }
+  }
+}
+"
+        );
+    }
+}
@@ -7,7 +7,7 @@ use crate::generator::common::{Component, Mode, Package, Port, Project, Record,
 use crate::logical::{Group, LogicalType, Stream, Union};
 use crate::physical::{Origin, Signal, Width};
 use crate::traits::Identify;
-use crate::{cat, Document};
+use crate::{cat, Document, NonNegative};
 
 // Generator-global constants:
 
@@ -187,12 +187,11 @@ impl Typify for Stream {
                 _ => cat!(pre, name),
             });
 
-            // Insert data record. There must be something there since it is not null.
-            rec.insert_new_field(
-                "data",
-                self.data().fancy(cat!(pre, name, "data")).unwrap(),
-                false,
-            );
+            // Insert data record, if any. Streams that are kept despite having null data
+            // only consist of valid and ready.
+            if let Some(data_type) = self.data().fancy(cat!(pre, name, "data")) {
+                rec.insert_new_field("data", data_type, false);
+            }
 
             // Check signals related to dimensionality, complexity, etc.
             if let Some(sig) = signals.last() {
@@ -208,6 +207,29 @@ impl Typify for Stream {
                 rec.insert_new_field("strb", sig.width().into(), sig.reversed());
             }
 
+            // For streams with multiple lanes of which some may be unused, expose which lanes
+            // carry an element, and how many do, as derived from stai, endi and strb.
+            let n = physical.element_lanes();
+            if let Some(sig) = [signals.stai(), signals.endi(), signals.strb()]
+                .iter()
+                .flatten()
+                .next()
+                .filter(|_| n.get() > 1)
+            {
+                rec.insert_new_field("lane_valid", Type::bitvec(n.get()), sig.reversed());
+                // The count ranges from zero up to and including n.
+                let count = NonNegative::BITS - n.get().leading_zeros();
+                rec.insert_new_field("lane_count", Type::bitvec(count), sig.reversed());
+            }
+
+            // Insert user record, if the stream carries user-defined transfer content.
+            if let Some(sig) = signals.user() {
+                if let Some(user_type) = self.user().and_then(|u| u.fancy(cat!(pre, name, "user")))
+                {
+                    rec.insert_new_field("user", user_type, sig.reversed());
+                }
+            }
+
             Some(Type::Record(rec))
         } else {
             None
@@ -266,11 +288,12 @@ impl Portify for Interface {
         }
 
         for (path, phys) in synth.streams() {
+            let mode = self.stream_mode(path);
             for s in phys.signal_list().into_iter() {
                 let port_name = cat!(n.clone(), path, s.identifier());
                 ports.push(Port::new(
                     port_name,
-                    s.origin().mode_for(self.mode()),
+                    s.origin().mode_for(mode),
                     s.width().into(),
                 ));
             }
@@ -294,7 +317,7 @@ impl Portify for Interface {
         // Split the LogicalType up into discrete, simple streams.
         for (path, simple_stream) in self.typ().split_streams().streams() {
             if let Some(typ) = simple_stream.fancy(cat!(tn.clone(), path)) {
-                result.push(Port::new(cat!(n, path), self.mode().into(), typ));
+                result.push(Port::new(cat!(n, path), self.stream_mode(path).into(), typ));
             }
         }
 
@@ -324,37 +347,58 @@ impl Componentify for Streamlet {
                     Port::new_documented("rst", Mode::In, Type::Bit, None),
                 ];
                 self.interfaces().for_each(|interface| {
-                    all_ports.extend(interface.canonical(interface.identifier()));
+                    all_ports.extend(
+                        interface
+                            .canonical(interface.identifier())
+                            .into_iter()
+                            .map(|port| port.with_attributes(interface.metadata())),
+                    );
                 });
                 all_ports
             },
             self.doc(),
         )
+        .with_attributes(self.metadata())
     }
 
     fn fancy(&self, suffix: Option<&str>) -> Option<Component> {
-        Some(Component::new(
-            cat!(self.identifier().to_string(), suffix.unwrap_or("")),
-            vec![],
-            {
-                let mut all_ports: Vec<Port> = vec![
-                    Port::new_documented("clk", Mode::In, Type::Bit, None),
-                    Port::new_documented("rst", Mode::In, Type::Bit, None),
-                ];
-                all_ports.extend(
-                    self.interfaces()
-                        .flat_map(|interface| {
-                            interface.fancy(
-                                interface.identifier(),
-                                cat!(self.identifier().to_string(), interface.identifier()),
-                            )
-                        })
-                        .collect::<Vec<Port>>(),
-                );
-                all_ports
-            },
-            self.doc(),
-        ))
+        Some(
+            Component::new(
+                cat!(self.identifier().to_string(), suffix.unwrap_or("")),
+                vec![],
+                {
+                    let mut all_ports: Vec<Port> = vec![
+                        Port::new_documented("clk", Mode::In, Type::Bit, None),
+                        Port::new_documented("rst", Mode::In, Type::Bit, None),
+                    ];
+                    all_ports.extend(
+                        self.interfaces()
+                            .flat_map(|interface| {
+                                interface
+                                    .fancy(
+                                        interface.identifier(),
+                                        // Interfaces that refer to a named type share its types.
+                                        match interface.type_name() {
+                                            Some(name) => name.to_string(),
+                                            None => {
+                                                cat!(
+                                                    self.identifier().to_string(),
+                                                    interface.identifier()
+                                                )
+                                            }
+                                        },
+                                    )
+                                    .into_iter()
+                                    .map(move |port| port.with_attributes(interface.metadata()))
+                            })
+                            .collect::<Vec<Port>>(),
+                    );
+                    all_ports
+                },
+                self.doc(),
+            )
+            .with_attributes(self.metadata()),
+        )
     }
 }
 
@@ -588,6 +632,50 @@ pub(crate) mod tests {
             );
         }
 
+        #[test]
+        fn lane_validity() -> Result<()> {
+            use crate::logical::{Direction, Synchronicity};
+            use crate::physical::Complexity;
+            use crate::PositiveReal;
+
+            let stream = |complexity: u32| {
+                LogicalType::from(Stream::new(
+                    LogicalType::try_new_bits(8).unwrap(),
+                    PositiveReal::new(3.).unwrap(),
+                    0,
+                    Synchronicity::Sync,
+                    Complexity::new_major(complexity),
+                    Direction::Forward,
+                    None,
+                    false,
+                ))
+            };
+            let fields = |typ: Type| match typ {
+                Type::Record(rec) => rec
+                    .fields()
+                    .map(|f| (f.identifier().to_string(), f.typ().clone()))
+                    .collect::<Vec<_>>(),
+                _ => unreachable!(),
+            };
+
+            assert_eq!(
+                fields(stream(7).fancy("test").unwrap()),
+                vec![
+                    ("valid".to_string(), Type::Bit),
+                    ("ready".to_string(), Type::Bit),
+                    ("data".to_string(), Type::bitvec(8)),
+                    ("stai".to_string(), Type::bitvec(2)),
+                    ("endi".to_string(), Type::bitvec(2)),
+                    ("strb".to_string(), Type::bitvec(3)),
+                    ("lane_valid".to_string(), Type::bitvec(3)),
+                    ("lane_count".to_string(), Type::bitvec(2)),
+                ]
+            );
+            // All lanes are used at lower complexities.
+            assert_eq!(fields(stream(4).fancy("test").unwrap()).len(), 3);
+            Ok(())
+        }
+
         #[test]
         fn interface_to_port() {
             let if0 = Interface::try_new("test", crate::design::Mode::In, streams::prim(8), None)
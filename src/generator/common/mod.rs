@@ -4,8 +4,8 @@
 //! generation that back-ends may or may not use.
 
 use crate::traits::Identify;
-use crate::{cat, Document};
-use crate::{NonNegative, Reversed};
+use crate::{cat, Document, Error, Result};
+use crate::{NonNegative, Reverse};
 
 pub mod convert;
 
@@ -65,9 +65,10 @@ impl Field {
     }
 }
 
-impl Reversed for Field {
-    fn reversed(&self) -> Self {
-        Field::new(self.name.clone(), self.typ.clone(), !self.reversed)
+impl Reverse for Field {
+    /// Reverse the direction of this field w.r.t. the other fields in the record.
+    fn reverse(&mut self) {
+        self.reversed = !self.reversed;
     }
 }
 
@@ -167,6 +168,16 @@ impl Record {
     }
 }
 
+impl Reverse for Record {
+    /// Reverse the direction of all fields of this record.
+    ///
+    /// Nested records do not need to be touched, since their fields are reversed along with the
+    /// field that contains them.
+    fn reverse(&mut self) {
+        self.fields.iter_mut().for_each(|f| f.reverse());
+    }
+}
+
 /// Hardware types.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
@@ -197,13 +208,17 @@ impl Type {
     }
 
     /// Flatten a type to a non-nested type bundle.
+    ///
+    /// The leaves of the bundle are marked reversed if they are reversed with respect to the
+    /// flattened type, i.e. if an odd number of the fields that lead to them is reversed.
+    /// `reversed` marks the type itself as reversed.
     pub fn flatten(&self, prefix: Vec<String>, reversed: bool) -> TypeBundle {
         let mut result: TypeBundle = vec![];
         match self {
             Type::Record(rec) => rec.fields.iter().for_each(|field| {
                 let mut new_prefix = prefix.clone();
                 new_prefix.push(field.name.clone());
-                result.extend(field.typ.flatten(new_prefix, field.reversed))
+                result.extend(field.typ.flatten(new_prefix, reversed != field.reversed))
             }),
             _ => result.push((prefix, self.clone(), reversed)),
         }
@@ -217,6 +232,32 @@ impl Type {
             _ => false,
         }
     }
+
+    /// Returns true if this type has the same structure as the other type, i.e. if they are
+    /// equal apart from the identifiers of the records they consist of.
+    pub fn same_shape(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Record(a), Type::Record(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields().zip(b.fields()).all(|(a, b)| {
+                        a.name == b.name && a.reversed == b.reversed && a.typ.same_shape(&b.typ)
+                    })
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl Reverse for Type {
+    /// Reverse the direction of all fields of this type.
+    ///
+    /// Primitive types have no direction of their own; their direction is determined by the port
+    /// they are used in, so reversing them has no effect.
+    fn reverse(&mut self) {
+        if let Type::Record(rec) = self {
+            rec.reverse()
+        }
+    }
 }
 
 /// A parameter for components.
@@ -235,12 +276,12 @@ pub enum Mode {
     Out,
 }
 
-impl Reversed for Mode {
-    fn reversed(&self) -> Self {
-        match self {
+impl Reverse for Mode {
+    fn reverse(&mut self) {
+        *self = match self {
             Mode::In => Mode::Out,
             Mode::Out => Mode::In,
-        }
+        };
     }
 }
 
@@ -255,6 +296,8 @@ pub struct Port {
     typ: Type,
     /// Port documentation.
     doc: Option<String>,
+    /// Port attributes, as key-value pairs.
+    attributes: Vec<(String, String)>,
 }
 
 impl Port {
@@ -265,6 +308,7 @@ impl Port {
             mode,
             typ,
             doc: None,
+            attributes: vec![],
         }
     }
 
@@ -280,6 +324,7 @@ impl Port {
             mode,
             typ,
             doc,
+            attributes: vec![],
         }
     }
 
@@ -308,6 +353,29 @@ impl Port {
     pub fn set_doc(&mut self, doc: impl Into<String>) {
         self.doc = Some(doc.into())
     }
+
+    /// Return the attributes of this port.
+    pub fn attributes(&self) -> &[(String, String)] {
+        self.attributes.as_slice()
+    }
+
+    /// Return this port with attributes added.
+    pub fn with_attributes(
+        mut self,
+        attributes: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.attributes
+            .extend(attributes.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+}
+
+impl Reverse for Port {
+    /// Reverse the mode of this port. The type of the port is left untouched, such that any
+    /// reversed fields keep their direction relative to the port.
+    fn reverse(&mut self) {
+        self.mode.reverse();
+    }
 }
 
 impl Identify for Port {
@@ -322,6 +390,243 @@ impl Document for Port {
     }
 }
 
+/// Polarity of a reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetPolarity {
+    /// The reset is asserted when it is high.
+    #[default]
+    ActiveHigh,
+    /// The reset is asserted when it is low.
+    ActiveLow,
+}
+
+/// Style of a reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetStyle {
+    /// The reset is sampled on the active edge of the clock.
+    #[default]
+    Sync,
+    /// The reset takes effect regardless of the clock.
+    Async,
+}
+
+/// The clock and reset of a component.
+///
+/// By default, components have a clock named `clk` and a synchronous, active-high reset named
+/// `rst`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockReset {
+    /// The identifier of the clock port.
+    clock: String,
+    /// The identifier of the reset port.
+    reset: String,
+    /// The polarity of the reset.
+    polarity: ResetPolarity,
+    /// The style of the reset.
+    style: ResetStyle,
+}
+
+impl Default for ClockReset {
+    fn default() -> Self {
+        ClockReset {
+            clock: "clk".to_string(),
+            reset: "rst".to_string(),
+            polarity: ResetPolarity::default(),
+            style: ResetStyle::default(),
+        }
+    }
+}
+
+impl ClockReset {
+    /// Return this clock and reset with a different clock port identifier.
+    pub fn with_clock(mut self, clock: impl Into<String>) -> Self {
+        self.clock = clock.into();
+        self
+    }
+
+    /// Return this clock and reset with a different reset port identifier.
+    pub fn with_reset(mut self, reset: impl Into<String>) -> Self {
+        self.reset = reset.into();
+        self
+    }
+
+    /// Return this clock and reset with a different reset polarity.
+    pub fn with_polarity(mut self, polarity: ResetPolarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Return this clock and reset with a different reset style.
+    pub fn with_style(mut self, style: ResetStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the identifier of the clock port.
+    pub fn clock(&self) -> &str {
+        self.clock.as_str()
+    }
+
+    /// Returns the identifier of the reset port.
+    pub fn reset(&self) -> &str {
+        self.reset.as_str()
+    }
+
+    /// Returns the polarity of the reset.
+    pub fn polarity(&self) -> ResetPolarity {
+        self.polarity
+    }
+
+    /// Returns the style of the reset.
+    pub fn style(&self) -> ResetStyle {
+        self.style
+    }
+
+    /// Returns the clock and reset ports.
+    pub fn ports(&self) -> Vec<Port> {
+        vec![
+            Port::new(self.clock(), Mode::In, Type::Bit),
+            Port::new_documented(self.reset(), Mode::In, Type::Bit, self.reset_doc()),
+        ]
+    }
+
+    /// Returns true if the port is the clock or the reset port.
+    pub fn is_clock_or_reset(&self, port: &Port) -> bool {
+        port.typ == Type::Bit && [self.clock(), self.reset()].contains(&port.identifier())
+    }
+
+    /// Returns the documentation of the reset port. Resets that are not synchronous and
+    /// active-high are documented, since those are assumed otherwise.
+    fn reset_doc(&self) -> Option<String> {
+        if self.polarity == ResetPolarity::default() && self.style == ResetStyle::default() {
+            return None;
+        }
+        Some(format!(
+            " {} {} reset.",
+            match self.polarity {
+                ResetPolarity::ActiveHigh => "Active-high",
+                ResetPolarity::ActiveLow => "Active-low",
+            },
+            match self.style {
+                ResetStyle::Sync => "synchronous",
+                ResetStyle::Async => "asynchronous",
+            }
+        ))
+    }
+}
+
+/// Style of the identifiers of ports and record fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdentifierStyle {
+    /// Identifiers are derived from the names in the design as they are.
+    #[default]
+    Preserve,
+    /// Words are in lower case and separated by underscores, e.g. `data_valid`.
+    SnakeCase,
+    /// Words are joined, and all but the first word start with an upper case letter, e.g.
+    /// `dataValid`.
+    CamelCase,
+}
+
+impl IdentifierStyle {
+    /// Returns `identifier` converted to this style.
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::generator::common::IdentifierStyle;
+    ///
+    /// assert_eq!(IdentifierStyle::CamelCase.convert("a_data_valid"), "aDataValid");
+    /// assert_eq!(IdentifierStyle::SnakeCase.convert("aDataValid"), "a_data_valid");
+    /// assert_eq!(IdentifierStyle::Preserve.convert("aData_valid"), "aData_valid");
+    /// ```
+    pub fn convert(&self, identifier: &str) -> String {
+        match self {
+            IdentifierStyle::Preserve => identifier.to_string(),
+            IdentifierStyle::SnakeCase => {
+                let mut result = String::with_capacity(identifier.len());
+                let mut previous: Option<char> = None;
+                for c in identifier.chars() {
+                    if c.is_ascii_uppercase()
+                        && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                    {
+                        result.push('_');
+                    }
+                    result.push(c.to_ascii_lowercase());
+                    previous = Some(c);
+                }
+                result
+            }
+            IdentifierStyle::CamelCase => {
+                let mut result = String::with_capacity(identifier.len());
+                for word in identifier.split('_').filter(|w| !w.is_empty()) {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        if result.is_empty() {
+                            result.push(first);
+                        } else {
+                            result.push(first.to_ascii_uppercase());
+                        }
+                        result.extend(chars);
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Returns `typ` with the identifiers of all nested record fields converted to this style.
+    /// Returns an error if fields of the same record end up with the same identifier.
+    fn convert_type(&self, typ: &Type) -> Result<Type> {
+        match typ {
+            Type::Record(record) => {
+                let mut fields: Vec<Field> = Vec::with_capacity(record.fields.len());
+                for field in record.fields() {
+                    let name = self.convert(field.identifier());
+                    if let Some(other) = fields.iter().position(|f| f.name == name) {
+                        return Err(Error::BackEndError(format!(
+                            "Fields {} and {} of {} are both named {} in {} style",
+                            record.fields[other].name, field.name, record.identifier, name, self
+                        )));
+                    }
+                    fields.push(Field {
+                        name,
+                        typ: self.convert_type(field.typ())?,
+                        reversed: field.reversed,
+                    });
+                }
+                Ok(Type::Record(Record {
+                    identifier: record.identifier.clone(),
+                    fields,
+                }))
+            }
+            _ => Ok(typ.clone()),
+        }
+    }
+}
+
+impl std::str::FromStr for IdentifierStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "preserve" => Ok(IdentifierStyle::Preserve),
+            "snake" => Ok(IdentifierStyle::SnakeCase),
+            "camel" => Ok(IdentifierStyle::CamelCase),
+            _ => Err(Error::InvalidArgument(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for IdentifierStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentifierStyle::Preserve => write!(f, "preserve"),
+            IdentifierStyle::SnakeCase => write!(f, "snake"),
+            IdentifierStyle::CamelCase => write!(f, "camel"),
+        }
+    }
+}
+
 /// A component.
 #[derive(Debug, Clone)]
 pub struct Component {
@@ -333,6 +638,10 @@ pub struct Component {
     ports: Vec<Port>,
     /// Documentation.
     doc: Option<String>,
+    /// The clock and reset of the component.
+    clock_reset: ClockReset,
+    /// Component attributes, as key-value pairs.
+    attributes: Vec<(String, String)>,
 }
 
 impl Identify for Component {
@@ -360,6 +669,8 @@ impl Component {
             parameters,
             ports,
             doc,
+            clock_reset: ClockReset::default(),
+            attributes: vec![],
         }
     }
 
@@ -383,6 +694,98 @@ impl Component {
     pub fn set_doc(&mut self, doc: impl Into<String>) {
         self.doc = Some(doc.into())
     }
+
+    /// Return the attributes of this component.
+    pub fn attributes(&self) -> &[(String, String)] {
+        self.attributes.as_slice()
+    }
+
+    /// Return this component with attributes added.
+    pub fn with_attributes(
+        mut self,
+        attributes: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.attributes
+            .extend(attributes.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Return the clock and reset of this component.
+    pub fn clock_reset(&self) -> &ClockReset {
+        &self.clock_reset
+    }
+
+    /// Return this component with a different clock and reset. The clock and reset ports are
+    /// renamed accordingly, and the reset port is documented if it is not synchronous and
+    /// active-high.
+    ///
+    /// Returns an error if the clock and reset have the same identifier, or if another port
+    /// already has one of their identifiers.
+    pub fn with_clock_reset(mut self, clock_reset: ClockReset) -> Result<Self> {
+        if clock_reset.clock == clock_reset.reset {
+            return Err(Error::BackEndError(format!(
+                "The clock and reset of {} are both named {}",
+                self.identifier, clock_reset.clock
+            )));
+        }
+        if let Some(port) = self.ports.iter().find(|p| {
+            !self.clock_reset.is_clock_or_reset(p)
+                && [clock_reset.clock(), clock_reset.reset()].contains(&p.identifier())
+        }) {
+            return Err(Error::BackEndError(format!(
+                "Port {} of {} conflicts with its clock or reset",
+                port.identifier, self.identifier
+            )));
+        }
+        for port in self.ports.iter_mut() {
+            if port.typ != Type::Bit {
+                continue;
+            }
+            if port.identifier == self.clock_reset.clock {
+                port.identifier = clock_reset.clock.clone();
+            } else if port.identifier == self.clock_reset.reset {
+                port.identifier = clock_reset.reset.clone();
+                port.doc = clock_reset.reset_doc();
+            }
+        }
+        self.clock_reset = clock_reset;
+        Ok(self)
+    }
+
+    /// Return this component with the identifiers of its ports and their record fields
+    /// converted to `style`. The clock and reset ports are left as they are.
+    ///
+    /// Returns an error if ports, or fields of the same record, end up with the same identifier.
+    pub fn with_identifier_style(mut self, style: IdentifierStyle) -> Result<Self> {
+        if style == IdentifierStyle::Preserve {
+            return Ok(self);
+        }
+        let mut ports: Vec<Port> = Vec::with_capacity(self.ports.len());
+        for port in &self.ports {
+            let port = if self.clock_reset.is_clock_or_reset(port) {
+                port.clone()
+            } else {
+                Port {
+                    identifier: style.convert(port.identifier()),
+                    typ: style.convert_type(&port.typ)?,
+                    ..port.clone()
+                }
+            };
+            if let Some(other) = ports.iter().position(|p| p.identifier == port.identifier) {
+                return Err(Error::BackEndError(format!(
+                    "Ports {} and {} of {} are both named {} in {} style",
+                    self.ports[other].identifier,
+                    self.ports[ports.len()].identifier,
+                    self.identifier,
+                    port.identifier,
+                    style
+                )));
+            }
+            ports.push(port);
+        }
+        self.ports = ports;
+        Ok(self)
+    }
 }
 
 /// A library of components and types.
@@ -394,6 +797,29 @@ pub struct Package {
     pub components: Vec<Component>,
 }
 
+impl Package {
+    /// Return this package with a different clock and reset for all of its components.
+    pub fn with_clock_reset(mut self, clock_reset: &ClockReset) -> Result<Self> {
+        self.components = self
+            .components
+            .into_iter()
+            .map(|c| c.with_clock_reset(clock_reset.clone()))
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Return this package with the identifiers of the ports of all of its components, and their
+    /// record fields, converted to `style`.
+    pub fn with_identifier_style(mut self, style: IdentifierStyle) -> Result<Self> {
+        self.components = self
+            .components
+            .into_iter()
+            .map(|c| c.with_identifier_style(style))
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+}
+
 /// A project with libraries
 #[derive(Debug)]
 pub struct Project {
@@ -407,7 +833,7 @@ pub struct Project {
 pub(crate) mod test {
 
     use super::*;
-    use crate::cat;
+    use crate::{cat, Reversed};
 
     pub(crate) mod records {
 
@@ -473,6 +899,8 @@ pub(crate) mod test {
                 Port::new_documented("b", Mode::Out, records::rec_rev_nested("b"), None),
             ],
             doc: None,
+            clock_reset: ClockReset::default(),
+            attributes: vec![],
         }
     }
 
@@ -509,6 +937,66 @@ pub(crate) mod test {
         assert!(!flat[3].2);
     }
 
+    #[test]
+    fn flatten_rec_reversed() {
+        let leaves = |typ: Type, reversed| {
+            typ.flatten(vec![], reversed)
+                .into_iter()
+                .map(|(path, _, reversed)| (path.join("_"), reversed))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            leaves(records::rec_rev("test"), true),
+            vec![("c".to_string(), true), ("d".to_string(), false)]
+        );
+        assert_eq!(
+            leaves(records::rec_rev_nested("test").reversed(), false),
+            vec![
+                ("a_c".to_string(), true),
+                ("a_d".to_string(), true),
+                ("b_c".to_string(), true),
+                ("b_d".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn reverse() {
+        let mut m = Mode::In;
+        m.reverse();
+        assert_eq!(m, Mode::Out);
+        assert_eq!(m.reversed(), Mode::In);
+
+        assert_eq!(
+            records::rec_rev("test").reversed(),
+            Type::record(
+                "test",
+                vec![
+                    Field::new("c", Type::bitvec(42), true),
+                    Field::new("d", Type::bitvec(1337), false),
+                ],
+            )
+        );
+        assert_eq!(
+            records::rec_rev_nested("test").reversed(),
+            Type::record(
+                "test",
+                vec![
+                    Field::new("a", records::rec("test_a"), true),
+                    Field::new("b", records::rec_rev("test_b"), true),
+                ],
+            )
+        );
+        assert_eq!(Type::Bit.reversed(), Type::Bit);
+
+        let port = Port::new("test", Mode::In, records::rec_rev("test"));
+        assert_eq!(
+            port.reversed(),
+            Port::new("test", Mode::Out, records::rec_rev("test"))
+        );
+        assert_eq!(port.reversed().reversed(), port);
+    }
+
     #[test]
     fn has_reversed() {
         assert!(records::rec_rev("test").has_reversed());
@@ -522,4 +1010,91 @@ pub(crate) mod test {
         )
         .has_reversed());
     }
+
+    #[test]
+    fn clock_reset() -> Result<()> {
+        let mut ports = ClockReset::default().ports();
+        ports.push(Port::new("a", Mode::In, Type::Bit));
+        let comp = Component::new("test", vec![], ports.clone(), None);
+
+        let clock_reset = ClockReset::default()
+            .with_clock("clock")
+            .with_reset("reset_n")
+            .with_polarity(ResetPolarity::ActiveLow);
+        let renamed = comp.clone().with_clock_reset(clock_reset.clone())?;
+        assert_eq!(renamed.clock_reset(), &clock_reset);
+        assert_eq!(renamed.ports()[0].identifier(), "clock");
+        assert_eq!(renamed.ports()[1].identifier(), "reset_n");
+        assert_eq!(
+            renamed.ports()[1].doc(),
+            Some(" Active-low synchronous reset.".to_string())
+        );
+        assert_eq!(renamed.ports()[2].identifier(), "a");
+
+        assert!(comp
+            .clone()
+            .with_clock_reset(ClockReset::default().with_reset("a"))
+            .is_err());
+        assert!(comp
+            .with_clock_reset(ClockReset::default().with_reset("clk"))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn identifier_style() -> Result<()> {
+        let mut ports = ClockReset::default().ports();
+        ports.push(Port::new(
+            "in_data",
+            Mode::In,
+            Type::record(
+                "in_data",
+                vec![
+                    Field::new("first_name", Type::Bit, false),
+                    Field::new("lastName", Type::Bit, true),
+                ],
+            ),
+        ));
+        let comp = Component::new("test", vec![], ports, None);
+
+        let camel = comp
+            .clone()
+            .with_identifier_style(IdentifierStyle::CamelCase)?;
+        assert_eq!(camel.ports()[0].identifier(), "clk");
+        assert_eq!(camel.ports()[2].identifier(), "inData");
+        match camel.ports()[2].typ() {
+            Type::Record(record) => assert_eq!(
+                record.fields().map(|f| f.identifier()).collect::<Vec<_>>(),
+                vec!["firstName", "lastName"]
+            ),
+            _ => unreachable!(),
+        }
+
+        let snake = comp.with_identifier_style(IdentifierStyle::SnakeCase)?;
+        match snake.ports()[2].typ() {
+            Type::Record(record) => assert_eq!(
+                record.fields().map(|f| f.identifier()).collect::<Vec<_>>(),
+                vec!["first_name", "last_name"]
+            ),
+            _ => unreachable!(),
+        }
+
+        let colliding = Component::new(
+            "test",
+            vec![],
+            vec![
+                Port::new("a_b", Mode::In, Type::Bit),
+                Port::new("aB", Mode::In, Type::Bit),
+            ],
+            None,
+        );
+        assert_eq!(
+            colliding
+                .with_identifier_style(IdentifierStyle::CamelCase)
+                .unwrap_err()
+                .to_string(),
+            "Back-end error: Ports a_b and aB of test are both named aB in camel style"
+        );
+        Ok(())
+    }
 }
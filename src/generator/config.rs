@@ -0,0 +1,700 @@
+//! Generation configuration.
+//!
+//! This module defines the [GenerationConfig], which combines the configurations of all back-ends
+//! with the [HeaderConfig] and [SynthesisOptions] that apply to all of them, and can be read from
+//! a configuration file (by default `tydi.toml`).
+//!
+//! The configuration file uses a subset of TOML: one table per back-end and a `[header]` and
+//! `[synthesis]` table, with keys set to strings, integers or booleans. Comments start with `#`.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::generator::config::GenerationConfig;
+//! use tydi::generator::vhdl::AbstractionLevel;
+//!
+//! let config = GenerationConfig::from_toml(
+//!     r#"
+//!     ## VHDL back-end options.
+//!     [vhdl]
+//!     abstraction = "fancy"
+//!     suffix = "gen"
+//!     "#,
+//! )?;
+//! assert_eq!(config.vhdl.abstraction(), AbstractionLevel::Fancy);
+//!
+//! // Typos are reported with a suggestion.
+//! let err = GenerationConfig::from_toml("[vhdl]\nsufix = \"gen\"").unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "Configuration error: line 2: unknown key \"sufix\" in [vhdl], did you mean \"suffix\"?"
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::generator::chisel::ChiselConfig;
+use crate::generator::common::IdentifierStyle;
+#[cfg(feature = "firrtl")]
+use crate::generator::firrtl::FirrtlConfig;
+use crate::generator::header::HeaderConfig;
+use crate::generator::vhdl::VHDLConfig;
+use crate::logical::{FieldOrder, SynthesisOptions, UnionLowering};
+use crate::util::suggest;
+use crate::{Context, Error, PathName, Positive, Result};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::Path;
+
+/// Default file name of the generation configuration file.
+pub const CONFIG_FILE_NAME: &str = "tydi.toml";
+
+/// A configuration value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A string.
+    String(String),
+    /// An integer.
+    Integer(i64),
+    /// A boolean.
+    Boolean(bool),
+}
+
+impl Value {
+    /// Returns the string, or an error mentioning the key if this is not a string.
+    pub fn expect_str(&self, key: &str) -> Result<&str> {
+        match self {
+            Value::String(s) => Ok(s.as_str()),
+            _ => Err(self.mismatch(key, "a string")),
+        }
+    }
+
+    /// Returns the integer, or an error mentioning the key if this is not an integer.
+    pub fn expect_integer(&self, key: &str) -> Result<i64> {
+        match self {
+            Value::Integer(i) => Ok(*i),
+            _ => Err(self.mismatch(key, "an integer")),
+        }
+    }
+
+    /// Returns the boolean, or an error mentioning the key if this is not a boolean.
+    pub fn expect_bool(&self, key: &str) -> Result<bool> {
+        match self {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(self.mismatch(key, "a boolean")),
+        }
+    }
+
+    fn mismatch(&self, key: &str, expected: &str) -> Error {
+        Error::ConfigError(format!(
+            "expected {} for key \"{}\", found {}",
+            expected, key, self
+        ))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Trait for configurations that can be set up from a section of a configuration file.
+pub trait Configure {
+    /// The name of the section (table) of this configuration.
+    const SECTION: &'static str;
+    /// The keys accepted in the section.
+    const KEYS: &'static [&'static str];
+
+    /// Set the option with the given key to the given value.
+    ///
+    /// The key is guaranteed to be one of [Configure::KEYS].
+    fn set(&mut self, key: &str, value: &Value) -> Result<()>;
+
+    /// Override the options of self with the options that are set in `overrides`, e.g. to give
+    /// options passed on the command line precedence over those of a configuration file. Flags
+    /// are considered set if they are enabled, or disabled with their `no_` counterpart, see
+    /// [merge_flag].
+    fn merge(&mut self, overrides: Self)
+    where
+        Self: Sized;
+}
+
+/// Merge a flag with the flags of its overrides that enable and disable it.
+pub fn merge_flag(flag: &mut bool, enable: bool, disable: bool) {
+    if disable {
+        *flag = false;
+    } else {
+        *flag |= enable;
+    }
+}
+
+/// Configuration of all back-ends.
+#[derive(Debug, Default)]
+pub struct GenerationConfig {
+    /// VHDL back-end configuration.
+    pub vhdl: VHDLConfig,
+    /// Chisel back-end configuration.
+    pub chisel: ChiselConfig,
+    /// FIRRTL back-end configuration.
+    #[cfg(feature = "firrtl")]
+    pub firrtl: FirrtlConfig,
+    /// Header of the files generated by all back-ends.
+    pub header: HeaderConfig,
+    /// Options to convert the throughput of streams to element lanes, for all back-ends.
+    pub synthesis: SynthesisOptions,
+}
+
+impl GenerationConfig {
+    /// The sections accepted in a configuration file.
+    pub const SECTIONS: &'static [&'static str] = &[
+        VHDLConfig::SECTION,
+        ChiselConfig::SECTION,
+        #[cfg(feature = "firrtl")]
+        FirrtlConfig::SECTION,
+        HeaderConfig::SECTION,
+        SynthesisOptions::SECTION,
+    ];
+
+    /// Read a configuration from a string, starting from the default configuration.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        let mut config = GenerationConfig::default();
+        let mut section: Option<&str> = None;
+        let mut seen = HashSet::new();
+
+        for (idx, line) in input.lines().enumerate() {
+            let at_line = |e: Error| match e {
+                Error::ConfigError(msg) => Error::ConfigError(format!("line {}: {}", idx + 1, msg)),
+                e => e,
+            };
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| at_line(Error::ConfigError("unterminated table header".into())))?
+                    .trim();
+                section = Some(
+                    Self::SECTIONS
+                        .iter()
+                        .find(|s| **s == name)
+                        .ok_or_else(|| at_line(unknown("table", name, "", Self::SECTIONS)))?,
+                );
+                if !seen.insert((name.to_string(), None)) {
+                    return Err(at_line(Error::ConfigError(format!(
+                        "duplicate table [{}]",
+                        name
+                    ))));
+                }
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                at_line(Error::ConfigError(format!(
+                    "expected a table header or key = value, found \"{}\"",
+                    line
+                )))
+            })?;
+            let key = key.trim();
+            let value = parse_value(value.trim()).map_err(at_line)?;
+            let section = section.ok_or_else(|| {
+                at_line(Error::ConfigError(format!(
+                    "key \"{}\" must be placed in one of the tables: [{}]",
+                    key,
+                    Self::SECTIONS.join("], [")
+                )))
+            })?;
+            if !seen.insert((section.to_string(), Some(key.to_string()))) {
+                return Err(at_line(Error::ConfigError(format!(
+                    "duplicate key \"{}\" in [{}]",
+                    key, section
+                ))));
+            }
+            match section {
+                VHDLConfig::SECTION => set(&mut config.vhdl, key, &value),
+                ChiselConfig::SECTION => set(&mut config.chisel, key, &value),
+                #[cfg(feature = "firrtl")]
+                FirrtlConfig::SECTION => set(&mut config.firrtl, key, &value),
+                HeaderConfig::SECTION => set(&mut config.header, key, &value),
+                SynthesisOptions::SECTION => set(&mut config.synthesis, key, &value),
+                _ => unreachable!(),
+            }
+            .map_err(at_line)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Read a configuration from a file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path)
+            .map_err(Error::from)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml(&input).map_err(|e| match e {
+            Error::ConfigError(msg) => Error::ConfigError(format!("{}: {}", path.display(), msg)),
+            e => e,
+        })
+    }
+}
+
+impl Configure for SynthesisOptions {
+    const SECTION: &'static str = "synthesis";
+    const KEYS: &'static [&'static str] = &[
+        "rounding",
+        "max_lanes",
+        "lanes",
+        "union_lowering",
+        "unions",
+        "field_order",
+        "split_user",
+    ];
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<()> {
+        let options = std::mem::take(self);
+        *self = match key {
+            "rounding" => {
+                let rounding = value.expect_str(key)?;
+                options.with_rounding(rounding.parse().map_err(|_| {
+                    Error::ConfigError(format!(
+                        "unknown rounding strategy \"{}\", expected one of: up, down, nearest",
+                        rounding
+                    ))
+                })?)
+            }
+            "max_lanes" => options.with_max_lanes(expect_positive(key, value)?),
+            "lanes" => value
+                .expect_str(key)?
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(parse_lanes)
+                .try_fold(options, |options, lanes| {
+                    lanes.map(|(path, lanes)| options.with_lanes(path, lanes))
+                })?,
+            "union_lowering" => {
+                options.with_union_lowering(parse_union_lowering(value.expect_str(key)?)?)
+            }
+            "unions" => value
+                .expect_str(key)?
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(parse_union)
+                .try_fold(options, |options, union| {
+                    union.map(|(path, lowering)| options.with_union_lowering_at(path, lowering))
+                })?,
+            "field_order" => {
+                let order = value.expect_str(key)?;
+                options.with_field_order(order.parse::<FieldOrder>().map_err(|_| {
+                    Error::ConfigError(format!(
+                        "unknown field order \"{}\", expected one of: declaration, name",
+                        order
+                    ))
+                })?)
+            }
+            "split_user" => options.with_split_user(value.expect_bool(key)?),
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    fn merge(&mut self, overrides: Self) {
+        let mut options = std::mem::take(self);
+        if overrides.rounding() != Default::default() {
+            options = options.with_rounding(overrides.rounding());
+        }
+        if let Some(max_lanes) = overrides.max_lanes() {
+            options = options.with_max_lanes(max_lanes);
+        }
+        for (path, lanes) in overrides.lanes() {
+            options = options.with_lanes(path.clone(), *lanes);
+        }
+        if overrides.union_lowering() != Default::default() {
+            options = options.with_union_lowering(overrides.union_lowering());
+        }
+        for (path, lowering) in overrides.unions() {
+            options = options.with_union_lowering_at(path.clone(), *lowering);
+        }
+        if overrides.field_order() != Default::default() {
+            options = options.with_field_order(overrides.field_order());
+        }
+        if overrides.split_user() {
+            options = options.with_split_user(true);
+        }
+        *self = options;
+    }
+}
+
+/// Parse the number of element lanes of a stream, given as
+/// `<library>.<streamlet>.<interface>[.<path>]=<lanes>`, where the path consists of the names of
+/// the fields that lead to the stream, separated by dots.
+pub fn parse_lanes(input: &str) -> Result<(PathName, Positive)> {
+    let (name, lanes) = input.split_once('=').ok_or_else(|| {
+        Error::ConfigError(format!(
+            "expected <library>.<streamlet>.<interface>[.<path>]=<lanes>, found \"{}\"",
+            input
+        ))
+    })?;
+    let path = parse_path(name.trim(), "stream")?;
+    let lanes = lanes.trim().parse().map_err(|_| {
+        Error::ConfigError(format!(
+            "expected a positive number of lanes, found \"{}\"",
+            lanes.trim()
+        ))
+    })?;
+    Ok((path, lanes))
+}
+
+/// Parse the lowering of a Union, given as
+/// `<library>.<streamlet>.<interface>[.<path>]=<lowering>`, where the path consists of the names
+/// of the fields that lead to the Union, separated by dots.
+pub fn parse_union(input: &str) -> Result<(PathName, UnionLowering)> {
+    let (name, lowering) = input.split_once('=').ok_or_else(|| {
+        Error::ConfigError(format!(
+            "expected <library>.<streamlet>.<interface>[.<path>]=<lowering>, found \"{}\"",
+            input
+        ))
+    })?;
+    let path = parse_path(name.trim(), "union")?;
+    Ok((path, parse_union_lowering(lowering.trim())?))
+}
+
+/// Parse a union lowering strategy.
+fn parse_union_lowering(input: &str) -> Result<UnionLowering> {
+    input.parse().map_err(|_| {
+        Error::ConfigError(format!(
+            "unknown union lowering \"{}\", expected one of: overlay, streams",
+            input
+        ))
+    })
+}
+
+/// Parse the path of a `kind` of type within an interface, which must start with a library,
+/// streamlet and interface.
+fn parse_path(name: &str, kind: &str) -> Result<PathName> {
+    let path = PathName::try_new(name.split('.'))
+        .map_err(|e| Error::ConfigError(format!("invalid {} path \"{}\": {}", kind, name, e)))?;
+    if path.len() < 3 {
+        return Err(Error::ConfigError(format!(
+            "{} path \"{}\" must start with a library, streamlet and interface",
+            kind, name
+        )));
+    }
+    Ok(path)
+}
+
+/// Returns the positive integer of a value, or an error mentioning the key.
+fn expect_positive(key: &str, value: &Value) -> Result<Positive> {
+    let integer = value.expect_integer(key)?;
+    u32::try_from(integer)
+        .ok()
+        .and_then(Positive::new)
+        .ok_or_else(|| {
+            Error::ConfigError(format!(
+                "expected a positive integer for key \"{}\", found {}",
+                key, integer
+            ))
+        })
+}
+
+/// Returns the identifier style of a value, or an error mentioning the key.
+pub(crate) fn expect_identifier_style(key: &str, value: &Value) -> Result<IdentifierStyle> {
+    let style = value.expect_str(key)?;
+    style.parse().map_err(|_| {
+        Error::ConfigError(format!(
+            "unknown identifier style \"{}\", expected one of: preserve, snake, camel",
+            style
+        ))
+    })
+}
+
+/// Set a key of a configuration after validating it against the accepted keys.
+fn set<T: Configure>(config: &mut T, key: &str, value: &Value) -> Result<()> {
+    if T::KEYS.contains(&key) {
+        config.set(key, value)
+    } else {
+        Err(unknown(
+            "key",
+            key,
+            &format!(" in [{}]", T::SECTION),
+            T::KEYS,
+        ))
+    }
+}
+
+/// Returns an error for an unknown name, with a suggestion if there is a likely candidate.
+fn unknown(what: &str, name: &str, context: &str, candidates: &[&str]) -> Error {
+    Error::ConfigError(match suggest(name, candidates.iter().copied()) {
+        Some(s) => format!(
+            "unknown {} \"{}\"{}, did you mean \"{}\"?",
+            what, name, context, s
+        ),
+        None => format!(
+            "unknown {} \"{}\"{}, expected one of: {}",
+            what,
+            name,
+            context,
+            candidates.join(", ")
+        ),
+    })
+}
+
+/// Strip a comment from a line, ignoring comment characters inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '\\' if in_string => escaped = !escaped,
+            '"' if !escaped => in_string = !in_string,
+            '#' if !in_string => return &line[..idx],
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+/// Parse a configuration value.
+fn parse_value(input: &str) -> Result<Value> {
+    if let Some(quoted) = input.strip_prefix('"') {
+        let quoted = quoted
+            .strip_suffix('"')
+            .ok_or_else(|| Error::ConfigError(format!("unterminated string: {}", input)))?;
+        let mut result = String::with_capacity(quoted.len());
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                result.push(match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    _ => {
+                        return Err(Error::ConfigError(format!(
+                            "invalid escape sequence in string: {}",
+                            input
+                        )))
+                    }
+                });
+            } else {
+                result.push(c);
+            }
+        }
+        Ok(Value::String(result))
+    } else if let Ok(b) = input.parse::<bool>() {
+        Ok(Value::Boolean(b))
+    } else if let Ok(i) = input.replace('_', "").parse::<i64>() {
+        Ok(Value::Integer(i))
+    } else {
+        Err(Error::ConfigError(format!(
+            "invalid value \"{}\", expected a string, integer or boolean",
+            input
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::vhdl::AbstractionLevel;
+    use crate::logical::Rounding;
+
+    #[test]
+    fn parse_values() -> Result<()> {
+        assert_eq!(
+            parse_value("\"a # b\"")?,
+            Value::String("a # b".to_string())
+        );
+        assert_eq!(
+            parse_value(r#""a\"b\\""#)?,
+            Value::String("a\"b\\".to_string())
+        );
+        assert_eq!(parse_value("true")?, Value::Boolean(true));
+        assert_eq!(parse_value("1_000")?, Value::Integer(1000));
+        assert_eq!(parse_value("-3")?, Value::Integer(-3));
+        assert!(parse_value("\"open").is_err());
+        assert!(parse_value("fancy").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(strip_comment("a = 1 # comment"), "a = 1 ");
+        assert_eq!(strip_comment("a = \"#\" # comment"), "a = \"#\" ");
+        assert_eq!(strip_comment("a = \"\\\"#\""), "a = \"\\\"#\"");
+    }
+
+    #[test]
+    fn config() -> Result<()> {
+        let config = GenerationConfig::from_toml(
+            "# Options\n\n[vhdl]\nabstraction = \"canon\" # inline\nsuffix = \"\"\nassertions = true\n\n[chisel]\nsuffix = \"tydi\"\n",
+        )?;
+        assert_eq!(config.vhdl.abstraction(), AbstractionLevel::Canonical);
+        assert_eq!(config.vhdl.suffix(), None);
+        assert!(config.vhdl.assertions());
+        assert_eq!(
+            GenerationConfig::from_toml("")?.vhdl.abstraction(),
+            VHDLConfig::default().abstraction()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge() -> Result<()> {
+        let mut config = GenerationConfig::from_toml(
+            "[vhdl]\nabstraction = \"canon\"\nsuffix = \"x\"\nassertions = true\noutput = \"out\"",
+        )?
+        .vhdl;
+        let mut overrides = GenerationConfig::from_toml("[vhdl]\nabstraction = \"fancy\"")?.vhdl;
+        overrides.set("suffix", &Value::String("".to_string()))?;
+        config.merge(overrides);
+        assert_eq!(config.abstraction(), AbstractionLevel::Fancy);
+        assert_eq!(config.suffix(), Some("x"));
+        assert!(config.assertions());
+        assert_eq!(config.output(), Some(Path::new("out")));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn merge_flags() -> Result<()> {
+        use structopt::StructOpt;
+
+        let mut config = GenerationConfig::from_toml(
+            "[vhdl]\nassertions = true\nstubs = true\n[chisel]\nsbt = true\n[header]\ntimestamp = true",
+        )?;
+        config.vhdl.merge(VHDLConfig::from_iter(&[
+            "vhdl",
+            "--no-assertions",
+            "--context",
+        ]));
+        assert!(!config.vhdl.assertions());
+        assert!(config.vhdl.stubs());
+        assert!(config.vhdl.context());
+        config
+            .chisel
+            .merge(ChiselConfig::from_iter(&["chisel", "--no-sbt"]));
+        assert!(!config.chisel.sbt());
+        config
+            .header
+            .merge(HeaderConfig::from_iter(&["header", "--no-timestamp"]));
+        assert!(!config.header.timestamp());
+        #[cfg(feature = "firrtl")]
+        {
+            let mut config = GenerationConfig::from_toml("[firrtl]\nasync_reset = true")?.firrtl;
+            config.merge(FirrtlConfig::from_iter(&["firrtl", "--no-async-reset"]));
+            assert_eq!(
+                config.clock_reset(),
+                crate::generator::common::ClockReset::default()
+            );
+        }
+        assert!(VHDLConfig::from_iter_safe(&["vhdl", "--stubs", "--no-stubs"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn synthesis() -> Result<()> {
+        let config = GenerationConfig::from_toml(
+            "[synthesis]\nrounding = \"down\"\nmax_lanes = 4\nlanes = \"lib.x.a=8, lib.x.b.c=2\"",
+        )?
+        .synthesis;
+        assert_eq!(
+            config,
+            SynthesisOptions::new()
+                .with_rounding(Rounding::Down)
+                .with_max_lanes(Positive::new(4).unwrap())
+                .with_lanes(
+                    PathName::try_new(vec!["lib", "x", "a"])?,
+                    Positive::new(8).unwrap()
+                )
+                .with_lanes(
+                    PathName::try_new(vec!["lib", "x", "b", "c"])?,
+                    Positive::new(2).unwrap()
+                )
+        );
+
+        let err = |input: &str| GenerationConfig::from_toml(input).unwrap_err().to_string();
+        assert_eq!(
+            err("[synthesis]\nmax_lanes = 0"),
+            "Configuration error: line 2: expected a positive integer for key \"max_lanes\", found 0"
+        );
+        assert_eq!(
+            err("[synthesis]\nlanes = \"x.a=2\""),
+            "Configuration error: line 2: stream path \"x.a\" must start with a library, streamlet and interface"
+        );
+        assert_eq!(
+            parse_lanes("lib.x.a=none").unwrap_err().to_string(),
+            "Configuration error: expected a positive number of lanes, found \"none\""
+        );
+
+        let config = GenerationConfig::from_toml(
+            "[synthesis]\nunion_lowering = \"streams\"\nunions = \"lib.x.a.b=overlay\"\nfield_order = \"name\"\nsplit_user = true",
+        )?
+        .synthesis;
+        assert_eq!(config.field_order(), FieldOrder::Name);
+        assert!(config.split_user());
+        let path = PathName::try_new(vec!["lib", "x", "a", "b"])?;
+        assert_eq!(config.union_lowering(), UnionLowering::Streams);
+        assert_eq!(config.union_lowering_at(&path), UnionLowering::Overlay);
+        assert_eq!(
+            err("[synthesis]\nunion_lowering = \"split\""),
+            "Configuration error: line 2: unknown union lowering \"split\", expected one of: overlay, streams"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn config_errors() {
+        let err = |input: &str| GenerationConfig::from_toml(input).unwrap_err().to_string();
+        assert_eq!(
+            err("[vhld]"),
+            "Configuration error: line 1: unknown table \"vhld\", did you mean \"vhdl\"?"
+        );
+        assert_eq!(
+            err("[verilog]"),
+            format!(
+                "Configuration error: line 1: unknown table \"verilog\", expected one of: {}",
+                GenerationConfig::SECTIONS.join(", ")
+            )
+        );
+        assert_eq!(
+            err("suffix = \"gen\""),
+            format!(
+                "Configuration error: line 1: key \"suffix\" must be placed in one of the tables: [{}]",
+                GenerationConfig::SECTIONS.join("], [")
+            )
+        );
+        assert_eq!(
+            err("[vhdl]\n\nabstraction = true"),
+            "Configuration error: line 3: expected a string for key \"abstraction\", found true"
+        );
+        assert_eq!(
+            err("[vhdl]\nabstraction = \"fnacy\""),
+            "Configuration error: line 2: unknown abstraction level \"fnacy\", expected one of: canon, fancy"
+        );
+        assert_eq!(
+            err("[vhdl]\nsuffix = \"a\"\nsuffix = \"b\""),
+            "Configuration error: line 3: duplicate key \"suffix\" in [vhdl]"
+        );
+        assert_eq!(
+            err("[vhdl]\n[vhdl]"),
+            "Configuration error: line 2: duplicate table [vhdl]"
+        );
+        assert_eq!(
+            err("[vhdl\n"),
+            "Configuration error: line 1: unterminated table header"
+        );
+        assert_eq!(
+            err("[vhdl]\nsuffix"),
+            "Configuration error: line 2: expected a table header or key = value, found \"suffix\""
+        );
+    }
+}
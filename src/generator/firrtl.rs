@@ -0,0 +1,353 @@
+//! FIRRTL back-end.
+//!
+//! Generates a FIRRTL circuit for the canonical component of every streamlet, such that the
+//! components can be used with hardware compiler toolchains that take FIRRTL as input. Record
+//! ports are flattened to ground-typed ports like the canonical representation itself. Since
+//! streamlets have no implementation, the outputs of the generated modules are invalidated, to
+//! be replaced by the user or by other tools.
+//!
+//! The back-end is enabled by the `firrtl` feature flag.
+
+use crate::design::Project;
+use crate::experimental::Features;
+use crate::generator::common::{ClockReset, Component, Mode, ResetStyle, Type};
+use crate::generator::config::{merge_flag, Configure, Value};
+use crate::generator::header::HeaderConfig;
+use crate::generator::manifest::Manifest;
+use crate::generator::{write_if_changed, GenerateProject, Lowered};
+use crate::traits::{Document, Identify};
+use crate::{Error, Result};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+/// The FIRRTL version of generated circuits.
+pub const FIRRTL_VERSION: &str = "1.1.0";
+
+/// Returns a FIRRTL comment of a documentation string, indented with `indent`.
+fn comment(doc: &str, indent: &str) -> String {
+    doc.lines()
+        .map(|line| format!("{};{}\n", indent, line))
+        .collect()
+}
+
+/// Returns the FIRRTL ground type of a type that is not a record.
+fn ground_type(typ: &Type) -> Result<String> {
+    match typ {
+        Type::Bit => Ok("UInt<1>".to_string()),
+        Type::BitVec { width } => Ok(format!("UInt<{}>", width)),
+        Type::Record(_) => Err(Error::BackEndError(
+            "records must be flattened to ground types".to_string(),
+        )),
+    }
+}
+
+/// Returns a FIRRTL circuit with a single module that declares the ports of `component`, and
+/// invalidates its outputs.
+pub fn declare_circuit(component: &Component) -> Result<String> {
+    let clock_reset = component.clock_reset();
+    let mut ports = String::new();
+    let mut outputs = vec![];
+    for port in component.ports() {
+        if let Some(doc) = port.doc() {
+            ports.push_str(comment(doc.as_str(), "    ").as_str());
+        }
+        if clock_reset.is_clock_or_reset(port) {
+            let typ = if port.identifier() == clock_reset.clock() {
+                "Clock"
+            } else {
+                match clock_reset.style() {
+                    ResetStyle::Sync => "UInt<1>",
+                    ResetStyle::Async => "AsyncReset",
+                }
+            };
+            ports.push_str(format!("    input {} : {}\n", port.identifier(), typ).as_str());
+            continue;
+        }
+        for (path, typ, reversed) in port
+            .typ()
+            .flatten(vec![port.identifier().to_string()], false)
+        {
+            let identifier = path.join("_");
+            let output = (port.mode() == Mode::Out) != reversed;
+            ports.push_str(
+                format!(
+                    "    {} {} : {}\n",
+                    if output { "output" } else { "input" },
+                    identifier,
+                    ground_type(&typ)?
+                )
+                .as_str(),
+            );
+            if output {
+                outputs.push(identifier);
+            }
+        }
+    }
+
+    let mut result = format!("FIRRTL version {}\n", FIRRTL_VERSION);
+    result.push_str(format!("circuit {} :\n", component.identifier()).as_str());
+    if let Some(doc) = component.doc() {
+        result.push_str(comment(doc.as_str(), "  ").as_str());
+    }
+    result.push_str(format!("  module {} :\n", component.identifier()).as_str());
+    result.push_str(ports.as_str());
+    result.push('\n');
+    if outputs.is_empty() {
+        result.push_str("    skip\n");
+    }
+    for output in outputs {
+        result.push_str(format!("    {} is invalid\n", output).as_str());
+    }
+    Ok(result)
+}
+
+/// FIRRTL back-end configuration parameters.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct FirrtlConfig {
+    /// Suffix of generated files. Default = "gen", such that
+    /// generated files are named <name>.gen.fir.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    suffix: Option<String>,
+
+    /// Use an asynchronous reset, rather than a synchronous reset.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    async_reset: bool,
+
+    /// Disable --async-reset, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "async_reset"))]
+    no_async_reset: bool,
+
+    /// Output directory, if not given on the command line. Only set from configuration files.
+    #[cfg_attr(feature = "cli", structopt(skip))]
+    output: Option<PathBuf>,
+}
+
+impl FirrtlConfig {
+    /// Returns the suffix of generated files, if any.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns the clock and reset of the generated modules.
+    pub fn clock_reset(&self) -> ClockReset {
+        if self.async_reset {
+            ClockReset::default().with_style(ResetStyle::Async)
+        } else {
+            ClockReset::default()
+        }
+    }
+
+    /// Returns the configured output directory, if any.
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+}
+
+impl Default for FirrtlConfig {
+    fn default() -> Self {
+        FirrtlConfig {
+            suffix: Some("gen".to_string()),
+            async_reset: false,
+            no_async_reset: false,
+            output: None,
+        }
+    }
+}
+
+impl Configure for FirrtlConfig {
+    const SECTION: &'static str = "firrtl";
+    const KEYS: &'static [&'static str] = &["suffix", "async_reset", "output"];
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<()> {
+        match key {
+            "suffix" => {
+                let suffix = value.expect_str(key)?;
+                self.suffix = if suffix.is_empty() {
+                    None
+                } else {
+                    Some(suffix.to_string())
+                };
+            }
+            "async_reset" => self.async_reset = value.expect_bool(key)?,
+            "output" => self.output = Some(PathBuf::from(value.expect_str(key)?)),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, overrides: Self) {
+        self.suffix = overrides.suffix.or_else(|| self.suffix.take());
+        merge_flag(
+            &mut self.async_reset,
+            overrides.async_reset,
+            overrides.no_async_reset,
+        );
+        self.output = overrides.output.or_else(|| self.output.take());
+    }
+}
+
+/// A configurable FIRRTL back-end entry point.
+#[derive(Default)]
+pub struct FirrtlBackEnd {
+    /// Configuration for the FIRRTL back-end.
+    config: FirrtlConfig,
+    /// Whether to write files that have not changed.
+    force: bool,
+    /// Whether to write a manifest of the generated files.
+    manifest: bool,
+    /// The experimental features that are listed in the manifest.
+    features: Features,
+    /// The header of generated files.
+    header: HeaderConfig,
+}
+
+impl FirrtlBackEnd {
+    pub fn config(&self) -> &FirrtlConfig {
+        &self.config
+    }
+
+    /// Return this back-end that writes all files, even if their contents did not change.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Return this back-end that writes a [manifest](crate::generator::manifest) of the
+    /// generated files to the project directory.
+    pub fn with_manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Return this back-end that lists the enabled experimental `features` in its manifest.
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Return this back-end that starts generated files with a [header](crate::generator::header).
+    pub fn with_header(mut self, header: HeaderConfig) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl From<FirrtlConfig> for FirrtlBackEnd {
+    fn from(config: FirrtlConfig) -> Self {
+        FirrtlBackEnd {
+            config,
+            force: false,
+            manifest: false,
+            features: Features::default(),
+            header: HeaderConfig::default(),
+        }
+    }
+}
+
+impl GenerateProject for FirrtlBackEnd {
+    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        self.generate_lowered(&Lowered::new(project)?, path)
+    }
+
+    fn generate_lowered(&self, lowered: &Lowered, path: impl AsRef<Path>) -> Result<()> {
+        let project = lowered.project();
+        // Create the project directory.
+        let mut dir = path.as_ref().to_path_buf();
+        dir.push(project.identifier());
+
+        let mut changed = 0;
+        let mut manifest = Manifest::new(project, dir.as_path()).with_features(&self.features);
+        for lib in project.libraries() {
+            let lib_dir = dir.join(lib.identifier());
+            std::fs::create_dir_all(lib_dir.as_path())?;
+            let package = lowered
+                .canonical(lib.identifier())
+                .with_clock_reset(&self.config.clock_reset())?;
+            for component in &package.components {
+                let mut file: PathBuf = lib_dir.join(component.identifier());
+                file.set_extension(match self.config.suffix() {
+                    None => "fir".to_string(),
+                    Some(s) => format!("{}.fir", s),
+                });
+                let contents = declare_circuit(component)?;
+                // The version must remain the first line of the circuit.
+                let contents = match contents.split_once('\n') {
+                    Some((version, circuit)) if self.header.enabled() => format!(
+                        "{}\n{}",
+                        version,
+                        self.header
+                            .apply(project.identifier(), ";", circuit.to_string())
+                    ),
+                    _ => contents,
+                };
+                manifest.add(
+                    file.as_path(),
+                    contents.as_bytes(),
+                    lowered
+                        .streamlet(lib.identifier(), component.identifier())
+                        .map(|s| (lib.identifier(), s)),
+                );
+                if write_if_changed(file.as_path(), contents, self.force)? {
+                    info!("Wrote {}.", file.display());
+                    changed += 1;
+                } else {
+                    debug!("Skipped {}, unchanged.", file.display());
+                }
+            }
+        }
+
+        if self.manifest && manifest.write(self.force)? {
+            info!("Wrote {}.", manifest.path().display());
+        }
+
+        info!("{} file(s) changed.", changed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::{Field, Port};
+
+    #[test]
+    fn circuit() -> Result<()> {
+        let mut ports = ClockReset::default().ports();
+        ports.push(Port::new_documented(
+            "a",
+            Mode::In,
+            Type::record(
+                "a",
+                vec![
+                    Field::new("valid", Type::Bit, false),
+                    Field::new("ready", Type::Bit, true),
+                    Field::new("data", Type::bitvec(8), false),
+                ],
+            ),
+            Some(" Input.".to_string()),
+        ));
+        let component = Component::new("test_com", vec![], ports, None)
+            .with_clock_reset(ClockReset::default().with_style(ResetStyle::Async))?;
+        assert_eq!(
+            declare_circuit(&component)?,
+            "FIRRTL version 1.1.0
+circuit test_com :
+  module test_com :
+    input clk : Clock
+    ; Active-high asynchronous reset.
+    input rst : AsyncReset
+    ; Input.
+    input a_valid : UInt<1>
+    output a_ready : UInt<1>
+    input a_data : UInt<8>
+
+    a_ready is invalid
+"
+        );
+        Ok(())
+    }
+}
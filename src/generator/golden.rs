@@ -0,0 +1,197 @@
+//! Golden-file test utilities.
+//!
+//! Compares the output of back-ends with golden files that are checked in with the tests, such
+//! that changes to the generated sources, including their formatting, show up as test failures
+//! and as diffs in review. Output is [normalized] before it is compared, such that line endings
+//! and trailing whitespace do not cause spurious failures.
+//!
+//! When the [`UPDATE_GOLDEN`] environment variable is set, the golden files are (re)written with
+//! the actual output instead, e.g.:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --features golden
+//! ```
+//!
+//! The module is enabled by the `golden` feature flag.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::generator::golden;
+//!
+//! let dir = tempfile::tempdir()?;
+//! let golden = dir.path().join("x.vhd");
+//! std::fs::write(&golden, "entity x is\r\nend x;   \n\n")?;
+//! golden::check_file(&golden, "entity x is\nend x;\n")?;
+//! assert!(golden::check_file(&golden, "entity y is\nend y;\n").is_err());
+//! # Ok::<(), tydi::Error>(())
+//! ```
+//!
+//! [normalized]: fn.normalize.html
+//! [`UPDATE_GOLDEN`]: constant.UPDATE_GOLDEN.html
+
+use crate::{Context, Error, Result};
+use std::path::{Path, PathBuf};
+
+/// The environment variable that, when set, updates golden files instead of checking them.
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+/// Returns true if golden files should be updated rather than checked.
+pub fn update() -> bool {
+    std::env::var_os(UPDATE_GOLDEN).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Returns `text` with normalized whitespace: line endings are converted to `\n`, trailing
+/// whitespace is removed from all lines, leading and trailing empty lines are removed, and the
+/// result ends with a single newline, unless it is empty.
+pub fn normalize(text: &str) -> String {
+    let lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+    let start = lines.iter().position(|l| !l.is_empty());
+    let end = lines.iter().rposition(|l| !l.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let mut result = lines[start..=end].join("\n");
+            result.push('\n');
+            result
+        }
+        _ => String::new(),
+    }
+}
+
+/// Compares `actual` with `expected` after normalizing both. Returns an error that points out
+/// the first line that differs, with `name` to identify the output.
+pub fn compare(name: &str, actual: &str, expected: &str) -> Result<()> {
+    let (actual, expected) = (normalize(actual), normalize(expected));
+    if actual == expected {
+        return Ok(());
+    }
+    let (mut actual_lines, mut expected_lines) = (actual.lines(), expected.lines());
+    let mut line = 1;
+    loop {
+        match (actual_lines.next(), expected_lines.next()) {
+            (a, e) if a == e => line += 1,
+            (a, e) => {
+                return Err(Error::BackEndError(format!(
+                    "{} differs from its golden file at line {}:\n  expected: {}\n  actual:   {}\nSet {}=1 to update the golden file.",
+                    name,
+                    line,
+                    e.unwrap_or("<end of file>"),
+                    a.unwrap_or("<end of file>"),
+                    UPDATE_GOLDEN
+                )));
+            }
+        }
+    }
+}
+
+/// Checks `actual` against the golden file at `path`, or writes `actual` to `path` if golden
+/// files are updated.
+pub fn check_file(path: impl AsRef<Path>, actual: &str) -> Result<()> {
+    let path = path.as_ref();
+    if update() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, normalize(actual))?;
+        return Ok(());
+    }
+    let expected = std::fs::read_to_string(path)
+        .map_err(Error::from)
+        .with_context(|| {
+            format!(
+                "unable to read golden file {}. Set {}=1 to create it.",
+                path.display(),
+                UPDATE_GOLDEN
+            )
+        })?;
+    compare(
+        format!("output for {}", path.display()).as_str(),
+        actual,
+        expected.as_str(),
+    )
+}
+
+/// Returns the paths of all files in `dir` and its subdirectories, relative to `dir`, sorted.
+fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut result = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        for entry in std::fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else {
+                result.push(path);
+            }
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Checks all files that a back-end generated in `output` against the golden files with the
+/// same relative paths in `golden`. Files that are missing from either directory are reported as
+/// errors. If golden files are updated, `golden` is replaced by a copy of `output`.
+pub fn check_dir(output: impl AsRef<Path>, golden: impl AsRef<Path>) -> Result<()> {
+    let (output, golden) = (output.as_ref(), golden.as_ref());
+    let actual = files(output)?;
+    if update() {
+        if golden.exists() {
+            std::fs::remove_dir_all(golden)?;
+        }
+        for path in &actual {
+            check_file(
+                golden.join(path),
+                std::fs::read_to_string(output.join(path))?.as_str(),
+            )?;
+        }
+        return Ok(());
+    }
+    let expected = if golden.exists() {
+        files(golden)?
+    } else {
+        vec![]
+    };
+    if let Some(path) = expected.iter().find(|p| !actual.contains(p)) {
+        return Err(Error::BackEndError(format!(
+            "{} was not generated. Set {}=1 to remove its golden file.",
+            path.display(),
+            UPDATE_GOLDEN
+        )));
+    }
+    for path in &actual {
+        check_file(
+            golden.join(path),
+            std::fs::read_to_string(output.join(path))?.as_str(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn normalize() {
+        assert_eq!(super::normalize("\r\n\na  \r\n\tb\t\r\n\n\n"), "a\n\tb\n");
+        assert_eq!(super::normalize(" \n\n"), "");
+    }
+
+    #[test]
+    fn compare() {
+        assert!(super::compare("x", "a\nb", "a\nb\n").is_ok());
+        assert_eq!(
+            super::compare("x", "a\nc\n", "a\nb\n")
+                .unwrap_err()
+                .to_string(),
+            "Back-end error: x differs from its golden file at line 2:
+  expected: b
+  actual:   c
+Set UPDATE_GOLDEN=1 to update the golden file."
+        );
+        assert!(super::compare("x", "a\n", "a\nb\n")
+            .unwrap_err()
+            .to_string()
+            .contains("actual:   <end of file>"));
+    }
+}
@@ -0,0 +1,244 @@
+//! File headers.
+//!
+//! Back-ends can start every generated file with a header comment, rendered from a template
+//! with the following placeholders:
+//!
+//! - `{project}`: the identifier of the project,
+//! - `{version}`: the version of this crate,
+//! - `{license}`: the configured license text, e.g. an SPDX license identifier,
+//! - `{timestamp}`: the time of generation, in UTC.
+//!
+//! Lines with a `{license}` placeholder are omitted if no license is configured, and lines with
+//! a `{timestamp}` placeholder are omitted unless timestamps are enabled. Without timestamps,
+//! which is the default, headers are deterministic, such that builds are reproducible and
+//! unchanged files are not rewritten. Timestamps honor the `SOURCE_DATE_EPOCH` environment
+//! variable.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::generator::config::{Configure, Value};
+//! use tydi::generator::header::HeaderConfig;
+//!
+//! let mut header = HeaderConfig::default();
+//! header.set("license", &Value::String("SPDX-License-Identifier: MIT".to_string()))?;
+//! assert_eq!(
+//!     header.render("proj", "--"),
+//!     format!(
+//!         "-- Generated by Tydi {} for project proj.\n-- SPDX-License-Identifier: MIT\n\n",
+//!         env!("CARGO_PKG_VERSION")
+//!     )
+//! );
+//! # Ok::<(), tydi::Error>(())
+//! ```
+
+use crate::generator::config::{merge_flag, Configure, Value};
+use crate::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+/// The template of headers if no template is configured.
+pub const DEFAULT_TEMPLATE: &str =
+    "Generated by Tydi {version} for project {project}.\nGenerated at {timestamp}.\n{license}";
+
+/// The environment variable with the number of seconds since the Unix epoch to use as the
+/// generation timestamp, for reproducible builds.
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// File header configuration parameters.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct HeaderConfig {
+    /// Start generated files with a header comment. Implied by --header-template and --license.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    header: bool,
+
+    /// Disable --header, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "header"))]
+    no_header: bool,
+
+    /// Template of the header comment of generated files, with lines separated by \n.
+    /// Placeholders: {project}, {version}, {license} and {timestamp}.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    header_template: Option<String>,
+
+    /// License text of the header comment of generated files.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    license: Option<String>,
+
+    /// Include the time of generation in the header comment of generated files. Generated
+    /// files then change on every run, unless SOURCE_DATE_EPOCH is set.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    timestamp: bool,
+
+    /// Disable --timestamp, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "timestamp"))]
+    no_timestamp: bool,
+}
+
+impl HeaderConfig {
+    /// Returns true if generated files start with a header.
+    pub fn enabled(&self) -> bool {
+        self.header || self.header_template.is_some() || self.license.is_some()
+    }
+
+    /// Returns the template of headers.
+    pub fn template(&self) -> &str {
+        self.header_template.as_deref().unwrap_or(DEFAULT_TEMPLATE)
+    }
+
+    /// Returns the license text, if any.
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// Returns true if headers include the time of generation.
+    pub fn timestamp(&self) -> bool {
+        self.timestamp
+    }
+
+    /// Returns the header of files generated for the project with identifier `project`, with
+    /// every line prefixed by the line comment token `comment` and followed by an empty line.
+    /// Returns an empty string if headers are not enabled.
+    pub fn render(&self, project: &str, comment: &str) -> String {
+        if !self.enabled() {
+            return String::new();
+        }
+        let timestamp = if self.timestamp {
+            Some(format_timestamp(generation_time()))
+        } else {
+            None
+        };
+        let mut result = String::new();
+        for line in self.template().lines() {
+            let line = match (&timestamp, self.license()) {
+                (None, _) if line.contains("{timestamp}") => continue,
+                (_, None) if line.contains("{license}") => continue,
+                (timestamp, license) => line
+                    .replace("{project}", project)
+                    .replace("{version}", env!("CARGO_PKG_VERSION"))
+                    .replace("{timestamp}", timestamp.as_deref().unwrap_or(""))
+                    .replace("{license}", license.unwrap_or("")),
+            };
+            for line in line.split('\n') {
+                result.push_str(format!("{} {}", comment, line).trim_end());
+                result.push('\n');
+            }
+        }
+        result.push('\n');
+        result
+    }
+
+    /// Returns `contents` of a file generated for the project with identifier `project`, with
+    /// the header prepended. See [`render`](#method.render).
+    pub fn apply(&self, project: &str, comment: &str, contents: String) -> String {
+        if self.enabled() {
+            format!("{}{}", self.render(project, comment), contents)
+        } else {
+            contents
+        }
+    }
+}
+
+impl Configure for HeaderConfig {
+    const SECTION: &'static str = "header";
+    const KEYS: &'static [&'static str] = &["header", "template", "license", "timestamp"];
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<()> {
+        match key {
+            "header" => self.header = value.expect_bool(key)?,
+            "template" => self.header_template = Some(value.expect_str(key)?.to_string()),
+            "license" => self.license = Some(value.expect_str(key)?.to_string()),
+            "timestamp" => self.timestamp = value.expect_bool(key)?,
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, overrides: Self) {
+        merge_flag(&mut self.header, overrides.header, overrides.no_header);
+        self.header_template = overrides
+            .header_template
+            .or_else(|| self.header_template.take());
+        self.license = overrides.license.or_else(|| self.license.take());
+        merge_flag(
+            &mut self.timestamp,
+            overrides.timestamp,
+            overrides.no_timestamp,
+        );
+    }
+}
+
+/// Returns the time of generation in seconds since the Unix epoch: the value of
+/// [`SOURCE_DATE_EPOCH`] if it is set, or the current time otherwise.
+///
+/// [`SOURCE_DATE_EPOCH`]: constant.SOURCE_DATE_EPOCH.html
+fn generation_time() -> u64 {
+    std::env::var(SOURCE_DATE_EPOCH)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        })
+}
+
+/// Returns seconds since the Unix epoch as an RFC 3339 timestamp in UTC.
+fn format_timestamp(seconds: u64) -> String {
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    // Convert days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header() -> Result<()> {
+        let mut header = HeaderConfig::default();
+        assert_eq!(header.render("proj", "--"), "");
+        assert_eq!(header.apply("proj", "--", "x\n".to_string()), "x\n");
+
+        header.set(
+            "template",
+            &Value::String("{project} at {timestamp}\n\n{license}".to_string()),
+        )?;
+        assert_eq!(header.render("proj", "//"), "//\n\n");
+        header.set("license", &Value::String("A\nB".to_string()))?;
+        header.set("timestamp", &Value::Boolean(true))?;
+        let rendered = header.render("proj", "//");
+        assert!(rendered.starts_with("// proj at "));
+        assert!(rendered.ends_with("Z\n//\n// A\n// B\n\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_600_000_000), "2020-09-13T12:26:40Z");
+    }
+}
@@ -0,0 +1,215 @@
+//! Name mangling.
+//!
+//! Back-ends derive identifiers by concatenating names, which can result in collisions, e.g.
+//! `a_b` and `c` versus `a` and `b_c`, and in identifiers that are too long for downstream
+//! tools. A [`Mangler`] derives identifiers for a single namespace, such as a package, and
+//! guarantees that different lists of names result in different identifiers.
+//!
+//! [`Mangler`]: ./struct.Mangler.html
+
+use crate::{Error, Result};
+use std::collections::HashMap;
+
+/// The number of hexadecimal digits of the hash appended to shortened or colliding identifiers.
+const HASH_DIGITS: usize = 8;
+
+/// Returns a 32-bit FNV-1a hash of a list of names. Unlike the hashers of the standard library,
+/// this hash is guaranteed to be stable, such that generated identifiers are deterministic.
+fn hash(parts: &[String]) -> u32 {
+    let mut result: u32 = 0x811c_9dc5;
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            // Separate the names, such that ["ab", "c"] and ["a", "bc"] hash differently.
+            result ^= 0xff;
+            result = result.wrapping_mul(0x0100_0193);
+        }
+        for byte in part.bytes() {
+            result ^= u32::from(byte);
+            result = result.wrapping_mul(0x0100_0193);
+        }
+    }
+    result
+}
+
+/// Derives unique identifiers from lists of names within a namespace.
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::generator::mangle::Mangler;
+///
+/// let mut mangler = Mangler::new();
+/// assert_eq!(mangler.mangle(&["a_b", "c"])?, "a_b_c");
+/// // The same names always result in the same identifier.
+/// assert_eq!(mangler.mangle(&["a_b", "c"])?, "a_b_c");
+/// // Different names that concatenate to the same identifier get a hash appended.
+/// assert_eq!(mangler.mangle(&["a", "b_c"])?, "a_b_c_8b987b0b");
+/// # Ok::<(), tydi::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mangler {
+    /// The separator between names.
+    separator: String,
+    /// The maximum length of identifiers, if any.
+    max_length: Option<usize>,
+    /// The identifiers derived so far, and the names they were derived from.
+    names: HashMap<String, Vec<String>>,
+}
+
+impl Default for Mangler {
+    fn default() -> Self {
+        Mangler {
+            separator: "_".to_string(),
+            max_length: None,
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl Mangler {
+    /// Returns a new mangler that separates names with an underscore, like [`cat!`], and does
+    /// not limit the length of identifiers.
+    ///
+    /// [`cat!`]: ../../macro.cat.html
+    pub fn new() -> Self {
+        Mangler::default()
+    }
+
+    /// Returns this mangler with a different separator between names.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Returns this mangler with a maximum identifier length. Longer identifiers are truncated
+    /// and made unique by appending a hash of the names they were derived from.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Returns the separator between names.
+    pub fn separator(&self) -> &str {
+        self.separator.as_str()
+    }
+
+    /// Returns the maximum identifier length, if any.
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Reserves an identifier, such that no other names are mangled into it.
+    pub fn reserve(&mut self, identifier: impl Into<String>) -> Result<()> {
+        self.mangle(&[identifier.into()]).map(|_| ())
+    }
+
+    /// Returns a unique identifier for a list of names. Empty names are skipped.
+    ///
+    /// The names are joined with the separator. If the result exceeds the maximum length, or was
+    /// already derived from different names, it is truncated as needed and a hash of the names
+    /// is appended. Returns an error if this still results in a collision, or if the maximum
+    /// length is too short to append the hash.
+    pub fn mangle<T: AsRef<str>>(&mut self, names: &[T]) -> Result<String> {
+        let parts = names
+            .iter()
+            .map(|n| n.as_ref().to_string())
+            .filter(|n| !n.is_empty())
+            .collect::<Vec<_>>();
+
+        let joined = parts.join(self.separator.as_str());
+        let mut identifier = if self.max_length.is_some_and(|max| joined.len() > max) {
+            self.hashed(&joined, &parts)?
+        } else {
+            joined.clone()
+        };
+        if self.names.get(&identifier).is_some_and(|p| p != &parts) {
+            identifier = self.hashed(&joined, &parts)?;
+        }
+
+        match self.names.get(&identifier) {
+            Some(existing) if existing != &parts => Err(Error::BackEndError(format!(
+                "Name collision: {} and {} both map to {}",
+                existing.join(self.separator.as_str()),
+                joined,
+                identifier
+            ))),
+            Some(_) => Ok(identifier),
+            None => {
+                self.names.insert(identifier.clone(), parts);
+                Ok(identifier)
+            }
+        }
+    }
+
+    /// Returns an identifier with a hash of the names appended, truncating the joined names such
+    /// that the result does not exceed the maximum length.
+    fn hashed(&self, joined: &str, parts: &[String]) -> Result<String> {
+        let suffix = format!(
+            "{}{:0width$x}",
+            self.separator,
+            hash(parts),
+            width = HASH_DIGITS
+        );
+        let keep = match self.max_length {
+            Some(max) if max <= suffix.len() => {
+                return Err(Error::ConfigError(format!(
+                    "maximum identifier length {} is too short, it must exceed {}",
+                    max,
+                    suffix.len()
+                )))
+            }
+            Some(max) => max - suffix.len(),
+            None => joined.len(),
+        };
+        let prefix = joined
+            .char_indices()
+            .take_while(|(i, c)| i + c.len_utf8() <= keep)
+            .map(|(_, c)| c)
+            .collect::<String>();
+        Ok(format!("{}{}", prefix, suffix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mangle() -> Result<()> {
+        let mut mangler = Mangler::new();
+        assert_eq!(mangler.mangle(&["a", "", "b"])?, "a_b");
+        assert_eq!(
+            mangler.mangle(&["a_b"])?,
+            format!("a_b_{:08x}", hash(&["a_b".to_string()]))
+        );
+
+        let mut mangler = Mangler::new().with_separator("__").with_max_length(16);
+        assert_eq!(mangler.mangle(&["abc", "def"])?, "abc__def");
+        let long = mangler.mangle(&["abcdefgh", "ijklmnop"])?;
+        assert_eq!(long.len(), 16);
+        assert!(long.starts_with("abcdef__"));
+        assert_ne!(long, mangler.mangle(&["abcdefgh", "ijklmnoq"])?);
+
+        let mut mangler = Mangler::new();
+        let hashed = format!("a_b_{:08x}", hash(&["a".to_string(), "b".to_string()]));
+        mangler.reserve(hashed.as_str())?;
+        mangler.reserve("a_b")?;
+        assert_eq!(
+            mangler.mangle(&["a", "b"]),
+            Err(Error::BackEndError(format!(
+                "Name collision: {} and a_b both map to {}",
+                hashed, hashed
+            )))
+        );
+
+        let mut mangler = Mangler::new().with_max_length(9);
+        mangler.reserve("ab_c")?;
+        assert_eq!(
+            mangler.mangle(&["ab", "c"]),
+            Err(Error::ConfigError(
+                "maximum identifier length 9 is too short, it must exceed 9".to_string()
+            ))
+        );
+        Ok(())
+    }
+}
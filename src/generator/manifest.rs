@@ -0,0 +1,288 @@
+//! Generation manifests.
+//!
+//! A manifest lists every file that a back-end generated for a project, with a hash of its
+//! contents and the streamlets it was generated from, along with their [fingerprints], and the
+//! [experimental features] that were enabled while generating them. Build
+//! systems can use manifests to track the dependencies of generated files, and users can use them
+//! to trace generated code back to the Streamlet Definition Files it originates from.
+//!
+//! Back-ends write the manifest as JSON to [`FILE_NAME`] in the project directory, e.g.:
+//!
+//! ```json
+//! {
+//!   "project": "proj",
+//!   "experimental_features": ["union-streams"],
+//!   "files": [
+//!     {
+//!       "path": "lib_pkg.gen.vhd",
+//!       "hash": "4d5a6f3c1e2b0a97",
+//!       "sources": [
+//!         { "streamlet": "lib.x", "fingerprint": "9b1c07e2d4f3a586" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Hashes are 64-bit FNV-1a hashes, formatted as hexadecimal strings, which are stable across
+//! runs and platforms.
+//!
+//! [fingerprints]: ../../design/struct.Streamlet.html#method.fingerprint
+//! [experimental features]: ../../experimental/index.html
+//! [`FILE_NAME`]: constant.FILE_NAME.html
+
+use crate::design::{Project, Streamlet};
+use crate::experimental::Features;
+use crate::generator::write_if_changed;
+use crate::traits::Identify;
+use crate::util::FnvHasher;
+use crate::Result;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+/// The name of manifest files.
+pub const FILE_NAME: &str = "manifest.json";
+
+/// A generated file in a manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestFile {
+    /// The path of the file, relative to the directory of the manifest.
+    path: String,
+    /// The hash of the contents of the file.
+    hash: u64,
+    /// The paths of the streamlets the file was generated from, with their fingerprints.
+    sources: Vec<(String, u64)>,
+}
+
+impl ManifestFile {
+    /// Returns the path of the file relative to the directory of the manifest, with `/` as
+    /// separator.
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// Returns the hash of the contents of the file.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the streamlets the file was generated from, as `<library>.<streamlet>`, with
+    /// their fingerprints.
+    pub fn sources(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.sources.iter().map(|(s, f)| (s.as_str(), *f))
+    }
+}
+
+/// The files that a back-end generated for a project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// The identifier of the project.
+    project: String,
+    /// The directory of the manifest.
+    dir: PathBuf,
+    /// The experimental features that were enabled.
+    features: Features,
+    /// The generated files, in the order in which they were added.
+    files: Vec<ManifestFile>,
+}
+
+impl Manifest {
+    /// Construct an empty manifest of `project`, to be written to the directory `dir`.
+    pub fn new(project: &Project, dir: impl Into<PathBuf>) -> Self {
+        Manifest {
+            project: project.identifier().to_string(),
+            dir: dir.into(),
+            features: Features::default(),
+            files: vec![],
+        }
+    }
+
+    /// Return this manifest with the experimental `features` that were enabled.
+    pub fn with_features(mut self, features: &Features) -> Self {
+        self.features = features.clone();
+        self
+    }
+
+    /// Returns the experimental features that were enabled.
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// Add a generated file at `path` with `contents`, which was generated from `sources`: the
+    /// identifiers of libraries with streamlets of those libraries. Adding a file that was
+    /// already added replaces it.
+    pub fn add<'a>(
+        &mut self,
+        path: &Path,
+        contents: &[u8],
+        sources: impl IntoIterator<Item = (&'a str, &'a Streamlet)>,
+    ) {
+        let relative = path
+            .strip_prefix(&self.dir)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut hasher = FnvHasher::default();
+        hasher.write(contents);
+        let hash = hasher.finish();
+        let file = ManifestFile {
+            path: relative,
+            hash,
+            sources: sources
+                .into_iter()
+                .map(|(l, s)| (format!("{}.{}", l, s.identifier()), s.fingerprint()))
+                .collect(),
+        };
+        match self.files.iter_mut().find(|f| f.path == file.path) {
+            Some(existing) => *existing = file,
+            None => self.files.push(file),
+        }
+    }
+
+    /// Returns an iterator over the generated files.
+    pub fn files(&self) -> impl Iterator<Item = &ManifestFile> {
+        self.files.iter()
+    }
+
+    /// Returns the manifest as JSON.
+    pub fn to_json(&self) -> String {
+        let mut result = String::new();
+        result.push_str("{\n");
+        result.push_str(format!("  \"project\": {},\n", json_string(&self.project)).as_str());
+        result.push_str(
+            format!(
+                "  \"experimental_features\": [{}],\n",
+                self.features
+                    .iter()
+                    .map(|f| json_string(f.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .as_str(),
+        );
+        result.push_str("  \"files\": [");
+        for (index, file) in self.files.iter().enumerate() {
+            result.push_str(if index == 0 { "\n" } else { ",\n" });
+            result.push_str("    {\n");
+            result.push_str(format!("      \"path\": {},\n", json_string(&file.path)).as_str());
+            result.push_str(format!("      \"hash\": \"{:016x}\",\n", file.hash).as_str());
+            result.push_str("      \"sources\": [");
+            for (index, (streamlet, fingerprint)) in file.sources.iter().enumerate() {
+                result.push_str(if index == 0 { "\n" } else { ",\n" });
+                result.push_str(
+                    format!(
+                        "        {{ \"streamlet\": {}, \"fingerprint\": \"{:016x}\" }}",
+                        json_string(streamlet),
+                        fingerprint
+                    )
+                    .as_str(),
+                );
+            }
+            result.push_str(if file.sources.is_empty() {
+                "]\n"
+            } else {
+                "\n      ]\n"
+            });
+            result.push_str("    }");
+        }
+        result.push_str(if self.files.is_empty() {
+            "]\n"
+        } else {
+            "\n  ]\n"
+        });
+        result.push_str("}\n");
+        result
+    }
+
+    /// Returns the path that the manifest is written to: [`FILE_NAME`] in its directory.
+    ///
+    /// [`FILE_NAME`]: constant.FILE_NAME.html
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(FILE_NAME)
+    }
+
+    /// Write the manifest to its [path], unless it did not change and `force` is false. Returns
+    /// true if the manifest was written.
+    ///
+    /// [path]: #method.path
+    pub fn write(&self, force: bool) -> Result<bool> {
+        write_if_changed(self.path(), self.to_json(), force)
+    }
+}
+
+/// Returns `value` as a JSON string literal.
+pub(crate) fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::project::tests::proj::empty_proj;
+    use crate::design::streamlet::tests::streamlets::nulls_streamlet;
+    use crate::experimental::Feature;
+
+    #[test]
+    fn manifest() {
+        let (x, y) = (nulls_streamlet("x"), nulls_streamlet("y"));
+        let mut manifest = Manifest::new(&empty_proj(), "out/proj");
+        manifest.add(
+            Path::new("out/proj/lib_pkg.vhd"),
+            b"a",
+            vec![("lib", &x), ("lib", &y)],
+        );
+        manifest.add(Path::new("out/proj/Main.scala"), b"b", vec![]);
+        manifest.add(Path::new("out/proj/Main.scala"), b"c", vec![("lib", &x)]);
+        manifest.add(Path::new("out/proj/build.sbt"), b"d", vec![]);
+
+        let files = manifest.files().collect::<Vec<_>>();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[1].path(), "Main.scala");
+        assert_eq!(files[1].hash(), 0xaf63_de4c_8601_eff2);
+        assert_eq!(
+            files[0].sources().map(|(s, _)| s).collect::<Vec<_>>(),
+            vec!["lib.x", "lib.y"]
+        );
+
+        let json = manifest.to_json();
+        assert!(json.starts_with(
+            "{\n  \"project\": \"proj\",\n  \"experimental_features\": [],\n  \"files\": [\n    {\n"
+        ));
+        assert!(json.contains(
+            format!(
+                "      \"path\": \"Main.scala\",
+      \"hash\": \"af63de4c8601eff2\",
+      \"sources\": [
+        {{ \"streamlet\": \"lib.x\", \"fingerprint\": \"{:016x}\" }}
+      ]
+",
+                x.fingerprint()
+            )
+            .as_str()
+        ));
+        assert!(json.ends_with("      \"sources\": []\n    }\n  ]\n}\n"));
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+
+        let features = Features::default()
+            .with(Feature::UnionStreams)
+            .with(Feature::Firrtl);
+        let manifest = Manifest::new(&empty_proj(), "out/proj").with_features(&features);
+        assert_eq!(manifest.features(), &features);
+        assert_eq!(
+            manifest.to_json(),
+            "{\n  \"project\": \"proj\",\n  \"experimental_features\": [\"firrtl\", \"union-streams\"],\n  \"files\": []\n}\n"
+        );
+    }
+}
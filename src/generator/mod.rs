@@ -1,9 +1,25 @@
-use crate::design::Project;
-use crate::Result;
+use crate::design::{Library, Project, Streamlet};
+use crate::generator::common::convert::{Componentify, CANON_SUFFIX};
+use crate::generator::common::{Component, Package};
+use crate::traits::Identify;
+use crate::util::IndexMap;
+use crate::{NonNegative, Result};
 use std::path::Path;
 
 pub mod chisel;
 pub mod common;
+pub mod config;
+#[cfg(feature = "firrtl")]
+pub mod firrtl;
+#[cfg(feature = "golden")]
+pub mod golden;
+pub mod header;
+pub mod mangle;
+pub mod manifest;
+pub mod regions;
+pub mod registry;
+pub mod sim;
+pub mod variants;
 pub mod vhdl;
 
 /// Concatenate stuff using format with an underscore in between.
@@ -32,4 +48,322 @@ macro_rules! cat {
 pub trait GenerateProject {
     /// Generate source files from a [common::Project] and save them to [path].
     fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()>;
+
+    /// Generate source files from a project that is already lowered to the common hardware
+    /// representation, and save them to `path`. Back-ends that lower projects override this,
+    /// such that [generate_all] lowers a project only once for all back-ends.
+    fn generate_lowered(&self, lowered: &Lowered, path: impl AsRef<Path>) -> Result<()> {
+        self.generate(lowered.project(), path)
+    }
+}
+
+/// The common hardware representation of the streamlets of a project, which can be shared by
+/// multiple back-ends.
+#[derive(Debug)]
+pub struct Lowered<'p> {
+    /// The project that is lowered.
+    project: &'p Project,
+    /// The streamlets of every library, with their canonical and fancy components.
+    libraries: IndexMap<String, Vec<(Streamlet, Component, Option<Component>)>>,
+}
+
+impl<'p> Lowered<'p> {
+    /// Lower all streamlets of `project`. Returns an error if the bit count of a physical stream
+    /// of the project overflows.
+    pub fn new(project: &'p Project) -> Result<Self> {
+        Lowered::with_max_width(project, None)
+    }
+
+    /// Lower all streamlets of `project`, like [`new`]. Returns an error if the bit count of a
+    /// physical stream of the project overflows, or if the total bit count of an interface
+    /// exceeds `max_width`, if given, see [`Project::check_widths`].
+    ///
+    /// [`new`]: #method.new
+    /// [`Project::check_widths`]: ../design/struct.Project.html#method.check_widths
+    pub fn with_max_width(project: &'p Project, max_width: Option<NonNegative>) -> Result<Self> {
+        project.check_widths(max_width)?;
+        Ok(Lowered {
+            project,
+            libraries: project
+                .libraries()
+                .map(|lib| {
+                    (
+                        lib.identifier().to_string(),
+                        lib.streamlets()
+                            .into_iter()
+                            .map(|s| {
+                                let (canonical, fancy) = (s.canonical(CANON_SUFFIX), s.fancy(None));
+                                (s, canonical, fancy)
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the project that is lowered.
+    pub fn project(&self) -> &'p Project {
+        self.project
+    }
+
+    /// Returns the common hardware representation of only the components that are reachable
+    /// from the streamlets with the identifiers in `roots`: their canonical components, and the
+    /// fancy components that those wrap. Since record types are derived from the ports of the
+    /// components, the record types of unreachable components are pruned as well.
+    pub fn reachable(&self, roots: &[impl AsRef<str>]) -> Self {
+        Lowered {
+            project: self.project,
+            libraries: self
+                .project
+                .libraries()
+                .map(|lib| {
+                    (
+                        lib.identifier().to_string(),
+                        self.components(lib.identifier())
+                            .filter(|(s, _, _)| roots.iter().any(|r| r.as_ref() == s.identifier()))
+                            .cloned()
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the components of the streamlets of the library with identifier `library`, if
+    /// there is such a library.
+    fn components(
+        &self,
+        library: &str,
+    ) -> impl Iterator<Item = &(Streamlet, Component, Option<Component>)> {
+        self.libraries.get(library).into_iter().flatten()
+    }
+
+    /// Returns the lowered streamlets of the library with identifier `library`.
+    pub fn streamlets(&self, library: &str) -> impl Iterator<Item = &Streamlet> {
+        self.components(library).map(|(streamlet, _, _)| streamlet)
+    }
+
+    /// Returns the streamlet of the library with identifier `library` that the component with
+    /// identifier `component` is lowered from, if there is one.
+    pub fn streamlet(&self, library: &str, component: &str) -> Option<&Streamlet> {
+        self.components(library)
+            .find(|(_, canonical, fancy)| {
+                canonical.identifier() == component
+                    || fancy.iter().any(|f| f.identifier() == component)
+            })
+            .map(|(streamlet, _, _)| streamlet)
+    }
+
+    /// Returns a package with the canonical components of a library, like
+    /// [Packify::canonical](common::convert::Packify::canonical).
+    pub fn canonical(&self, library: &str) -> Package {
+        Package {
+            identifier: library.to_string(),
+            components: self
+                .components(library)
+                .map(|(_, canonical, _)| canonical.clone())
+                .collect(),
+        }
+    }
+
+    /// Returns a package with the canonical and fancy components of a library, like
+    /// [Packify::fancy](common::convert::Packify::fancy).
+    pub fn fancy(&self, library: &str) -> Package {
+        Package {
+            identifier: library.to_string(),
+            components: self
+                .components(library)
+                .flat_map(|(_, canonical, fancy)| std::iter::once(canonical).chain(fancy))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns a package with only the fancy components of a library.
+    pub fn fancy_only(&self, library: &str) -> Package {
+        Package {
+            identifier: library.to_string(),
+            components: self
+                .components(library)
+                .filter_map(|(_, _, fancy)| fancy.clone())
+                .collect(),
+        }
+    }
+}
+
+/// A back-end for [generate_all].
+pub enum BackEnd {
+    /// The VHDL back-end.
+    VHDL(vhdl::VHDLBackEnd),
+    /// The Chisel back-end.
+    Chisel(chisel::ChiselBackEnd),
+    /// The FIRRTL back-end.
+    #[cfg(feature = "firrtl")]
+    Firrtl(firrtl::FirrtlBackEnd),
+}
+
+impl BackEnd {
+    /// Returns the name of the subdirectory that this back-end generates sources in.
+    pub fn subdirectory(&self) -> &'static str {
+        match self {
+            BackEnd::VHDL(_) => "vhdl",
+            BackEnd::Chisel(_) => "chisel",
+            #[cfg(feature = "firrtl")]
+            BackEnd::Firrtl(_) => "firrtl",
+        }
+    }
+}
+
+impl From<vhdl::VHDLBackEnd> for BackEnd {
+    fn from(back_end: vhdl::VHDLBackEnd) -> Self {
+        BackEnd::VHDL(back_end)
+    }
+}
+
+impl From<chisel::ChiselBackEnd> for BackEnd {
+    fn from(back_end: chisel::ChiselBackEnd) -> Self {
+        BackEnd::Chisel(back_end)
+    }
+}
+
+#[cfg(feature = "firrtl")]
+impl From<firrtl::FirrtlBackEnd> for BackEnd {
+    fn from(back_end: firrtl::FirrtlBackEnd) -> Self {
+        BackEnd::Firrtl(back_end)
+    }
+}
+
+/// Generate source files for a project with multiple back-ends. The project is lowered to the
+/// common hardware representation once, after which every back-end generates its sources in its
+/// own [subdirectory](BackEnd::subdirectory) of `path`.
+pub fn generate_all(
+    project: &Project,
+    back_ends: &[BackEnd],
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let lowered = Lowered::new(project)?;
+    for back_end in back_ends {
+        let path = path.as_ref().join(back_end.subdirectory());
+        match back_end {
+            BackEnd::VHDL(vhdl) => vhdl.generate_lowered(&lowered, path)?,
+            BackEnd::Chisel(chisel) => chisel.generate_lowered(&lowered, path)?,
+            #[cfg(feature = "firrtl")]
+            BackEnd::Firrtl(firrtl) => firrtl.generate_lowered(&lowered, path)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns a comment block that lists the streams of the interfaces of a library that are pruned
+/// because they are null, such that users can tell why they do not result in ports. Lines start
+/// with the `comment` token of the language. Returns None if no streams are pruned.
+pub(crate) fn pruned_streams_comment(library: &Library, comment: &str) -> Option<String> {
+    let mut lines = vec![];
+    for streamlet in library.streamlets() {
+        for interface in streamlet.interfaces() {
+            for pruned in interface.typ().pruned_streams() {
+                let path = std::iter::once(streamlet.identifier())
+                    .chain(std::iter::once(interface.identifier()))
+                    .chain(pruned.path().into_iter().map(|n| n.as_ref()))
+                    .collect::<Vec<_>>()
+                    .join(".");
+                lines.push(format!("{}   {}: {}", comment, path, pruned.reason()));
+            }
+        }
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "{c} The following streams are null, so they do not result in ports:\n{}\n{c} Null streams are kept if their keep flag is set (x=true).\n",
+            lines.join("\n"),
+            c = comment
+        ))
+    }
+}
+
+/// Write the contents of a generated file to a path, unless the file already exists with
+/// identical contents and `force` is false. This prevents touching unchanged files, which would
+/// trigger needless recompilation by downstream tools.
+///
+/// Returns true if the file was written.
+pub(crate) fn write_if_changed(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+    force: bool,
+) -> Result<bool> {
+    let contents = contents.as_ref();
+    if !force {
+        if let Ok(existing) = std::fs::read(path.as_ref()) {
+            if existing == contents {
+                return Ok(false);
+            }
+        }
+    }
+    std::fs::write(path, contents)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_if_changed() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("file");
+
+        assert!(super::write_if_changed(&path, "a", false)?);
+        assert!(!super::write_if_changed(&path, "a", false)?);
+        assert!(super::write_if_changed(&path, "a", true)?);
+        assert!(super::write_if_changed(&path, "b", false)?);
+        assert_eq!(std::fs::read_to_string(&path)?, "b");
+        Ok(())
+    }
+
+    #[test]
+    fn generate_all() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let project = Project::from_builder(
+            crate::Name::try_new("proj")?,
+            crate::UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                crate::Name::try_new("lib")?,
+                crate::UniquelyNamedBuilder::new().with_items(vec![
+                    crate::design::streamlet::tests::streamlets::nulls_streamlet("test"),
+                ]),
+            )?]),
+        )?;
+        super::generate_all(
+            &project,
+            &[
+                vhdl::VHDLBackEnd::default().into(),
+                chisel::ChiselBackEnd::default().into(),
+            ],
+            tmpdir.path(),
+        )?;
+        assert!(tmpdir.path().join("vhdl/proj/lib_pkg.gen.vhd").exists());
+        assert!(tmpdir.path().join("chisel/proj/lib.gen.scala").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn pruned_streams() -> Result<()> {
+        let streamlet = |sdf| crate::parser::nom::streamlet(sdf).unwrap().1;
+        let lib = Library::from_builder(
+            crate::Name::try_new("lib")?,
+            crate::UniquelyNamedBuilder::new().with_items(vec![
+                streamlet("Streamlet x (a : in Group<b: Stream<Null>, c: Stream<Bits<1>>>)"),
+                streamlet("Streamlet y (a : in Stream<Null, x=true>)"),
+            ]),
+        )?;
+        assert_eq!(
+            pruned_streams_comment(&lib, "//").unwrap(),
+            "// The following streams are null, so they do not result in ports:
+//   x.a.b: it carries no data
+// Null streams are kept if their keep flag is set (x=true).
+"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,177 @@
+//! User regions.
+//!
+//! Generated files that users are meant to edit, such as architecture stubs, mark the parts that
+//! belong to the user with a pair of comment lines, e.g. for VHDL:
+//!
+//! ```vhdl
+//! -- BEGIN USER body
+//! -- END USER body
+//! ```
+//!
+//! When such a file is generated again, the contents of every region of the existing file are
+//! carried over to the region with the same name in the new file, such that regenerating, e.g.
+//! after changing an interface, does not discard hand-written code.
+
+use crate::{Error, Result};
+use std::collections::HashMap;
+
+/// Returns the name of the region that a line begins or ends, if it is a marker line with the
+/// given comment token and keyword.
+fn marker<'a>(line: &'a str, comment: &str, keyword: &str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix(comment)
+        .and_then(|l| l.trim_start().strip_prefix(keyword))
+        .map(|name| name.trim())
+}
+
+/// Returns the contents of all regions of a file, by name. Returns an error if regions are
+/// nested, not terminated, or defined more than once.
+fn regions<'a>(contents: &'a str, comment: &str) -> Result<HashMap<&'a str, Vec<&'a str>>> {
+    let mut result = HashMap::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+    for line in contents.lines() {
+        match (
+            &mut current,
+            marker(line, comment, "BEGIN USER"),
+            marker(line, comment, "END USER"),
+        ) {
+            (None, Some(name), _) => current = Some((name, vec![])),
+            (None, None, Some(name)) => {
+                return Err(Error::BackEndError(format!(
+                    "user region {} ends before it begins",
+                    name
+                )))
+            }
+            (Some((name, _)), Some(_), _) => {
+                return Err(Error::BackEndError(format!(
+                    "user region {} is not terminated",
+                    name
+                )))
+            }
+            (Some((name, lines)), None, Some(end)) => {
+                if *name != end {
+                    return Err(Error::BackEndError(format!(
+                        "user region {} is terminated as {}",
+                        name, end
+                    )));
+                }
+                if result.insert(*name, std::mem::take(lines)).is_some() {
+                    return Err(Error::BackEndError(format!(
+                        "user region {} is defined more than once",
+                        name
+                    )));
+                }
+                current = None;
+            }
+            (Some((_, lines)), None, None) => lines.push(line),
+            (None, None, None) => (),
+        }
+    }
+    match current {
+        Some((name, _)) => Err(Error::BackEndError(format!(
+            "user region {} is not terminated",
+            name
+        ))),
+        None => Ok(result),
+    }
+}
+
+/// Returns the `generated` contents of a file, with the contents of its user regions replaced by
+/// those of the `existing` file. Marker lines start with the `comment` token of the language.
+///
+/// Returns an error if the regions of either file are malformed, or if the existing file has a
+/// region that the generated file lacks, since its contents would be lost.
+pub fn preserve(existing: &str, generated: &str, comment: &str) -> Result<String> {
+    let old = regions(existing, comment)?;
+    let new = regions(generated, comment)?;
+    let mut lost = old
+        .iter()
+        .filter(|(name, lines)| !new.contains_key(*name) && !lines.is_empty())
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    if !lost.is_empty() {
+        lost.sort_unstable();
+        return Err(Error::BackEndError(format!(
+            "user region(s) {} would be discarded",
+            lost.join(", ")
+        )));
+    }
+
+    let mut result = String::with_capacity(existing.len().max(generated.len()));
+    let mut skip = false;
+    for line in generated.lines() {
+        if let Some(name) = marker(line, comment, "END USER") {
+            skip = false;
+            if let Some(lines) = old.get(name) {
+                for l in lines {
+                    result.push_str(l);
+                    result.push('\n');
+                }
+            }
+        }
+        if !skip {
+            result.push_str(line);
+            result.push('\n');
+        }
+        if let Some(name) = marker(line, comment, "BEGIN USER") {
+            skip = old.contains_key(name);
+        }
+    }
+    if !generated.ends_with('\n') {
+        result.pop();
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserve_regions() -> Result<()> {
+        let generated = "entity x is
+end x;
+begin
+  -- BEGIN USER body
+  -- END USER body
+end;
+";
+        let existing = "entity x_old is
+end x_old;
+begin
+  -- BEGIN USER body
+  a <= b;
+
+  c <= d;
+  -- END USER body
+end;
+";
+        assert_eq!(
+            preserve(existing, generated, "--")?,
+            "entity x is
+end x;
+begin
+  -- BEGIN USER body
+  a <= b;
+
+  c <= d;
+  -- END USER body
+end;
+"
+        );
+        assert_eq!(preserve("", generated, "--")?, generated);
+        assert_eq!(
+            preserve(existing, "-- BEGIN USER decls\n-- END USER decls\n", "--"),
+            Err(Error::BackEndError(
+                "user region(s) body would be discarded".to_string()
+            ))
+        );
+        assert_eq!(
+            preserve("-- BEGIN USER a\n", generated, "--"),
+            Err(Error::BackEndError(
+                "user region a is not terminated".to_string()
+            ))
+        );
+        Ok(())
+    }
+}
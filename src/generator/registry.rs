@@ -0,0 +1,216 @@
+//! Back-end registry.
+//!
+//! A [`Registry`] maps names to back-ends, such that tools can select back-ends by name, and
+//! downstream crates can add their own [`GenerateProject`] implementations to the back-ends of
+//! this crate without changes to it.
+//!
+//! # Example
+//!
+//! ```
+//! use std::path::Path;
+//! use tydi::design::Project;
+//! use tydi::generator::registry::Registry;
+//! use tydi::generator::GenerateProject;
+//! use tydi::Identify;
+//!
+//! struct Listing;
+//!
+//! impl GenerateProject for Listing {
+//!     fn generate(&self, project: &Project, path: impl AsRef<Path>) -> tydi::Result<()> {
+//!         let names = project
+//!             .libraries()
+//!             .flat_map(|lib| lib.streamlets())
+//!             .map(|s| format!("{}\n", s.identifier()))
+//!             .collect::<String>();
+//!         std::fs::write(path.as_ref().join("streamlets.txt"), names)?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let registry = Registry::builtin().with("listing", Listing)?;
+//! assert!(registry.names().any(|name| name == "vhdl"));
+//! assert!(registry.names().any(|name| name == "listing"));
+//! # Ok::<(), tydi::Error>(())
+//! ```
+//!
+//! [`Registry`]: struct.Registry.html
+//! [`GenerateProject`]: ../trait.GenerateProject.html
+
+use crate::design::Project;
+use crate::generator::config::GenerationConfig;
+use crate::generator::{chisel, vhdl, GenerateProject, Lowered};
+use crate::util::IndexMap;
+use crate::{Error, Result};
+use std::fmt;
+use std::path::Path;
+
+/// A back-end in a registry.
+type BackEnd = Box<dyn Fn(&Lowered, &Path) -> Result<()>>;
+
+/// Back-ends by name.
+#[derive(Default)]
+pub struct Registry {
+    back_ends: IndexMap<String, BackEnd>,
+}
+
+impl Registry {
+    /// Construct an empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Construct a registry with the back-ends of this crate with their default configuration:
+    /// `vhdl`, `chisel` and, with the `firrtl` feature, `firrtl`.
+    pub fn builtin() -> Self {
+        Registry::configured(&GenerationConfig::default())
+    }
+
+    /// Construct a registry with the back-ends of this crate, like [`builtin`], configured by
+    /// `config`.
+    ///
+    /// [`builtin`]: #method.builtin
+    pub fn configured(config: &GenerationConfig) -> Self {
+        let mut registry = Registry::new();
+        registry.insert(
+            "vhdl",
+            vhdl::VHDLBackEnd::from(config.vhdl.clone()).with_header(config.header.clone()),
+        );
+        registry.insert(
+            "chisel",
+            chisel::ChiselBackEnd::from(config.chisel.clone()).with_header(config.header.clone()),
+        );
+        #[cfg(feature = "firrtl")]
+        registry.insert(
+            "firrtl",
+            crate::generator::firrtl::FirrtlBackEnd::from(config.firrtl.clone())
+                .with_header(config.header.clone()),
+        );
+        registry
+    }
+
+    fn insert(&mut self, name: impl Into<String>, back_end: impl GenerateProject + 'static) {
+        self.back_ends.insert(
+            name.into(),
+            Box::new(move |lowered, path| back_end.generate_lowered(lowered, path)),
+        );
+    }
+
+    /// Register a back-end with the given name. Returns an error if a back-end with that name
+    /// is already registered.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        back_end: impl GenerateProject + 'static,
+    ) -> Result<()> {
+        let name = name.into();
+        if self.contains(name.as_str()) {
+            return Err(Error::InvalidArgument(format!(
+                "back-end {} is already registered",
+                name
+            )));
+        }
+        self.insert(name, back_end);
+        Ok(())
+    }
+
+    /// Return this registry with a back-end registered with the given name, like
+    /// [`register`](#method.register).
+    pub fn with(
+        mut self,
+        name: impl Into<String>,
+        back_end: impl GenerateProject + 'static,
+    ) -> Result<Self> {
+        self.register(name, back_end)?;
+        Ok(self)
+    }
+
+    /// Returns true if a back-end with the given name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.back_ends.contains_key(name)
+    }
+
+    /// Returns an iterator over the names of the registered back-ends, in the order in which
+    /// they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.back_ends.keys().map(String::as_str)
+    }
+
+    /// Generate source files for `project` in `path` with the back-end with the given name.
+    /// Returns an error if there is no such back-end.
+    pub fn generate(&self, name: &str, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        let back_end = self.get(name)?;
+        back_end(&Lowered::new(project)?, path.as_ref())
+    }
+
+    /// Generate source files for a `lowered` project in `path` with the back-end with the given
+    /// name. Returns an error if there is no such back-end.
+    pub fn generate_lowered(
+        &self,
+        name: &str,
+        lowered: &Lowered,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        self.get(name)?(lowered, path.as_ref())
+    }
+
+    /// Returns the back-end with the given name, or an error if there is no such back-end.
+    fn get(&self, name: &str) -> Result<&BackEnd> {
+        self.back_ends.get(name).ok_or_else(|| {
+            Error::InvalidTarget(format!(
+                "unknown back-end {}, expected one of: {}",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            ))
+        })
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::project::tests::proj::empty_proj;
+
+    struct Empty;
+
+    impl GenerateProject for Empty {
+        fn generate(&self, _: &Project, path: impl AsRef<Path>) -> Result<()> {
+            std::fs::write(path.as_ref().join("empty"), "")?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registry() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let mut registry = Registry::builtin().with("empty", Empty)?;
+        assert!(registry.register("vhdl", Empty).is_err());
+
+        registry.generate("empty", &empty_proj(), tmpdir.path())?;
+        assert!(tmpdir.path().join("empty").exists());
+        registry.generate("vhdl", &empty_proj(), tmpdir.path())?;
+        assert!(tmpdir.path().join("proj/lib_pkg.gen.vhd").exists());
+        assert!(registry
+            .generate("verilog", &empty_proj(), tmpdir.path())
+            .unwrap_err()
+            .to_string()
+            .starts_with(
+                "Invalid target: unknown back-end verilog, expected one of: vhdl, chisel"
+            ));
+
+        let config = GenerationConfig::from_toml("[vhdl]\nsuffix = \"cfg\"")?;
+        let project = empty_proj();
+        Registry::configured(&config).generate_lowered(
+            "vhdl",
+            &Lowered::new(&project)?,
+            tmpdir.path(),
+        )?;
+        assert!(tmpdir.path().join("proj/lib_pkg.cfg.vhd").exists());
+        Ok(())
+    }
+}
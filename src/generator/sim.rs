@@ -0,0 +1,377 @@
+//! Simulation model generator.
+//!
+//! Generates Rust source code that models the transfers of physical streams, for use in test
+//! harnesses of generated hardware, e.g. through cocotb/VPI bindings or file-based stimuli.
+//!
+//! For every physical stream, an element struct and a transfer struct are generated. A transfer
+//! holds the element lanes and the `last`, `stai`, `endi`, `strb` and `user` signals, if the stream
+//! has them. Transfers can be encoded to, and decoded from, the bits of the signals of the stream,
+//! least significant bit first, in the order of the [signal list]. Fields of at most 64 bits are
+//! represented by a `u64`, wider fields by a `Vec<bool>`.
+//!
+//! [signal list]: ../../physical/struct.SignalList.html
+
+use crate::design::{Library, Streamlet};
+use crate::physical::{Fields, PhysicalStream};
+use crate::traits::Identify;
+use crate::{cat, NonNegative, PathName, Result};
+
+/// Generate trait for Rust simulation models.
+pub trait DeclareModel {
+    /// Generate Rust source code that models the transfers of the physical streams of self.
+    fn declare_model(&self) -> Result<String>;
+}
+
+/// Helper functions used by the generated code to encode and decode fields.
+const HELPERS: &str = "/// Appends the `width` least significant bits of `value` to `bits`.
+#[allow(dead_code)]
+fn put(bits: &mut Vec<bool>, value: u64, width: usize) {
+    bits.extend((0..width).map(|i| (value >> i) & 1 == 1));
+}
+
+/// Appends the first `width` bits of `value` to `bits`, padded with zeros.
+#[allow(dead_code)]
+fn put_wide(bits: &mut Vec<bool>, value: &[bool], width: usize) {
+    bits.extend((0..width).map(|i| value.get(i).copied().unwrap_or(false)));
+}
+
+/// Returns the value of `width` bits of `bits` starting at `offset`.
+#[allow(dead_code)]
+fn get(bits: &[bool], offset: usize, width: usize) -> u64 {
+    (0..width).fold(0, |acc, i| acc | ((bits[offset + i] as u64) << i))
+}
+
+/// Returns `width` bits of `bits` starting at `offset`.
+#[allow(dead_code)]
+fn get_wide(bits: &[bool], offset: usize, width: usize) -> Vec<bool> {
+    bits[offset..offset + width].to_vec()
+}
+";
+
+/// Rust keywords, which cannot be used as field names without escaping them.
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Returns an upper camel case Rust type name for a snake case name.
+fn type_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Returns a Rust field name for the path of a field, or `default` if the path is empty.
+fn field_name(path: &PathName, default: &str) -> String {
+    let name = if path.is_empty() {
+        default.to_string()
+    } else {
+        path.into_iter()
+            .map(|n| n.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_")
+    };
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Returns the Rust type of a field with the given width.
+fn field_type(width: NonNegative) -> &'static str {
+    if width <= 64 {
+        "u64"
+    } else {
+        "Vec<bool>"
+    }
+}
+
+/// Returns a statement that encodes an expression with the given width.
+fn encode(expr: &str, width: NonNegative) -> String {
+    if width <= 64 {
+        format!("put(bits, {}, {});", expr, width)
+    } else {
+        format!("put_wide(bits, &{}, {});", expr, width)
+    }
+}
+
+/// Returns an expression that decodes a field at the given offset expression with the given
+/// width.
+fn decode(offset: &str, width: NonNegative) -> String {
+    if width <= 64 {
+        format!("get(bits, {}, {})", offset, width)
+    } else {
+        format!("get_wide(bits, {}, {})", offset, width)
+    }
+}
+
+/// Returns the declaration of a struct named `name` with the given fields, along with methods to
+/// encode it into, and decode it from, bits.
+fn declare_struct(name: &str, doc: &str, fields: &Fields) -> Result<String> {
+    let bits = fields.values().map(|b| b.get() as usize).sum::<usize>();
+    let members = fields
+        .iter()
+        .map(|(path, width)| {
+            format!(
+                "    /// {}, {} bit(s).\n    pub {}: {},\n",
+                if path.is_empty() {
+                    "The value".to_string()
+                } else {
+                    format!("Field `{}`", path)
+                },
+                width,
+                field_name(path, "value"),
+                field_type(width.get())
+            )
+        })
+        .collect::<String>();
+    let encoders = fields
+        .iter()
+        .map(|(path, width)| {
+            format!(
+                "        {}\n",
+                encode(&format!("self.{}", field_name(path, "value")), width.get())
+            )
+        })
+        .collect::<String>();
+    let decoders = fields
+        .bit_ranges()?
+        .into_iter()
+        .map(|(path, range)| {
+            format!(
+                "            {}: {},\n",
+                field_name(path, "value"),
+                decode(
+                    &format!("offset + {}", range.start),
+                    range.end - range.start
+                )
+            )
+        })
+        .collect::<String>();
+
+    Ok(format!(
+        "/// {doc}
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct {name} {{
+{members}}}
+
+impl {name} {{
+    /// The number of bits of an encoded {name}.
+    pub const BITS: usize = {bits};
+
+    /// Appends the bits of this {name} to `bits`.
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {{
+{encoders}    }}
+
+    /// Decodes a {name} from `bits`, starting at `offset`.
+    pub fn decode_from(bits: &[bool], offset: usize) -> Self {{
+        {name} {{
+{decoders}        }}
+    }}
+}}",
+        doc = doc,
+        name = name,
+        members = members,
+        bits = bits,
+        encoders = encoders,
+        decoders = decoders,
+    ))
+}
+
+/// Returns the model of a physical stream, where the generated types are named after `name`.
+fn declare_stream(name: &str, stream: &PhysicalStream) -> Result<String> {
+    let base = type_name(name);
+    let element = format!("{}Element", base);
+    let user = format!("{}User", base);
+    let transfer = format!("{}Transfer", base);
+    let signals = stream.signal_list();
+    let lanes = stream.element_lanes().get();
+
+    let mut result = Vec::new();
+    let mut members = String::new();
+    let mut encoders = String::new();
+    let mut decoders = String::new();
+    let mut offset: NonNegative = 0;
+
+    if signals.data().is_some() {
+        result.push(declare_struct(
+            &element,
+            &format!("An element of physical stream `{}`.", name),
+            stream.element_fields(),
+        )?);
+        members.push_str(&format!(
+            "    /// The element lanes. Missing lanes are encoded as default elements.\n    pub data: Vec<{}>,\n",
+            element
+        ));
+        encoders.push_str(
+            "        for lane in 0..Self::LANES {
+            self.data.get(lane).cloned().unwrap_or_default().encode_into(bits);
+        }\n",
+        );
+        decoders.push_str(&format!(
+            "            data: (0..Self::LANES)
+                .map(|lane| {}::decode_from(bits, lane * {}::BITS))
+                .collect(),\n",
+            element, element
+        ));
+        offset += stream.data_bit_count();
+    }
+
+    for (signal, width) in vec![
+        ("last", stream.last_bit_count()),
+        ("stai", stream.stai_bit_count()),
+        ("endi", stream.endi_bit_count()),
+        ("strb", stream.strb_bit_count()),
+    ]
+    .into_iter()
+    .filter(|(_, width)| *width > 0)
+    {
+        members.push_str(&format!(
+            "    /// The `{}` signal, {} bit(s).\n    pub {}: {},\n",
+            signal,
+            width,
+            signal,
+            field_type(width)
+        ));
+        encoders.push_str(&format!(
+            "        {}\n",
+            encode(&format!("self.{}", signal), width)
+        ));
+        decoders.push_str(&format!(
+            "            {}: {},\n",
+            signal,
+            decode(&offset.to_string(), width)
+        ));
+        offset += width;
+    }
+
+    if signals.user().is_some() {
+        result.push(declare_struct(
+            &user,
+            &format!("The user signals of physical stream `{}`.", name),
+            stream.user(),
+        )?);
+        members.push_str(&format!(
+            "    /// The user signals.\n    pub user: {},\n",
+            user
+        ));
+        encoders.push_str("        self.user.encode_into(bits);\n");
+        decoders.push_str(&format!(
+            "            user: {}::decode_from(bits, {}),\n",
+            user, offset
+        ));
+    }
+
+    result.push(format!(
+        "/// A transfer on physical stream `{name}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct {transfer} {{
+{members}}}
+
+impl {transfer} {{
+    /// The number of element lanes.
+    pub const LANES: usize = {lanes};
+    /// The number of bits of an encoded transfer, excluding valid and ready.
+    pub const BITS: usize = {bits};
+
+    /// Encodes this transfer into the bits of the signals of the stream, least significant bit
+    /// first, in the order data, last, stai, endi, strb, user.
+    pub fn encode(&self) -> Vec<bool> {{
+        let mut bits = Vec::with_capacity(Self::BITS);
+        self.encode_into(&mut bits);
+        bits
+    }}
+
+    /// Appends the bits of this transfer to `bits`.
+    #[allow(unused_variables)]
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {{
+{encoders}    }}
+
+    /// Decodes a transfer from the bits of the signals of the stream, as encoded by `encode`.
+    #[allow(unused_variables)]
+    pub fn decode(bits: &[bool]) -> Self {{
+        {transfer} {{
+{decoders}        }}
+    }}
+}}",
+        name = name,
+        transfer = transfer,
+        members = members,
+        lanes = lanes,
+        bits = signals.bit_count(),
+        encoders = encoders,
+        decoders = decoders,
+    ));
+
+    Ok(result.join("\n\n"))
+}
+
+/// Returns the models of all physical streams of a streamlet.
+fn streamlet_models(streamlet: &Streamlet) -> Result<Vec<String>> {
+    let mut models = Vec::new();
+    for interface in streamlet.interfaces() {
+        for (path, stream) in interface.typ().synthesize().streams() {
+            models.push(declare_stream(
+                &cat!(streamlet.identifier(), interface.identifier(), path),
+                stream,
+            )?);
+        }
+    }
+    Ok(models)
+}
+
+impl DeclareModel for Streamlet {
+    fn declare_model(&self) -> Result<String> {
+        let mut models = vec![HELPERS.to_string()];
+        models.extend(streamlet_models(self)?);
+        Ok(format!("{}\n", models.join("\n\n")))
+    }
+}
+
+impl DeclareModel for Library {
+    fn declare_model(&self) -> Result<String> {
+        let mut models = vec![
+            format!(
+                "// Simulation models of the physical streams of library `{}`.",
+                self.identifier()
+            ),
+            HELPERS.to_string(),
+        ];
+        for streamlet in self.streamlets() {
+            models.extend(streamlet_models(&streamlet)?);
+        }
+        Ok(format!("{}\n", models.join("\n\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() -> Result<()> {
+        assert_eq!(type_name("x_a_b"), "XAB");
+        assert_eq!(type_name("streamlet_in"), "StreamletIn");
+        assert_eq!(field_name(&PathName::new_empty(), "value"), "value");
+        assert_eq!(
+            field_name(&PathName::try_new(vec!["a", "B"])?, "value"),
+            "a_b"
+        );
+        assert_eq!(
+            field_name(&PathName::try_new(vec!["type"])?, "value"),
+            "r#type"
+        );
+        Ok(())
+    }
+}
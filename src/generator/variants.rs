@@ -0,0 +1,324 @@
+//! Streamlet variants.
+//!
+//! A parameterized streamlet is a function from parameter [`Bindings`] to a streamlet, e.g. a
+//! streamlet with a configurable data width. [`Variants`] elaborates parameterized streamlets
+//! over sets of bindings into distinct streamlets, named after the parameterized streamlet with
+//! the bindings as suffix, such that all variants can be generated in a single package, e.g. for
+//! IP catalogs.
+//!
+//! A catalog of the variants, mapping the bindings of parameters to the names of the generated
+//! streamlets, can be written as JSON to [`FILE_NAME`], e.g.:
+//!
+//! ```json
+//! {
+//!   "variants": [
+//!     { "streamlet": "x", "name": "x_width_8", "parameters": { "width": 8 } }
+//!   ]
+//! }
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use std::convert::TryInto;
+//! use tydi::design::{Interface, Mode, Streamlet};
+//! use tydi::generator::variants::{sweep, Variants};
+//! use tydi::logical::LogicalType;
+//! use tydi::{Identify, Name};
+//!
+//! let variants = Variants::new().with(
+//!     "x",
+//!     sweep(vec![("width", vec![8, 16])])?,
+//!     |bindings| {
+//!         let width = LogicalType::try_new_bits(bindings.get("width").unwrap())?;
+//!         let a: Interface = (Name::try_new("a")?, Mode::In, width).try_into()?;
+//!         Streamlet::try_new("x", vec![a])
+//!     },
+//! )?;
+//! let library = variants.library(Name::try_new("lib")?)?;
+//! assert_eq!(
+//!     library.streamlets().iter().map(|s| s.identifier().to_string()).collect::<Vec<_>>(),
+//!     vec!["x_width_8", "x_width_16"]
+//! );
+//! # Ok::<(), tydi::Error>(())
+//! ```
+//!
+//! [`Bindings`]: struct.Bindings.html
+//! [`Variants`]: struct.Variants.html
+//! [`FILE_NAME`]: constant.FILE_NAME.html
+
+use crate::design::{Library, Streamlet};
+use crate::generator::manifest::json_string;
+use crate::generator::write_if_changed;
+use crate::traits::Identify;
+use crate::util::IndexMap;
+use crate::{Error, Name, NonNegative, Result, UniquelyNamedBuilder};
+use std::convert::TryInto;
+use std::path::Path;
+
+/// The name of variant catalog files.
+pub const FILE_NAME: &str = "variants.json";
+
+/// Values bound to the parameters of a parameterized streamlet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bindings(IndexMap<Name, NonNegative>);
+
+impl Bindings {
+    /// Construct bindings without parameters.
+    pub fn new() -> Self {
+        Bindings::default()
+    }
+
+    /// Returns these bindings with `value` bound to the parameter named `name`, replacing the
+    /// value of a parameter with the same name. Returns an error if the name is invalid.
+    pub fn with(
+        mut self,
+        name: impl TryInto<Name, Error = impl Into<Box<dyn std::error::Error>>>,
+        value: NonNegative,
+    ) -> Result<Self> {
+        let name = name
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.into().to_string()))?;
+        self.0.insert(name, value);
+        Ok(self)
+    }
+
+    /// Returns the value bound to the parameter named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<NonNegative> {
+        self.0
+            .iter()
+            .find(|(n, _)| **n == *name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns an iterator over the parameters and their values, in the order in which they were
+    /// bound.
+    pub fn iter(&self) -> impl Iterator<Item = (&Name, NonNegative)> {
+        self.0.iter().map(|(name, value)| (name, *value))
+    }
+
+    /// Returns the suffix of the names of variants with these bindings: the names and values of
+    /// the parameters, separated by underscores, e.g. `width_8_depth_4`.
+    pub fn suffix(&self) -> String {
+        self.iter()
+            .map(|(name, value)| format!("{}_{}", name, value))
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+}
+
+/// Returns the bindings of all combinations of the given values of parameters, varying the
+/// value of the last parameter fastest. Returns an error if the name of a parameter is invalid.
+pub fn sweep(
+    parameters: impl IntoIterator<Item = (impl Into<String>, Vec<NonNegative>)>,
+) -> Result<Vec<Bindings>> {
+    let mut result = vec![Bindings::new()];
+    for (name, values) in parameters {
+        let name = Name::try_new(name)?;
+        result = result
+            .into_iter()
+            .flat_map(|bindings| {
+                values
+                    .iter()
+                    .map(|value| bindings.clone().with(name.clone(), *value))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Result<_>>()?;
+    }
+    Ok(result)
+}
+
+/// A variant of a parameterized streamlet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// The name of the parameterized streamlet.
+    parameterized: Name,
+    /// The bindings of the parameters of this variant.
+    bindings: Bindings,
+    /// The elaborated streamlet, named after the parameterized streamlet and the bindings.
+    streamlet: Streamlet,
+}
+
+impl Variant {
+    /// Returns the name of the parameterized streamlet this is a variant of.
+    pub fn parameterized(&self) -> &Name {
+        &self.parameterized
+    }
+
+    /// Returns the bindings of the parameters of this variant.
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Returns the elaborated streamlet.
+    pub fn streamlet(&self) -> &Streamlet {
+        &self.streamlet
+    }
+}
+
+/// Variants of parameterized streamlets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variants {
+    variants: Vec<Variant>,
+}
+
+impl Variants {
+    /// Construct an empty set of variants.
+    pub fn new() -> Self {
+        Variants::default()
+    }
+
+    /// Elaborate the parameterized streamlet named `name` for every bindings in `bindings`,
+    /// where `elaborate` returns the streamlet for given bindings. The elaborated streamlets are
+    /// renamed to `name`, suffixed with the [suffix](struct.Bindings.html#method.suffix) of their
+    /// bindings, if any.
+    ///
+    /// Returns an error if the name is invalid, or if `elaborate` returns an error.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        bindings: impl IntoIterator<Item = Bindings>,
+        elaborate: impl Fn(&Bindings) -> Result<Streamlet>,
+    ) -> Result<()> {
+        let name = Name::try_new(name)?;
+        for bindings in bindings {
+            let streamlet = elaborate(&bindings)?;
+            let suffix = bindings.suffix();
+            let variant = if suffix.is_empty() {
+                name.clone()
+            } else {
+                Name::try_new(format!("{}_{}", name, suffix))?
+            };
+            self.variants.push(Variant {
+                parameterized: name.clone(),
+                bindings,
+                streamlet: streamlet.with_name(variant),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns these variants with the variants of a parameterized streamlet added, like
+    /// [`add`](#method.add).
+    pub fn with(
+        mut self,
+        name: impl Into<String>,
+        bindings: impl IntoIterator<Item = Bindings>,
+        elaborate: impl Fn(&Bindings) -> Result<Streamlet>,
+    ) -> Result<Self> {
+        self.add(name, bindings, elaborate)?;
+        Ok(self)
+    }
+
+    /// Returns an iterator over the variants, in the order in which they were elaborated.
+    pub fn iter(&self) -> impl Iterator<Item = &Variant> {
+        self.variants.iter()
+    }
+
+    /// Returns a library named `name` with the streamlets of all variants. Returns an error if
+    /// variants have the same name, e.g. when bindings are repeated.
+    pub fn library(&self, name: Name) -> Result<Library> {
+        Library::from_builder(
+            name,
+            UniquelyNamedBuilder::new()
+                .with_items(self.variants.iter().map(|v| v.streamlet.clone())),
+        )
+    }
+
+    /// Returns the catalog of the variants as JSON.
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{\n  \"variants\": [");
+        for (index, variant) in self.variants.iter().enumerate() {
+            result.push_str(if index == 0 { "\n" } else { ",\n" });
+            let parameters = variant
+                .bindings
+                .iter()
+                .map(|(name, value)| format!("{}: {}", json_string(name.as_ref()), value))
+                .collect::<Vec<_>>();
+            let parameters = if parameters.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", parameters.join(", "))
+            };
+            result.push_str(
+                format!(
+                    "    {{ \"streamlet\": {}, \"name\": {}, \"parameters\": {} }}",
+                    json_string(variant.parameterized.as_ref()),
+                    json_string(variant.streamlet.identifier()),
+                    parameters
+                )
+                .as_str(),
+            );
+        }
+        result.push_str(if self.variants.is_empty() {
+            "]\n"
+        } else {
+            "\n  ]\n"
+        });
+        result.push_str("}\n");
+        result
+    }
+
+    /// Write the catalog of the variants to [`FILE_NAME`] in `dir`, unless it did not change and
+    /// `force` is false. Returns true if the catalog was written.
+    ///
+    /// [`FILE_NAME`]: constant.FILE_NAME.html
+    pub fn write(&self, dir: impl AsRef<Path>, force: bool) -> Result<bool> {
+        write_if_changed(dir.as_ref().join(FILE_NAME), self.to_json(), force)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::{Interface, Mode};
+    use crate::logical::LogicalType;
+
+    fn elaborate(bindings: &Bindings) -> Result<Streamlet> {
+        let data = LogicalType::try_new_bits(bindings.get("width").unwrap_or(1))?;
+        let a: Interface = (Name::try_new("a")?, Mode::In, data).try_into()?;
+        Streamlet::try_new("ignored", vec![a])
+    }
+
+    #[test]
+    fn variants() -> Result<()> {
+        let bindings = sweep(vec![("width", vec![8, 16]), ("depth", vec![4])])?;
+        assert_eq!(
+            bindings.iter().map(Bindings::suffix).collect::<Vec<_>>(),
+            vec!["width_8_depth_4", "width_16_depth_4"]
+        );
+        assert!(sweep(vec![("_x", vec![1])]).is_err());
+
+        let variants = Variants::new().with("x", bindings, elaborate)?.with(
+            "y",
+            vec![Bindings::new()],
+            elaborate,
+        )?;
+        let library = variants.library(Name::try_new("lib")?)?;
+        assert_eq!(
+            library
+                .streamlets()
+                .iter()
+                .map(|s| s.identifier().to_string())
+                .collect::<Vec<_>>(),
+            vec!["x_width_8_depth_4", "x_width_16_depth_4", "y"]
+        );
+        assert_eq!(
+            variants.to_json(),
+            r#"{
+  "variants": [
+    { "streamlet": "x", "name": "x_width_8_depth_4", "parameters": { "width": 8, "depth": 4 } },
+    { "streamlet": "x", "name": "x_width_16_depth_4", "parameters": { "width": 16, "depth": 4 } },
+    { "streamlet": "y", "name": "y", "parameters": {} }
+  ]
+}
+"#
+        );
+        assert_eq!(Variants::new().to_json(), "{\n  \"variants\": []\n}\n");
+
+        let repeated =
+            Variants::new().with("x", vec![Bindings::new(), Bindings::new()], elaborate)?;
+        assert!(repeated.library(Name::try_new("lib")?).is_err());
+        Ok(())
+    }
+}
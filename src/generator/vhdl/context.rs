@@ -0,0 +1,91 @@
+//! Context declarations.
+//!
+//! Generates a VHDL-2008 context declaration for every library, with the library and use
+//! clauses that design units need to use the generated packages. Generated design units then
+//! reference the context instead of repeating the use clauses, and so can user code.
+//!
+//! Context declarations cannot refer to the `work` library, so they refer to the generated
+//! packages through the logical library that they are compiled into.
+
+/// Returns the identifier of the context of the package with identifier `package`.
+pub fn context_identifier(package: &str) -> String {
+    format!("{}_context", package)
+}
+
+/// Returns the context clause of generated design units that use the package with identifier
+/// `package`, without a context declaration.
+pub fn use_clauses(package: &str) -> String {
+    format!(
+        "library ieee;\nuse ieee.std_logic_1164.all;\n\nlibrary work;\nuse work.{}.all;\n\n",
+        package
+    )
+}
+
+/// Returns a context declaration for the package with identifier `package`, which is compiled
+/// into `library`. If `fields` is set, the context also uses the package with field slicing
+/// helpers of the package.
+pub fn declare_context(package: &str, library: &str, fields: bool) -> String {
+    let mut result = format!("context {} is\n", context_identifier(package));
+    result.push_str("  library ieee;\n");
+    result.push_str("  use ieee.std_logic_1164.all;\n");
+    result.push_str("  use ieee.numeric_std.all;\n\n");
+    result.push_str(format!("  library {};\n", library).as_str());
+    result.push_str(format!("  use {}.{}.all;\n", library, package).as_str());
+    if fields {
+        result.push_str(format!("  use {}.{}_fields.all;\n", library, package).as_str());
+    }
+    result.push_str("end context;\n");
+    result
+}
+
+/// Returns `design_unit`, a design file generated for the package with identifier `package`, with
+/// its use clauses replaced by a reference to the context of the package in `library`.
+pub fn use_context(design_unit: &str, package: &str, library: &str) -> String {
+    design_unit.replacen(
+        use_clauses(package).as_str(),
+        format!(
+            "library {l};\ncontext {l}.{};\n\n",
+            context_identifier(package),
+            l = library
+        )
+        .as_str(),
+        1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::test::test_comp;
+    use crate::generator::vhdl::stubs::DeclareStub;
+    use crate::Result;
+
+    #[test]
+    fn context() -> Result<()> {
+        assert_eq!(
+            declare_context("lib", "proj", true),
+            "context lib_context is
+  library ieee;
+  use ieee.std_logic_1164.all;
+  use ieee.numeric_std.all;
+
+  library proj;
+  use proj.lib.all;
+  use proj.lib_fields.all;
+end context;
+"
+        );
+        assert!(use_context(
+            test_comp().declare_stub("lib", false)?.as_str(),
+            "lib",
+            "proj"
+        )
+        .starts_with(
+            "library proj;
+context proj.lib_context;
+
+architecture behavioral of test_comp is"
+        ));
+        Ok(())
+    }
+}
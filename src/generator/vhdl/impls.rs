@@ -2,7 +2,11 @@
 
 use crate::error::Error::BackEndError;
 use crate::generator::common::{Component, Mode, Package, Port, Record, Type};
-use crate::generator::vhdl::{Analyze, Declare, DeclareType, Split, VHDLIdentifier};
+use crate::generator::vhdl::context::use_clauses;
+use crate::generator::vhdl::{
+    Analyze, Declare, DeclareEntity, DeclareType, DeclareWithDefaults, DefaultValue, Split,
+    VHDLIdentifier,
+};
 use crate::traits::Identify;
 use crate::{cat, Document, Result};
 use std::collections::HashMap;
@@ -45,6 +49,99 @@ fn declare_rec(rec: &Record) -> Result<String> {
     }
 }
 
+/// Returns the record types that are declared for a record, split and suffixed like
+/// [`DeclareType::declare`] declares them, in the same order.
+fn declared_records(rec: &Record, is_root_type: bool) -> Vec<Record> {
+    fn in_order(rec: Record) -> Vec<Record> {
+        let mut result = vec![];
+        for field in rec.fields() {
+            if let Type::Record(nested) = field.typ() {
+                result.extend(declared_records(nested, false));
+            }
+        }
+        result.push(rec);
+        result
+    }
+
+    if rec.has_reversed() {
+        let (dn, up) = rec.split();
+        let suffix = |s| if is_root_type { s } else { "" };
+        let mut result = in_order(dn.unwrap().append_name_nested(suffix("dn")));
+        result.extend(in_order(up.unwrap().append_name_nested(suffix("up"))));
+        result
+    } else {
+        in_order(rec.clone())
+    }
+}
+
+/// Declare the record types of a root record type as subtypes of those of another root record
+/// type with the same shape.
+fn declare_aliases(rec: &Record, of: &Record) -> Result<String> {
+    declared_records(rec, true)
+        .iter()
+        .zip(declared_records(of, true))
+        .map(|(alias, actual)| {
+            Ok(format!(
+                "subtype {} is {};",
+                alias.vhdl_identifier()?,
+                actual.vhdl_identifier()?
+            ))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Returns the identifier of the default constant of a record type.
+fn default_identifier(rec: &Record) -> String {
+    cat!(rec.identifier().to_string(), "default")
+}
+
+/// Declare the default constants of the record types that are declared for a record, in the
+/// order in which [`DeclareType::declare`] declares them.
+fn declare_defaults(rec: &Record, is_root_type: bool, value: DefaultValue) -> Result<Vec<String>> {
+    declared_records(rec, is_root_type)
+        .iter()
+        .map(|rec| {
+            let associations = rec
+                .fields()
+                .map(|field| {
+                    let actual = match field.typ() {
+                        Type::Bit => value.literal().to_string(),
+                        Type::BitVec { .. } => format!("(others => {})", value.literal()),
+                        // Nested records are declared, with their defaults, before this one.
+                        Type::Record(nested) => default_identifier(nested),
+                    };
+                    format!("{} => {}", field.identifier(), actual)
+                })
+                .collect::<Vec<_>>();
+            Ok(format!(
+                "constant {} : {} := ({});",
+                default_identifier(rec),
+                rec.vhdl_identifier()?,
+                associations.join(", ")
+            ))
+        })
+        .collect()
+}
+
+/// Declare the default constants of the record types of a root record type that are declared as
+/// subtypes of those of another root record type, see [`declare_aliases`].
+fn declare_alias_defaults(rec: &Record, of: &Record) -> Result<String> {
+    declared_records(rec, true)
+        .iter()
+        .zip(declared_records(of, true))
+        .map(|(alias, actual)| {
+            Ok(format!(
+                "constant {} : {} := {};",
+                default_identifier(alias),
+                alias.vhdl_identifier()?,
+                default_identifier(&actual)
+            ))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
 impl DeclareType for Record {
     fn declare(&self, is_root_type: bool) -> Result<String> {
         let mut result = String::new();
@@ -108,7 +205,7 @@ impl Analyze for Type {
                 let mut result: Vec<Type> = vec![self.clone()];
                 for f in rec.fields() {
                     let children = f.typ().list_record_types();
-                    result.extend(children.into_iter());
+                    result.extend(children);
                 }
                 result
             }
@@ -144,6 +241,46 @@ impl VHDLIdentifier for Port {
     }
 }
 
+/// Declare the port clause of a component or entity, if it has any ports.
+fn declare_ports(ports: &[Port]) -> Result<String> {
+    let mut result = String::new();
+    if !ports.is_empty() {
+        let mut ports = ports.iter().peekable();
+        result.push_str("  port(\n");
+        while let Some(p) = ports.next() {
+            result.push_str("    ");
+            // If the port type has reversed fields, we need to split it up because VHDL.
+            if p.has_reversed() {
+                let (dn, up) = p.split();
+                match dn {
+                    None => unreachable!(),
+                    Some(dn_port) => {
+                        result.push_str(dn_port.declare()?.as_str());
+                        result.push_str(";\n");
+                    }
+                };
+                match up {
+                    None => unreachable!(),
+                    Some(up_port) => {
+                        result.push_str("    ");
+                        result.push_str(up_port.declare()?.as_str());
+                    }
+                };
+            } else {
+                result.push_str(p.declare()?.as_str());
+            }
+
+            if ports.peek().is_some() {
+                result.push_str(";\n");
+            } else {
+                result.push('\n');
+            }
+        }
+        result.push_str("  );\n")
+    }
+    Ok(result)
+}
+
 impl Declare for Component {
     fn declare(&self) -> Result<String> {
         let mut result = String::new();
@@ -153,41 +290,78 @@ impl Declare for Component {
             result.push('\n');
         }
         result.push_str(format!("component {}\n", self.identifier()).as_str());
-        if !self.ports().is_empty() {
-            let mut ports = self.ports().iter().peekable();
-            result.push_str("  port(\n");
-            while let Some(p) = ports.next() {
-                result.push_str("    ");
-                // If the port type has reversed fields, we need to split it up because VHDL.
-                if p.has_reversed() {
-                    let (dn, up) = p.split();
-                    match dn {
-                        None => unreachable!(),
-                        Some(dn_port) => {
-                            result.push_str(dn_port.declare()?.as_str());
-                            result.push_str(";\n");
-                        }
-                    };
-                    match up {
-                        None => unreachable!(),
-                        Some(up_port) => {
-                            result.push_str("    ");
-                            result.push_str(up_port.declare()?.as_str());
-                        }
-                    };
-                } else {
-                    result.push_str(p.declare()?.as_str());
-                }
+        result.push_str(declare_ports(self.ports())?.as_str());
+        result.push_str("end component;");
+        Ok(result)
+    }
+}
 
-                if ports.peek().is_some() {
-                    result.push_str(";\n");
-                } else {
-                    result.push('\n');
-                }
+/// Prefix of the keys of attributes that are emitted as VHDL attributes.
+const ATTRIBUTE_PREFIX: &str = "vhdl.";
+
+/// Declare the VHDL attributes of a component and its ports, as entity declarative items.
+fn declare_attributes(component: &Component) -> String {
+    let mut specs: Vec<(String, String, &str, String)> = vec![];
+    let mut push = |item: String, class, attributes: &[(String, String)]| {
+        for (key, value) in attributes {
+            if let Some(name) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+                specs.push((name.to_string(), item.clone(), class, value.clone()));
             }
-            result.push_str("  );\n")
         }
-        result.push_str("end component;");
+    };
+    push(
+        component.identifier().to_string(),
+        "entity",
+        component.attributes(),
+    );
+    for port in component.ports() {
+        // Ports with reversed fields are split up, but keep the attributes of the port.
+        if port.has_reversed() {
+            let (dn, up) = port.split();
+            for p in dn.iter().chain(up.iter()) {
+                push(p.identifier().to_string(), "signal", port.attributes());
+            }
+        } else {
+            push(port.identifier().to_string(), "signal", port.attributes());
+        }
+    }
+
+    let mut result = String::new();
+    let mut declared: Vec<&String> = vec![];
+    for (name, _, _, _) in &specs {
+        if !declared.contains(&name) {
+            result.push_str(format!("  attribute {} : string;\n", name).as_str());
+            declared.push(name);
+        }
+    }
+    for (name, item, class, value) in &specs {
+        result.push_str(
+            format!(
+                "  attribute {} of {} : {} is \"{}\";\n",
+                name,
+                item,
+                class,
+                value.replace('"', "\"\"")
+            )
+            .as_str(),
+        );
+    }
+    result
+}
+
+impl DeclareEntity for Component {
+    fn declare_entity(&self, package: &str) -> Result<String> {
+        let mut result = String::new();
+        result.push_str(use_clauses(package).as_str());
+        if let Some(doc) = self.doc() {
+            result.push_str("--");
+            result.push_str(doc.replace('\n', "\n--").as_str());
+            result.push('\n');
+        }
+        result.push_str(format!("entity {} is\n", self.identifier()).as_str());
+        result.push_str(declare_ports(self.ports())?.as_str());
+        result.push_str(declare_attributes(self).as_str());
+        result.push_str(format!("end {};", self.identifier()).as_str());
         Ok(result)
     }
 }
@@ -196,8 +370,12 @@ impl Analyze for Component {
     fn list_record_types(&self) -> Vec<Type> {
         let mut result: Vec<Type> = vec![];
         for p in self.ports().iter() {
+            // Ports may share a record type, e.g. when their interfaces refer to the same named
+            // type, so only list every type once.
             if let Type::Record(_) = p.typ() {
-                result.push(p.typ())
+                if !result.contains(&p.typ()) {
+                    result.push(p.typ())
+                }
             }
         }
         result
@@ -206,39 +384,85 @@ impl Analyze for Component {
 
 impl Declare for Package {
     fn declare(&self) -> Result<String> {
-        let mut result = String::new();
-        result.push_str(format!("package {} is\n\n", self.identifier).as_str());
-
-        // Whatever generated the common representation is responsible to not to use the same
-        // identifiers for different types.
-        // Use a set to remember which type identifiers we've already used, so we don't declare
-        // them twice, and produce an error otherwise.
-        let mut type_ids = HashMap::<String, Type>::new();
-        for c in &self.components {
-            let comp_records = c.list_record_types();
-            for r in comp_records.iter() {
-                match type_ids.get(&r.vhdl_identifier()?) {
-                    None => {
-                        type_ids.insert(r.vhdl_identifier()?, r.clone());
-                        result.push_str(format!("{}\n\n", r.declare(true)?).as_str());
-                    }
-                    Some(already_defined_type) => {
-                        if r != already_defined_type {
-                            return Err(BackEndError(format!(
-                                "Type name conflict: {}",
-                                already_defined_type
-                                    .vhdl_identifier()
-                                    .unwrap_or_else(|_| "".to_string())
-                            )));
+        declare_package(self, None)
+    }
+}
+
+impl DeclareWithDefaults for Package {
+    fn declare_with_defaults(&self, value: DefaultValue) -> Result<String> {
+        declare_package(self, Some(value))
+    }
+}
+
+/// Declare a package, with the default constants of its record types if `defaults` is set.
+fn declare_package(package: &Package, defaults: Option<DefaultValue>) -> Result<String> {
+    let mut result = String::new();
+    result.push_str(format!("package {} is\n\n", package.identifier).as_str());
+
+    // Whatever generated the common representation is responsible to not to use the same
+    // identifiers for different types.
+    // Use a set to remember which type identifiers we've already used, so we don't declare
+    // them twice, and produce an error otherwise.
+    let mut type_ids = HashMap::<String, Type>::new();
+    // Types that are declared in full. Types with the same shape as one of these are declared
+    // as subtypes of it, so identical types are only declared once.
+    let mut declared: Vec<Record> = vec![];
+    for c in &package.components {
+        let comp_records = c.list_record_types();
+        for r in comp_records.iter() {
+            match type_ids.get(&r.vhdl_identifier()?) {
+                None => {
+                    type_ids.insert(r.vhdl_identifier()?, r.clone());
+                    let rec = match r {
+                        Type::Record(rec) => rec,
+                        _ => unreachable!(),
+                    };
+                    match declared
+                        .iter()
+                        .find(|d| Type::Record((*d).clone()).same_shape(r))
+                    {
+                        Some(actual) => {
+                            result.push_str(
+                                format!("{}\n\n", declare_aliases(rec, actual)?).as_str(),
+                            );
+                            if defaults.is_some() {
+                                result.push_str(
+                                    format!("{}\n\n", declare_alias_defaults(rec, actual)?)
+                                        .as_str(),
+                                );
+                            }
+                        }
+                        None => {
+                            declared.push(rec.clone());
+                            result.push_str(format!("{}\n\n", r.declare(true)?).as_str());
+                            if let Some(value) = defaults {
+                                result.push_str(
+                                    format!(
+                                        "{}\n\n",
+                                        declare_defaults(rec, true, value)?.join("\n")
+                                    )
+                                    .as_str(),
+                                );
+                            }
                         }
                     }
                 }
+                Some(already_defined_type) => {
+                    if r != already_defined_type {
+                        return Err(BackEndError(format!(
+                            "Type name conflict: {}",
+                            already_defined_type
+                                .vhdl_identifier()
+                                .unwrap_or_else(|_| "".to_string())
+                        )));
+                    }
+                }
             }
-            result.push_str(format!("{}\n\n", c.declare()?).as_str());
         }
-        result.push_str(format!("end {};", self.identifier).as_str());
-        Ok(result)
+        result.push_str(format!("{}\n\n", c.declare()?).as_str());
     }
+    result.push_str(format!("end {};", package.identifier).as_str());
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -308,6 +532,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn record_defaults() -> Result<()> {
+        let rec = match records::rec_rev_nested("rec") {
+            Type::Record(rec) => rec,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            declare_defaults(&rec, true, DefaultValue::Zero)?,
+            vec![
+                "constant rec_a_dn_default : rec_a_dn_type := (c => (others => '0'), d => (others => '0'));",
+                "constant rec_b_dn_default : rec_b_dn_type := (c => (others => '0'));",
+                "constant rec_dn_default : rec_dn_type := (a => rec_a_dn_default, b => rec_b_dn_default);",
+                "constant rec_b_up_default : rec_b_up_type := (d => (others => '0'));",
+                "constant rec_up_default : rec_up_type := (b => rec_b_up_default);",
+            ]
+        );
+
+        let package = Package {
+            identifier: "lib".to_string(),
+            components: vec![
+                test_comp(),
+                Component::new(
+                    "x",
+                    vec![],
+                    vec![Port::new("c", Mode::In, records::rec_rev("c"))],
+                    None,
+                ),
+            ],
+        }
+        .declare_with_defaults(DefaultValue::DontCare)?;
+        assert!(package.contains(
+            "constant b_dn_default : b_dn_type := (a => b_a_dn_default, b => b_b_dn_default);"
+        ));
+        assert!(
+            package.contains("constant b_b_up_default : b_b_up_type := (d => (others => '-'));")
+        );
+        assert!(package.contains("constant c_up_default : c_up_type := a_up_default;"));
+        Ok(())
+    }
+
     #[test]
     fn port_decl() {
         let p = Port::new("test", Mode::In, Type::BitVec { width: 10 });
@@ -322,8 +586,7 @@ mod test {
         let c = test_comp().with_doc(" My awesome\n Component".to_string());
         assert_eq!(
             c.declare().unwrap(),
-            concat!(
-                "-- My awesome
+            "-- My awesome
 -- Component
 component test_comp
   port(
@@ -333,10 +596,59 @@ component test_comp
     b_up : in b_up_type
   );
 end component;"
-            )
         );
     }
 
+    #[test]
+    fn entity_decl() {
+        let c = test_comp().with_doc(" My awesome\n Component".to_string());
+        assert_eq!(
+            c.declare_entity("test").unwrap(),
+            "library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.test.all;
+
+-- My awesome
+-- Component
+entity test_comp is
+  port(
+    a_dn : in a_dn_type;
+    a_up : out a_up_type;
+    b_dn : out b_dn_type;
+    b_up : in b_up_type
+  );
+end test_comp;"
+        );
+    }
+
+    #[test]
+    fn entity_attributes() {
+        let c = Component::new(
+            "test_comp",
+            vec![],
+            vec![
+                Port::new("a", Mode::In, Type::Bit)
+                    .with_attributes(vec![("vhdl.mark_debug", "true"), ("vendor", "x")]),
+                Port::new("b", Mode::Out, records::rec_rev("b"))
+                    .with_attributes(vec![("vhdl.mark_debug", "true")]),
+            ],
+            None,
+        )
+        .with_attributes(vec![("vhdl.keep_hierarchy", "say \"yes\"")]);
+        assert!(c.declare_entity("test").unwrap().ends_with(
+            "  );
+  attribute keep_hierarchy : string;
+  attribute mark_debug : string;
+  attribute keep_hierarchy of test_comp : entity is \"say \"\"yes\"\"\";
+  attribute mark_debug of a : signal is \"true\";
+  attribute mark_debug of b_dn : signal is \"true\";
+  attribute mark_debug of b_up : signal is \"true\";
+end test_comp;"
+        ));
+    }
+
     #[test]
     fn package_decl() {
         let p = Package {
@@ -3,21 +3,36 @@
 //! This module contains functionality to convert hardware defined in the common hardware
 //! representation to VHDL source files.
 
-use crate::design::Project;
+use crate::design::{Project, Streamlet};
 use crate::generator::common::*;
-use crate::generator::GenerateProject;
-use crate::{Error, Result, Reversed};
-use log::debug;
-use std::path::Path;
+use crate::generator::config::{expect_identifier_style, merge_flag, Configure, Value};
+use crate::generator::{pruned_streams_comment, write_if_changed, GenerateProject, Lowered};
+use crate::{Context, Error, Result, Reversed};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
 
 use crate::cat;
-use crate::generator::common::convert::Packify;
+use crate::experimental::Features;
+use crate::generator::header::HeaderConfig;
+use crate::generator::manifest::Manifest;
+use crate::generator::regions;
+use crate::generator::vhdl::context::{declare_context, use_context};
+use crate::generator::vhdl::psl::DeclareAssertions;
+use crate::generator::vhdl::slices::DeclareFieldSlices;
+use crate::generator::vhdl::stubs::DeclareStub;
+use crate::generator::vhdl::toplevel::{DeclareToplevel, ToplevelBus};
 use crate::traits::Identify;
 use std::str::FromStr;
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
 
+pub mod context;
 mod impls;
+pub mod psl;
+pub mod render;
+pub mod slices;
+pub mod stubs;
+pub mod toplevel;
 
 /// Generate trait for generic VHDL declarations.
 pub trait Declare {
@@ -25,12 +40,26 @@ pub trait Declare {
     fn declare(&self) -> Result<String>;
 }
 
+/// Generate trait for VHDL package declarations that include a default constant of every record
+/// type.
+pub trait DeclareWithDefaults {
+    /// Generate a VHDL declaration from self, with the bits of the constants set to `value`.
+    fn declare_with_defaults(&self, value: DefaultValue) -> Result<String>;
+}
+
 /// Generate trait for VHDL type declarations.
 pub trait DeclareType {
     /// Generate a VHDL declaration from self.
     fn declare(&self, is_root_type: bool) -> Result<String>;
 }
 
+/// Generate trait for VHDL entity declarations.
+pub trait DeclareEntity {
+    /// Generate a VHDL design file with an entity declaration from self, using the types declared
+    /// in the package with the given identifier.
+    fn declare_entity(&self, package: &str) -> Result<String>;
+}
+
 /// Generate trait for VHDL package declarations.
 pub trait DeclareLibrary {
     /// Generate a VHDL declaration from self.
@@ -50,19 +79,14 @@ pub trait Analyze {
 }
 
 /// Abstraction levels
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
 pub enum AbstractionLevel {
     Canonical,
+    #[default]
     Fancy,
 }
 
-impl Default for AbstractionLevel {
-    fn default() -> Self {
-        AbstractionLevel::Fancy
-    }
-}
-
 impl FromStr for AbstractionLevel {
     type Err = Error;
 
@@ -75,8 +99,232 @@ impl FromStr for AbstractionLevel {
     }
 }
 
+/// VHDL language standards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VHDLStandard {
+    /// VHDL-93, for legacy tools.
+    Vhdl93,
+    /// VHDL-2008.
+    #[default]
+    Vhdl2008,
+}
+
+/// Reserved words of VHDL-93.
+const RESERVED_93: &[&str] = &[
+    "abs",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "attribute",
+    "begin",
+    "block",
+    "body",
+    "buffer",
+    "bus",
+    "case",
+    "component",
+    "configuration",
+    "constant",
+    "disconnect",
+    "downto",
+    "else",
+    "elsif",
+    "end",
+    "entity",
+    "exit",
+    "file",
+    "for",
+    "function",
+    "generate",
+    "generic",
+    "group",
+    "guarded",
+    "if",
+    "impure",
+    "in",
+    "inertial",
+    "inout",
+    "is",
+    "label",
+    "library",
+    "linkage",
+    "literal",
+    "loop",
+    "map",
+    "mod",
+    "nand",
+    "new",
+    "next",
+    "nor",
+    "not",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "package",
+    "port",
+    "postponed",
+    "procedure",
+    "process",
+    "pure",
+    "range",
+    "record",
+    "register",
+    "reject",
+    "rem",
+    "report",
+    "return",
+    "rol",
+    "ror",
+    "select",
+    "severity",
+    "signal",
+    "shared",
+    "sla",
+    "sll",
+    "sra",
+    "srl",
+    "subtype",
+    "then",
+    "to",
+    "transport",
+    "type",
+    "unaffected",
+    "units",
+    "until",
+    "use",
+    "variable",
+    "wait",
+    "when",
+    "while",
+    "with",
+    "xnor",
+    "xor",
+];
+
+/// Words that are reserved in VHDL-2008, in addition to those of VHDL-93.
+const RESERVED_2008: &[&str] = &[
+    "assume",
+    "assume_guarantee",
+    "context",
+    "cover",
+    "default",
+    "fairness",
+    "force",
+    "parameter",
+    "property",
+    "protected",
+    "release",
+    "restrict",
+    "restrict_guarantee",
+    "sequence",
+    "strong",
+    "vmode",
+    "vprop",
+    "vunit",
+];
+
+impl VHDLStandard {
+    /// Returns true if an identifier is a reserved word in this standard. VHDL identifiers are
+    /// case-insensitive.
+    pub fn is_reserved(&self, identifier: &str) -> bool {
+        let identifier = identifier.to_lowercase();
+        RESERVED_93.contains(&identifier.as_str())
+            || (*self == VHDLStandard::Vhdl2008 && RESERVED_2008.contains(&identifier.as_str()))
+    }
+
+    /// Returns an error if a component, port or record field of a package has an identifier
+    /// that is a reserved word in this standard.
+    pub fn check_identifiers(&self, package: &Package) -> Result<()> {
+        let check = |kind: &str, identifier: &str| {
+            if self.is_reserved(identifier) {
+                Err(Error::BackEndError(format!(
+                    "{} {} of package {} is a reserved word in {}",
+                    kind, identifier, package.identifier, self
+                )))
+            } else {
+                Ok(())
+            }
+        };
+        for component in &package.components {
+            check("Component", component.identifier())?;
+            for port in component.ports() {
+                check("Port", port.identifier())?;
+                for typ in port.typ().list_record_types() {
+                    if let Type::Record(rec) = typ {
+                        for field in rec.fields() {
+                            check("Field", field.identifier())?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for VHDLStandard {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "93" => Ok(VHDLStandard::Vhdl93),
+            "2008" => Ok(VHDLStandard::Vhdl2008),
+            _ => Err(Error::InvalidArgument(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for VHDLStandard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VHDLStandard::Vhdl93 => write!(f, "VHDL-93"),
+            VHDLStandard::Vhdl2008 => write!(f, "VHDL-2008"),
+        }
+    }
+}
+
+/// Values of the bits of the default constants of record types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultValue {
+    /// All bits are '0'.
+    Zero,
+    /// All bits are '-' (don't care).
+    DontCare,
+}
+
+impl DefaultValue {
+    /// Returns the VHDL literal of a bit with this value.
+    pub fn literal(&self) -> &'static str {
+        match self {
+            DefaultValue::Zero => "'0'",
+            DefaultValue::DontCare => "'-'",
+        }
+    }
+}
+
+impl FromStr for DefaultValue {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "0" => Ok(DefaultValue::Zero),
+            "-" => Ok(DefaultValue::DontCare),
+            _ => Err(Error::InvalidArgument(s.to_string())),
+        }
+    }
+}
+
 /// VHDL back-end configuration parameters.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
 pub struct VHDLConfig {
     /// Abstraction level of generated files.
@@ -92,12 +340,261 @@ pub struct VHDLConfig {
     /// generated files are named <name>.gen.vhd.
     #[cfg_attr(feature = "cli", structopt(short, long))]
     suffix: Option<String>,
+
+    /// VHDL standard of generated files, for tools that do not support VHDL-2008.
+    /// Possible options: 93, 2008. Default = 2008.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    standard: Option<VHDLStandard>,
+
+    /// Generate PSL assertions that check the stream protocol of the canonical components.
+    /// The verification units are written to <name>_psl.gen.psl.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    assertions: bool,
+
+    /// Disable --assertions, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "assertions"))]
+    no_assertions: bool,
+
+    /// Generate an entity declaration for every component in its own file, in addition to the
+    /// package. Entity files are written to <library>/<component>.gen.vhd.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    split_files: bool,
+
+    /// Disable --split-files, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "split_files"))]
+    no_split_files: bool,
+
+    /// Generate a package with constants and functions to slice element fields out of the data
+    /// signals of the canonical components, and to derive which element lanes are valid.
+    /// The package is written to <name>_fields_pkg.gen.vhd.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    field_slices: bool,
+
+    /// Disable --field-slices, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "field_slices"))]
+    no_field_slices: bool,
+
+    /// Generate a top-level entity for every library that instantiates its streamlets and
+    /// exposes all of their ports. The entity is written to <name>_toplevel.gen.vhd.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    toplevel: bool,
+
+    /// Disable --toplevel, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "toplevel"))]
+    no_toplevel: bool,
+
+    /// Comma-separated identifiers of the streamlets to instantiate in the top-level entities.
+    /// Default = all streamlets.
+    #[cfg_attr(feature = "cli", structopt(long, use_delimiter = true))]
+    toplevel_streamlets: Vec<String>,
+
+    /// Prefix the top-level ports of every instance with the identifier of its streamlet.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    toplevel_prefix: bool,
+
+    /// Disable --toplevel-prefix, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "toplevel_prefix"))]
+    no_toplevel_prefix: bool,
+
+    /// Declare the components and record types of all streamlets in the packages. By default,
+    /// only those of the streamlets that are selected with --toplevel-streamlets are declared.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    keep_unused: bool,
+
+    /// Disable --keep-unused, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "keep_unused"))]
+    no_keep_unused: bool,
+
+    /// Time-multiplex the physical streams of interfaces onto a single set of top-level ports,
+    /// given as <name>=<streamlet>.<interface>,<streamlet>.<interface>... This option can be
+    /// repeated for multiple buses.
+    #[cfg_attr(feature = "cli", structopt(long, number_of_values = 1))]
+    toplevel_buses: Vec<ToplevelBus>,
+
+    /// Generate a VHDL-2008 context declaration for every library, with the use clauses of its
+    /// packages, and reference it from all generated entities and architectures instead of the
+    /// use clauses. The context is written to <name>_context.gen.vhd.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    context: bool,
+
+    /// Disable --context, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "context"))]
+    no_context: bool,
+
+    /// Logical library that the generated packages are compiled into, which context
+    /// declarations refer to. Default = the identifier of the project.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    context_library: Option<String>,
+
+    /// Generate an architecture stub for every component, to be implemented by the user between
+    /// the -- BEGIN USER and -- END USER markers. Stubs are written to <library>/<component>.vhd,
+    /// and the code between the markers is preserved when they are generated again.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    stubs: bool,
+
+    /// Disable --stubs, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "stubs"))]
+    no_stubs: bool,
+
+    /// List the streams that do not result in ports because they are null in a comment at the
+    /// top of every package.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    pruned_streams: bool,
+
+    /// Disable --pruned-streams, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "pruned_streams"))]
+    no_pruned_streams: bool,
+
+    /// Declare a constant <record>_default of every record type in the packages, with all bits
+    /// set to the given value, to initialize signals and reset registers with.
+    /// Possible options: 0, -.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    record_defaults: Option<DefaultValue>,
+
+    /// Identifier of the clock port of all components. Default = "clk".
+    #[cfg_attr(feature = "cli", structopt(long))]
+    clock: Option<String>,
+
+    /// Identifier of the reset port of all components. Default = "rst".
+    #[cfg_attr(feature = "cli", structopt(long))]
+    reset: Option<String>,
+
+    /// Assert the reset when it is low, rather than when it is high.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    reset_active_low: bool,
+
+    /// Disable --reset-active-low, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "reset_active_low"))]
+    no_reset_active_low: bool,
+
+    /// Use an asynchronous reset, rather than a synchronous reset.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    async_reset: bool,
+
+    /// Disable --async-reset, e.g. when it is enabled in the configuration file.
+    #[cfg_attr(feature = "cli", structopt(long, conflicts_with = "async_reset"))]
+    no_async_reset: bool,
+
+    /// Style of the identifiers of ports and record fields.
+    /// Possible options: preserve, snake, camel. Default = preserve.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    identifier_style: Option<IdentifierStyle>,
+
+    /// Output directory, if not given on the command line. Only set from configuration files.
+    #[cfg_attr(feature = "cli", structopt(skip))]
+    output: Option<PathBuf>,
 }
 
 impl VHDLConfig {
     pub fn abstraction(&self) -> AbstractionLevel {
         self.abstraction.unwrap_or_default()
     }
+
+    /// Returns the suffix of generated files, if any.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns the VHDL standard of generated files.
+    pub fn standard(&self) -> VHDLStandard {
+        self.standard.unwrap_or_default()
+    }
+
+    /// Returns whether PSL protocol assertions are generated.
+    pub fn assertions(&self) -> bool {
+        self.assertions
+    }
+
+    /// Returns whether entities are generated in separate files.
+    pub fn split_files(&self) -> bool {
+        self.split_files
+    }
+
+    /// Returns whether field slicing helpers are generated.
+    pub fn field_slices(&self) -> bool {
+        self.field_slices
+    }
+
+    /// Returns whether top-level entities are generated.
+    pub fn toplevel(&self) -> bool {
+        self.toplevel
+    }
+
+    /// Returns the identifiers of the streamlets to instantiate in the top-level entities, or an
+    /// empty slice if all streamlets are instantiated.
+    pub fn toplevel_streamlets(&self) -> &[String] {
+        self.toplevel_streamlets.as_slice()
+    }
+
+    /// Returns whether the top-level ports are prefixed with the identifiers of the streamlets.
+    pub fn toplevel_prefix(&self) -> bool {
+        self.toplevel_prefix
+    }
+
+    /// Returns whether the components and record types of streamlets that are not selected for
+    /// the top-level entities are declared.
+    pub fn keep_unused(&self) -> bool {
+        self.keep_unused
+    }
+
+    /// Returns the buses of the top-level entities.
+    pub fn toplevel_buses(&self) -> &[ToplevelBus] {
+        self.toplevel_buses.as_slice()
+    }
+
+    /// Returns whether context declarations are generated.
+    pub fn context(&self) -> bool {
+        self.context
+    }
+
+    /// Returns the logical library that context declarations refer to, if it is configured.
+    pub fn context_library(&self) -> Option<&str> {
+        self.context_library.as_deref()
+    }
+
+    /// Returns whether architecture stubs are generated.
+    pub fn stubs(&self) -> bool {
+        self.stubs
+    }
+
+    /// Returns whether pruned null streams are listed in the packages.
+    pub fn pruned_streams(&self) -> bool {
+        self.pruned_streams
+    }
+
+    /// Returns the value of the bits of the default constants of record types, if they are
+    /// declared.
+    pub fn record_defaults(&self) -> Option<DefaultValue> {
+        self.record_defaults
+    }
+
+    /// Returns the clock and reset of the generated components.
+    pub fn clock_reset(&self) -> ClockReset {
+        let mut result = ClockReset::default();
+        if let Some(clock) = &self.clock {
+            result = result.with_clock(clock.as_str());
+        }
+        if let Some(reset) = &self.reset {
+            result = result.with_reset(reset.as_str());
+        }
+        if self.reset_active_low {
+            result = result.with_polarity(ResetPolarity::ActiveLow);
+        }
+        if self.async_reset {
+            result = result.with_style(ResetStyle::Async);
+        }
+        result
+    }
+
+    /// Returns the style of the identifiers of ports and record fields.
+    pub fn identifier_style(&self) -> IdentifierStyle {
+        self.identifier_style.unwrap_or_default()
+    }
+
+    /// Returns the configured output directory, if any.
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
 }
 
 impl Default for VHDLConfig {
@@ -105,53 +602,524 @@ impl Default for VHDLConfig {
         VHDLConfig {
             suffix: Some("gen".to_string()),
             abstraction: Some(AbstractionLevel::Canonical),
+            standard: None,
+            assertions: false,
+            no_assertions: false,
+            split_files: false,
+            no_split_files: false,
+            field_slices: false,
+            no_field_slices: false,
+            toplevel: false,
+            no_toplevel: false,
+            toplevel_streamlets: vec![],
+            toplevel_prefix: false,
+            no_toplevel_prefix: false,
+            keep_unused: false,
+            no_keep_unused: false,
+            toplevel_buses: vec![],
+            context: false,
+            no_context: false,
+            context_library: None,
+            stubs: false,
+            no_stubs: false,
+            pruned_streams: false,
+            no_pruned_streams: false,
+            record_defaults: None,
+            clock: None,
+            reset: None,
+            reset_active_low: false,
+            no_reset_active_low: false,
+            async_reset: false,
+            no_async_reset: false,
+            identifier_style: None,
+            output: None,
         }
     }
 }
 
+impl Configure for VHDLConfig {
+    const SECTION: &'static str = "vhdl";
+    const KEYS: &'static [&'static str] = &[
+        "abstraction",
+        "suffix",
+        "standard",
+        "assertions",
+        "split_files",
+        "field_slices",
+        "toplevel",
+        "toplevel_streamlets",
+        "toplevel_prefix",
+        "keep_unused",
+        "toplevel_buses",
+        "context",
+        "context_library",
+        "stubs",
+        "pruned_streams",
+        "record_defaults",
+        "clock",
+        "reset",
+        "reset_active_low",
+        "async_reset",
+        "identifier_style",
+        "output",
+    ];
+
+    fn set(&mut self, key: &str, value: &Value) -> Result<()> {
+        match key {
+            "abstraction" => {
+                let level = value.expect_str(key)?;
+                self.abstraction = Some(level.parse().map_err(|_| {
+                    Error::ConfigError(format!(
+                        "unknown abstraction level \"{}\", expected one of: canon, fancy",
+                        level
+                    ))
+                })?);
+            }
+            "suffix" => {
+                let suffix = value.expect_str(key)?;
+                self.suffix = if suffix.is_empty() {
+                    None
+                } else {
+                    Some(suffix.to_string())
+                };
+            }
+            "standard" => {
+                let standard = match value {
+                    Value::Integer(i) => i.to_string(),
+                    _ => value.expect_str(key)?.to_string(),
+                };
+                self.standard = Some(standard.parse().map_err(|_| {
+                    Error::ConfigError(format!(
+                        "unknown VHDL standard \"{}\", expected one of: 93, 2008",
+                        standard
+                    ))
+                })?);
+            }
+            "assertions" => self.assertions = value.expect_bool(key)?,
+            "split_files" => self.split_files = value.expect_bool(key)?,
+            "field_slices" => self.field_slices = value.expect_bool(key)?,
+            "toplevel" => self.toplevel = value.expect_bool(key)?,
+            "toplevel_streamlets" => {
+                self.toplevel_streamlets = value
+                    .expect_str(key)?
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            }
+            "toplevel_prefix" => self.toplevel_prefix = value.expect_bool(key)?,
+            "keep_unused" => self.keep_unused = value.expect_bool(key)?,
+            "toplevel_buses" => {
+                self.toplevel_buses = value
+                    .expect_str(key)?
+                    .split(';')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse())
+                    .collect::<Result<_>>()?
+            }
+            "context" => self.context = value.expect_bool(key)?,
+            "context_library" => self.context_library = Some(value.expect_str(key)?.to_string()),
+            "stubs" => self.stubs = value.expect_bool(key)?,
+            "pruned_streams" => self.pruned_streams = value.expect_bool(key)?,
+            "record_defaults" => {
+                let default = value.expect_str(key)?;
+                self.record_defaults = Some(default.parse().map_err(|_| {
+                    Error::ConfigError(format!(
+                        "unknown default value \"{}\", expected one of: 0, -",
+                        default
+                    ))
+                })?);
+            }
+            "clock" => self.clock = Some(value.expect_str(key)?.to_string()),
+            "reset" => self.reset = Some(value.expect_str(key)?.to_string()),
+            "reset_active_low" => self.reset_active_low = value.expect_bool(key)?,
+            "async_reset" => self.async_reset = value.expect_bool(key)?,
+            "identifier_style" => {
+                self.identifier_style = Some(expect_identifier_style(key, value)?)
+            }
+            "output" => self.output = Some(PathBuf::from(value.expect_str(key)?)),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, overrides: Self) {
+        self.abstraction = overrides.abstraction.or(self.abstraction);
+        self.suffix = overrides.suffix.or_else(|| self.suffix.take());
+        self.standard = overrides.standard.or(self.standard);
+        merge_flag(
+            &mut self.assertions,
+            overrides.assertions,
+            overrides.no_assertions,
+        );
+        merge_flag(
+            &mut self.split_files,
+            overrides.split_files,
+            overrides.no_split_files,
+        );
+        merge_flag(
+            &mut self.field_slices,
+            overrides.field_slices,
+            overrides.no_field_slices,
+        );
+        merge_flag(
+            &mut self.toplevel,
+            overrides.toplevel,
+            overrides.no_toplevel,
+        );
+        if !overrides.toplevel_streamlets.is_empty() {
+            self.toplevel_streamlets = overrides.toplevel_streamlets;
+        }
+        merge_flag(
+            &mut self.toplevel_prefix,
+            overrides.toplevel_prefix,
+            overrides.no_toplevel_prefix,
+        );
+        merge_flag(
+            &mut self.keep_unused,
+            overrides.keep_unused,
+            overrides.no_keep_unused,
+        );
+        if !overrides.toplevel_buses.is_empty() {
+            self.toplevel_buses = overrides.toplevel_buses;
+        }
+        merge_flag(&mut self.context, overrides.context, overrides.no_context);
+        self.context_library = overrides
+            .context_library
+            .or_else(|| self.context_library.take());
+        merge_flag(&mut self.stubs, overrides.stubs, overrides.no_stubs);
+        merge_flag(
+            &mut self.pruned_streams,
+            overrides.pruned_streams,
+            overrides.no_pruned_streams,
+        );
+        self.record_defaults = overrides.record_defaults.or(self.record_defaults);
+        self.clock = overrides.clock.or_else(|| self.clock.take());
+        self.reset = overrides.reset.or_else(|| self.reset.take());
+        merge_flag(
+            &mut self.reset_active_low,
+            overrides.reset_active_low,
+            overrides.no_reset_active_low,
+        );
+        merge_flag(
+            &mut self.async_reset,
+            overrides.async_reset,
+            overrides.no_async_reset,
+        );
+        self.identifier_style = overrides.identifier_style.or(self.identifier_style);
+        self.output = overrides.output.or_else(|| self.output.take());
+    }
+}
+
 /// A configurable VHDL back-end entry point.
 #[derive(Default)]
 pub struct VHDLBackEnd {
     /// Configuration for the VHDL back-end.
     config: VHDLConfig,
+    /// Whether to write files that have not changed.
+    force: bool,
+    /// Whether to write a manifest of the generated files.
+    manifest: bool,
+    /// The experimental features that are listed in the manifest.
+    features: Features,
+    /// The header of generated files.
+    header: HeaderConfig,
 }
 
 impl VHDLBackEnd {
     pub fn config(&self) -> &VHDLConfig {
         &self.config
     }
+
+    /// Return this back-end that writes all files, even if their contents did not change.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Return this back-end that writes a [manifest](crate::generator::manifest) of the
+    /// generated files to the project directory.
+    pub fn with_manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Return this back-end that lists the enabled experimental `features` in its manifest.
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Return this back-end that starts generated files with a [header](crate::generator::header).
+    pub fn with_header(mut self, header: HeaderConfig) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Write a generated file, if it changed. Returns true if the file was written.
+    fn write(&self, path: &Path, contents: String) -> Result<bool> {
+        let written = write_if_changed(path, contents, self.force)?;
+        if written {
+            info!("Wrote {}.", path.to_str().unwrap_or(""));
+        } else {
+            debug!("Skipped {}, unchanged.", path.to_str().unwrap_or(""));
+        }
+        Ok(written)
+    }
+
+    /// Returns the extension of generated files, including the configured suffix.
+    fn extension(&self, extension: &str) -> String {
+        match self.config.suffix() {
+            None => extension.to_string(),
+            Some(s) => format!("{}.{}", s, extension),
+        }
+    }
 }
 
 impl From<VHDLConfig> for VHDLBackEnd {
     fn from(config: VHDLConfig) -> Self {
-        VHDLBackEnd { config }
+        VHDLBackEnd {
+            config,
+            force: false,
+            manifest: false,
+            features: Features::default(),
+            header: HeaderConfig::default(),
+        }
     }
 }
 
 impl GenerateProject for VHDLBackEnd {
     fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        self.generate_lowered(&Lowered::new(project)?, path)
+    }
+
+    fn generate_lowered(&self, lowered: &Lowered, path: impl AsRef<Path>) -> Result<()> {
+        let project = lowered.project();
         // Create the project directory.
         let mut dir = path.as_ref().to_path_buf();
         dir.push(project.identifier());
         std::fs::create_dir_all(dir.as_path())?;
 
+        if self.config().toplevel() {
+            for name in self.config().toplevel_streamlets() {
+                if !project
+                    .libraries()
+                    .any(|lib| lib.streamlets().iter().any(|s| s.identifier() == name))
+                {
+                    return Err(Error::ConfigError(format!(
+                        "top-level streamlet \"{}\" does not exist",
+                        name
+                    )));
+                }
+            }
+        }
+
+        if self.config().stubs() && self.config().split_files() && self.config().suffix().is_none()
+        {
+            return Err(Error::ConfigError(
+                "stubs and split entity files require a suffix to tell them apart".to_string(),
+            ));
+        }
+
+        if self.config().assertions() && self.config().standard() == VHDLStandard::Vhdl93 {
+            return Err(Error::ConfigError(
+                "PSL assertions require VHDL-2008".to_string(),
+            ));
+        }
+
+        if self.config().context() && self.config().standard() == VHDLStandard::Vhdl93 {
+            return Err(Error::ConfigError(
+                "context declarations require VHDL-2008".to_string(),
+            ));
+        }
+
+        if self.config().assertions()
+            && self.config().identifier_style() != IdentifierStyle::Preserve
+        {
+            return Err(Error::ConfigError(
+                "PSL assertions require the preserve identifier style".to_string(),
+            ));
+        }
+
+        // Only declare what the selected top-level streamlets need.
+        let reachable;
+        let lowered = if self.config().toplevel()
+            && !self.config().toplevel_streamlets().is_empty()
+            && !self.config().keep_unused()
+        {
+            reachable = lowered.reachable(self.config().toplevel_streamlets());
+            &reachable
+        } else {
+            lowered
+        };
+
+        let mut changed = Vec::new();
+        let mut manifest = Manifest::new(project, dir.as_path()).with_features(&self.features);
+        let mut write =
+            |path: PathBuf, contents: String, sources: Vec<(&str, &Streamlet)>| -> Result<()> {
+                let contents = self.header.apply(project.identifier(), "--", contents);
+                manifest.add(path.as_path(), contents.as_bytes(), sources);
+                if self.write(path.as_path(), contents)? {
+                    changed.push(path);
+                }
+                Ok(())
+            };
+
+        let context_library = self
+            .config()
+            .context_library()
+            .unwrap_or_else(|| project.identifier());
+        // Design units reference the context of their library if it is generated.
+        let header = |contents: String, package: &str| {
+            if self.config().context() {
+                use_context(contents.as_str(), package, context_library)
+            } else {
+                contents
+            }
+        };
+
+        let clock_reset = self.config().clock_reset();
         for lib in project.libraries() {
+            // The streamlets that generated files are generated from.
+            let streamlets = lib.streamlets();
+            let all = streamlets
+                .iter()
+                .map(|s| (lib.identifier(), s))
+                .collect::<Vec<_>>();
+            let selected = lowered
+                .streamlets(lib.identifier())
+                .map(|s| (lib.identifier(), s))
+                .collect::<Vec<_>>();
+            let source = |component: &Component| {
+                lowered
+                    .streamlet(lib.identifier(), component.identifier())
+                    .map(|s| (lib.identifier(), s))
+                    .into_iter()
+                    .collect()
+            };
             let mut pkg = dir.clone();
             pkg.push(format!("{}_pkg", lib.identifier()));
-            pkg.set_extension(match self.config.suffix.clone() {
-                None => "vhd".to_string(),
-                Some(s) => format!("{}.vhd", s),
-            });
-            std::fs::write(
-                pkg.as_path(),
-                match self.config().abstraction() {
-                    AbstractionLevel::Canonical => lib.canonical(),
-                    AbstractionLevel::Fancy => lib.fancy(),
+            pkg.set_extension(self.extension("vhd"));
+            let package = match self.config().abstraction() {
+                AbstractionLevel::Canonical => lowered.canonical(lib.identifier()),
+                AbstractionLevel::Fancy => lowered.fancy(lib.identifier()),
+            }
+            .with_clock_reset(&clock_reset)?
+            .with_identifier_style(self.config().identifier_style())?;
+            self.config().standard().check_identifiers(&package)?;
+            let mut contents = match self.config().record_defaults() {
+                Some(value) => package.declare_with_defaults(value)?,
+                None => package.declare()?,
+            };
+            if self.config().pruned_streams() {
+                if let Some(comment) = pruned_streams_comment(lib, "--") {
+                    contents = format!("{}\n{}", comment, contents);
+                }
+            }
+            write(pkg, contents, selected.clone())?;
+
+            let slices = if self.config().field_slices() {
+                lib.declare_field_slices()?
+            } else {
+                None
+            };
+
+            if self.config().context() {
+                let mut context = dir.clone();
+                context.push(format!("{}_context", lib.identifier()));
+                context.set_extension(self.extension("vhd"));
+                write(
+                    context,
+                    declare_context(lib.identifier(), context_library, slices.is_some()),
+                    selected.clone(),
+                )?;
+            }
+
+            if self.config().split_files() {
+                let lib_dir = dir.join(lib.identifier());
+                std::fs::create_dir_all(lib_dir.as_path())?;
+                for component in &package.components {
+                    let mut entity = lib_dir.join(component.identifier());
+                    entity.set_extension(self.extension("vhd"));
+                    write(
+                        entity,
+                        header(
+                            component.declare_entity(package.identifier.as_str())?,
+                            lib.identifier(),
+                        ),
+                        source(component),
+                    )?;
+                }
+            }
+
+            if self.config().stubs() {
+                let lib_dir = dir.join(lib.identifier());
+                std::fs::create_dir_all(lib_dir.as_path())?;
+                for component in &package.components {
+                    let mut stub = lib_dir.join(component.identifier());
+                    stub.set_extension("vhd");
+                    let generated = header(
+                        component.declare_stub(
+                            package.identifier.as_str(),
+                            !self.config().split_files(),
+                        )?,
+                        lib.identifier(),
+                    );
+                    let contents = match std::fs::read_to_string(&stub) {
+                        Ok(existing) => regions::preserve(&existing, &generated, stubs::COMMENT)
+                            .with_context(|| {
+                                format!("Failed to preserve user code in {}", stub.display())
+                            })?,
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => generated,
+                        // Never overwrite a stub that exists but cannot be read.
+                        Err(err) => {
+                            return Err(Error::from(err)).with_context(|| {
+                                format!("Failed to read existing stub {}", stub.display())
+                            })
+                        }
+                    };
+                    write(stub, contents, source(component))?;
                 }
-                .declare()?,
-            )?;
-            debug!("Wrote {}.", pkg.as_path().to_str().unwrap_or(""));
+            }
+
+            if let Some(slices) = slices {
+                let mut fields = dir.clone();
+                fields.push(format!("{}_fields_pkg", lib.identifier()));
+                fields.set_extension(self.extension("vhd"));
+                write(fields, slices, all.clone())?;
+            }
+
+            if self.config().toplevel() {
+                if let Some(toplevel) = lib.declare_toplevel(
+                    self.config().toplevel_streamlets(),
+                    self.config().toplevel_prefix(),
+                    &clock_reset,
+                    self.config().identifier_style(),
+                    self.config().toplevel_buses(),
+                )? {
+                    let mut top = dir.clone();
+                    top.push(format!("{}_toplevel", lib.identifier()));
+                    top.set_extension(self.extension("vhd"));
+                    write(top, header(toplevel, lib.identifier()), selected.clone())?;
+                }
+            }
+
+            if self.config().assertions() {
+                if let Some(units) = lib.declare_assertions(&clock_reset)? {
+                    let mut psl = dir.clone();
+                    psl.push(format!("{}_psl", lib.identifier()));
+                    psl.set_extension(self.extension("psl"));
+                    write(psl, units, all.clone())?;
+                }
+            }
         }
+
+        if self.manifest && manifest.write(self.force)? {
+            info!("Wrote {}.", manifest.path().display());
+        }
+
+        info!("{} file(s) changed.", changed.len());
         Ok(())
     }
 }
@@ -230,6 +1198,7 @@ impl Split for Port {
                         _ => t,
                     },
                 )
+                .with_attributes(self.attributes().iter().cloned())
             }),
             type_up.map(|t| {
                 Port::new(
@@ -240,6 +1209,7 @@ impl Split for Port {
                         _ => t,
                     },
                 )
+                .with_attributes(self.attributes().iter().cloned())
             }),
         )
     }
@@ -248,6 +1218,7 @@ impl Split for Port {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::design::Library;
     use crate::Reversed;
     use std::fs;
 
@@ -405,9 +1376,145 @@ mod test {
 
         // Check if files were correctly generated.
         assert!(fs::metadata(&path).is_ok());
-        assert!(fs::metadata(&path.join("proj")).is_ok());
-        assert!(fs::metadata(&path.join("proj/lib_pkg.gen.vhd")).is_ok());
+        assert!(fs::metadata(path.join("proj")).is_ok());
+        assert!(fs::metadata(path.join("proj/lib_pkg.gen.vhd")).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn backend_split_files() -> Result<()> {
+        let v = VHDLBackEnd::from(VHDLConfig {
+            split_files: true,
+            ..VHDLConfig::default()
+        })
+        .with_manifest(true);
+        let project = Project::from_builder(
+            crate::Name::try_new("proj")?,
+            crate::UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                crate::Name::try_new("lib")?,
+                crate::UniquelyNamedBuilder::new().with_items(vec![
+                    crate::design::streamlet::tests::streamlets::nulls_streamlet("test"),
+                ]),
+            )?]),
+        )?;
 
+        let tmpdir = tempfile::tempdir()?;
+        v.generate(&project, tmpdir.path())?;
+
+        assert!(fs::metadata(tmpdir.path().join("proj/lib_pkg.gen.vhd")).is_ok());
+        assert!(
+            fs::read_to_string(tmpdir.path().join("proj/lib/test_com.gen.vhd"))?
+                .contains("entity test_com is")
+        );
+        let manifest = fs::read_to_string(tmpdir.path().join("proj/manifest.json"))?;
+        assert!(manifest.contains("\"path\": \"lib/test_com.gen.vhd\""));
+        assert!(manifest.contains("\"streamlet\": \"lib.test\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn backend_stubs() -> Result<()> {
+        let v = VHDLBackEnd::from(VHDLConfig {
+            stubs: true,
+            ..VHDLConfig::default()
+        });
+        let project = Project::from_builder(
+            crate::Name::try_new("proj")?,
+            crate::UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                crate::Name::try_new("lib")?,
+                crate::UniquelyNamedBuilder::new().with_items(vec![
+                    crate::design::streamlet::tests::streamlets::nulls_streamlet("test"),
+                ]),
+            )?]),
+        )?;
+
+        let tmpdir = tempfile::tempdir()?;
+        let stub = tmpdir.path().join("proj/lib/test_com.vhd");
+        v.generate(&project, tmpdir.path())?;
+        assert!(fs::read_to_string(&stub)?.contains("architecture"));
+
+        // A stub that cannot be read is reported, not overwritten.
+        fs::write(&stub, [0xff, 0xfe])?;
+        assert!(v
+            .generate(&project, tmpdir.path())
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to read existing stub"));
+        assert_eq!(fs::read(&stub)?, [0xff, 0xfe]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn backend_keep_unused() -> Result<()> {
+        let project = Project::from_builder(
+            crate::Name::try_new("proj")?,
+            crate::UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                crate::Name::try_new("lib")?,
+                crate::UniquelyNamedBuilder::new().with_items(vec![
+                    crate::design::streamlet::tests::streamlets::nulls_streamlet("x"),
+                    crate::design::streamlet::tests::streamlets::nulls_streamlet("y"),
+                ]),
+            )?]),
+        )?;
+        let package = |keep_unused| -> Result<String> {
+            let tmpdir = tempfile::tempdir()?;
+            VHDLBackEnd::from(VHDLConfig {
+                toplevel: true,
+                toplevel_streamlets: vec!["y".to_string()],
+                keep_unused,
+                ..VHDLConfig::default()
+            })
+            .generate(&project, tmpdir.path())?;
+            Ok(fs::read_to_string(
+                tmpdir.path().join("proj/lib_pkg.gen.vhd"),
+            )?)
+        };
+
+        let pruned = package(false)?;
+        assert!(!pruned.contains("component x_com"));
+        assert!(pruned.contains("component y_com"));
+        assert!(package(true)?.contains("component x_com"));
+        Ok(())
+    }
+
+    #[test]
+    fn standard() -> Result<()> {
+        assert!(VHDLStandard::Vhdl93.is_reserved("Signal"));
+        assert!(!VHDLStandard::Vhdl93.is_reserved("sequence"));
+        assert!(VHDLStandard::Vhdl2008.is_reserved("sequence"));
+
+        let package = Package {
+            identifier: "lib".to_string(),
+            components: vec![Component::new(
+                "x",
+                vec![],
+                vec![Port::new("default", Mode::In, Type::Bit)],
+                None,
+            )],
+        };
+        assert!(VHDLStandard::Vhdl93.check_identifiers(&package).is_ok());
+        assert_eq!(
+            VHDLStandard::Vhdl2008.check_identifiers(&package),
+            Err(Error::BackEndError(
+                "Port default of package lib is a reserved word in VHDL-2008".to_string()
+            ))
+        );
+
+        let v = VHDLBackEnd::from(VHDLConfig {
+            standard: Some(VHDLStandard::Vhdl93),
+            assertions: true,
+            ..VHDLConfig::default()
+        });
+        let tmpdir = tempfile::tempdir()?;
+        assert!(v
+            .generate(
+                &crate::design::project::tests::proj::empty_proj(),
+                tmpdir.path()
+            )
+            .is_err());
         Ok(())
     }
 }
@@ -0,0 +1,309 @@
+//! PSL protocol assertions.
+//!
+//! Generates PSL verification units that check the physical stream invariants of the canonical
+//! components during simulation. The units are bound to the canonical component entities.
+
+use crate::design::{Library, Streamlet};
+use crate::generator::common::convert::CANON_SUFFIX;
+use crate::generator::common::{ClockReset, ResetPolarity, ResetStyle};
+use crate::physical::PhysicalStream;
+use crate::traits::Identify;
+use crate::{cat, NonNegative, Result};
+
+/// Generate trait for PSL verification units.
+pub trait DeclareAssertions {
+    /// Generate PSL verification units checking the stream protocol of self, or None if there
+    /// is nothing to check. The properties are clocked and aborted according to `clock_reset`.
+    fn declare_assertions(&self, clock_reset: &ClockReset) -> Result<Option<String>>;
+}
+
+/// Returns a VHDL bit string literal of `value` with `width` bits.
+fn bits(value: NonNegative, width: NonNegative) -> String {
+    format!("\"{:0width$b}\"", value, width = width as usize)
+}
+
+/// Returns a VHDL bit string literal with `width` bits that are all one. Unlike [bits], this
+/// works for any width.
+fn ones(width: NonNegative) -> String {
+    format!("\"{}\"", "1".repeat(width as usize))
+}
+
+/// Returns the PSL properties for a physical stream, where all signals are prefixed with
+/// `prefix`.
+fn properties(prefix: &str, stream: &PhysicalStream) -> Vec<String> {
+    let signals = stream.signal_list();
+    let c = stream.complexity();
+    let n = stream.element_lanes().get();
+    let d = stream.dimensionality();
+
+    let valid = format!("{} = '1'", cat!(prefix, "valid"));
+    let ready = format!("{} = '1'", cat!(prefix, "ready"));
+    let stall = format!("({} and {} = '0')", valid, cat!(prefix, "ready"));
+    let mut result = Vec::new();
+
+    // The downstream signals must be stable until the transfer is acknowledged.
+    let mut stable = vec![valid.clone()];
+    stable.extend(
+        signals
+            .into_iter()
+            .skip(2)
+            .map(|s| format!("stable({})", cat!(prefix, s.identifier()))),
+    );
+    result.push(format!("{} -> next ({})", stall, stable.join(" and ")));
+
+    if let Some(last) = signals.last() {
+        let last = cat!(prefix, last.identifier());
+        // valid may only be released after the innermost (C < 3) or outermost (C < 2)
+        // sequence is completed.
+        if !c.allows_stall_between_inner_sequences() {
+            result.push(format!(
+                "({} and {} and {} /= {}) -> next ({})",
+                valid,
+                ready,
+                last,
+                ones(d),
+                valid
+            ));
+        } else if !c.allows_stall_within_inner_sequences() {
+            result.push(format!(
+                "({} and {} and {} = {}) -> next ({})",
+                valid,
+                ready,
+                last,
+                bits(0, d),
+                valid
+            ));
+        }
+        // last bits cannot be postponed.
+        if !c.allows_postponed_last() {
+            for j in 1..d {
+                result.push(format!(
+                    "({} and {}({}) = '1') -> {}({}) = '1'",
+                    valid,
+                    last,
+                    j,
+                    last,
+                    j - 1
+                ));
+            }
+        }
+    }
+
+    let in_range = |signal: &str| {
+        format!(
+            "{} -> to_integer(unsigned({})) < {}",
+            valid,
+            cat!(prefix, signal),
+            n
+        )
+    };
+
+    if let Some(stai) = signals.stai() {
+        let stai = stai.identifier().to_string();
+        if !n.is_power_of_two() {
+            result.push(in_range(&stai));
+        }
+        if let Some(endi) = signals.endi() {
+            result.push(format!(
+                "{} -> unsigned({}) >= unsigned({})",
+                valid,
+                cat!(prefix, endi.identifier()),
+                cat!(prefix, stai)
+            ));
+        }
+    }
+
+    if let Some(endi) = signals.endi() {
+        let width = stream.endi_bit_count();
+        if !n.is_power_of_two() {
+            result.push(in_range(endi.identifier()));
+        }
+        // All lanes must be used for all but the last transfer of the innermost sequence.
+        if !c.allows_partial_transfers() {
+            if let Some(last) = signals.last() {
+                result.push(format!(
+                    "({} and {} = {}) -> {} = {}",
+                    valid,
+                    cat!(prefix, last.identifier()),
+                    bits(0, d),
+                    cat!(prefix, endi.identifier()),
+                    bits(n - 1, width)
+                ));
+            }
+        }
+    }
+
+    // strb only indicates empty transfers below C = 8.
+    if let Some(strb) = signals.strb() {
+        if c.requires_contiguous_lanes() && n > 1 {
+            let strb = cat!(prefix, strb.identifier());
+            result.push(format!(
+                "{} -> ({} = {} or {} = {})",
+                valid,
+                strb,
+                bits(0, n),
+                strb,
+                ones(n)
+            ));
+        }
+    }
+
+    result
+}
+
+impl DeclareAssertions for Streamlet {
+    fn declare_assertions(&self, clock_reset: &ClockReset) -> Result<Option<String>> {
+        let entity = cat!(self.identifier().to_string(), CANON_SUFFIX.unwrap_or(""));
+        let abort = match clock_reset.style() {
+            ResetStyle::Sync => "sync_abort",
+            ResetStyle::Async => "async_abort",
+        };
+        let level = match clock_reset.polarity() {
+            ResetPolarity::ActiveHigh => '1',
+            ResetPolarity::ActiveLow => '0',
+        };
+        let mut body = String::new();
+        for interface in self.interfaces() {
+            let synth = interface.typ().synthesize();
+            for (path, stream) in synth.streams() {
+                let prefix = cat!(interface.identifier(), path);
+                for (index, property) in properties(&prefix, stream).iter().enumerate() {
+                    body.push_str(
+                        format!(
+                            "  {}: assert always ({}) {} {} = '{}';\n",
+                            cat!(prefix, "protocol", index),
+                            property,
+                            abort,
+                            clock_reset.reset(),
+                            level
+                        )
+                        .as_str(),
+                    );
+                }
+            }
+        }
+
+        if body.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(format!(
+            "vunit {}({}) {{\n  default clock is rising_edge({});\n\n{}}}",
+            cat!(entity, "psl"),
+            entity,
+            clock_reset.clock(),
+            body
+        )))
+    }
+}
+
+impl DeclareAssertions for Library {
+    fn declare_assertions(&self, clock_reset: &ClockReset) -> Result<Option<String>> {
+        let units = self
+            .streamlets()
+            .iter()
+            .map(|s| s.declare_assertions(clock_reset))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if units.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(units.join("\n\n")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::{Interface, Mode};
+    use crate::logical::{Direction, LogicalType, Stream, Synchronicity};
+    use crate::{Name, PositiveReal, UniquelyNamedBuilder};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn bit_literals() {
+        assert_eq!(bits(0, 3), "\"000\"");
+        assert_eq!(bits(5, 4), "\"0101\"");
+        assert_eq!(ones(40), format!("\"{}\"", "1".repeat(40)));
+    }
+
+    #[test]
+    fn stream_properties() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 8)], 3, 2, 1, vec![])?;
+        assert_eq!(
+            properties("x", &stream),
+            vec![
+                "(x_valid = '1' and x_ready = '0') -> next (x_valid = '1' and stable(x_data) and stable(x_last) and stable(x_endi) and stable(x_strb))",
+                "(x_valid = '1' and x_ready = '1' and x_last /= \"11\") -> next (x_valid = '1')",
+                "(x_valid = '1' and x_last(1) = '1') -> x_last(0) = '1'",
+                "x_valid = '1' -> to_integer(unsigned(x_endi)) < 3",
+                "(x_valid = '1' and x_last = \"00\") -> x_endi = \"10\"",
+                "x_valid = '1' -> (x_strb = \"000\" or x_strb = \"111\")",
+            ]
+        );
+
+        let stream = PhysicalStream::try_new(vec![("a", 8)], 4, 0, 8, vec![])?;
+        assert_eq!(
+            properties("x", &stream),
+            vec![
+                "(x_valid = '1' and x_ready = '0') -> next (x_valid = '1' and stable(x_data) and stable(x_stai) and stable(x_endi) and stable(x_strb))",
+                "x_valid = '1' -> unsigned(x_endi) >= unsigned(x_stai)",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wide_stream_properties() -> Result<()> {
+        // The all-ones literals must not overflow for 32 or more lanes or dimensions.
+        let stream = PhysicalStream::try_new(vec![("a", 1)], 40, 40, 1, vec![])?;
+        let properties = properties("x", &stream);
+        let ones = "1".repeat(40);
+        assert!(properties.contains(&format!(
+            "(x_valid = '1' and x_ready = '1' and x_last /= \"{}\") -> next (x_valid = '1')",
+            ones
+        )));
+        assert!(properties.contains(&format!(
+            "x_valid = '1' -> (x_strb = \"{}\" or x_strb = \"{}\")",
+            "0".repeat(40),
+            ones
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn streamlet_vunit() -> Result<()> {
+        let stream = LogicalType::from(Stream::new(
+            LogicalType::try_new_bits(8)?,
+            PositiveReal::new(1.0)?,
+            1,
+            Synchronicity::Sync,
+            2,
+            Direction::Forward,
+            None,
+            false,
+        ));
+        let streamlet = Streamlet::from_builder(
+            Name::try_from("test")?,
+            UniquelyNamedBuilder::new().with_items(vec![
+                Interface::try_new("b", Mode::In, LogicalType::try_new_bits(1)?, None)?,
+                Interface::try_new("a", Mode::In, stream, None)?,
+            ]),
+            None,
+        )?;
+        assert_eq!(
+            streamlet.declare_assertions(&ClockReset::default())?.unwrap(),
+            "vunit test_com_psl(test_com) {
+  default clock is rising_edge(clk);
+
+  a_protocol_0: assert always ((a_valid = '1' and a_ready = '0') -> next (a_valid = '1' and stable(a_data) and stable(a_last) and stable(a_strb))) sync_abort rst = '1';
+  a_protocol_1: assert always ((a_valid = '1' and a_ready = '1' and a_last = \"0\") -> next (a_valid = '1')) sync_abort rst = '1';
+}"
+        );
+        Ok(())
+    }
+}
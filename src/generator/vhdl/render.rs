@@ -0,0 +1,171 @@
+//! Snippet rendering.
+//!
+//! Renders fragments of VHDL from the common representation, such as a component declaration or
+//! a port map, for users that generate the surrounding code with their own templates. Ports with
+//! reversed fields are split into a downstream and an upstream part, like in all generated
+//! declarations.
+//!
+//! Every function takes an indentation level, the number of two-space indents that every
+//! non-empty line of the snippet is prefixed with. Snippets do not end with a newline.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::generator::common::{Component, Mode, Port, Type};
+//! use tydi::generator::vhdl::render;
+//!
+//! let component = Component::new(
+//!     "x",
+//!     vec![],
+//!     vec![Port::new("a", Mode::In, Type::bitvec(8))],
+//!     None,
+//! );
+//! assert_eq!(
+//!     render::port_map("x_inst", &component, |port| format!("x_{}", port), 1)?,
+//!     "  x_inst : x\n    port map(\n      a => x_a\n    );"
+//! );
+//! # Ok::<(), tydi::Error>(())
+//! ```
+
+use crate::generator::common::{Component, Port, Type};
+use crate::generator::vhdl::{Declare, DeclareType, Split, VHDLIdentifier};
+use crate::traits::Identify;
+use crate::{Document, Result};
+
+/// Returns `snippet` with every non-empty line prefixed with `level` two-space indents.
+pub fn indent(snippet: &str, level: usize) -> String {
+    let prefix = "  ".repeat(level);
+    snippet
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the ports that `port` is declared as: the port itself, or its downstream and upstream
+/// parts if it has reversed fields.
+fn split(port: &Port) -> Vec<Port> {
+    if port.has_reversed() {
+        let (dn, up) = port.split();
+        dn.into_iter().chain(up).collect()
+    } else {
+        vec![port.clone()]
+    }
+}
+
+/// Returns a comment with the documentation of `port`, if it has any, followed by a newline.
+fn doc_comment(port: &Port) -> String {
+    port.doc()
+        .map(|doc| doc.lines().map(|line| format!("--{}\n", line)).collect())
+        .unwrap_or_default()
+}
+
+/// Renders the declaration of a component.
+pub fn component(component: &Component, level: usize) -> Result<String> {
+    Ok(indent(component.declare()?.as_str(), level))
+}
+
+/// Renders the declarations of the record types of `typ`, including the record types of its
+/// fields. Returns an empty string if `typ` is not a record.
+pub fn record_types(typ: &Type, level: usize) -> Result<String> {
+    match typ {
+        Type::Record(_) => Ok(indent(typ.declare(true)?.as_str(), level)),
+        _ => Ok(String::new()),
+    }
+}
+
+/// Renders the interface declarations of a port, as in the port clause of an entity or
+/// component, separated by semicolons.
+pub fn ports(port: &Port, level: usize) -> Result<String> {
+    let mut declarations = vec![];
+    for (index, p) in split(port).iter().enumerate() {
+        declarations.push(format!(
+            "{}{} : {} {}",
+            if index == 0 {
+                doc_comment(port)
+            } else {
+                String::new()
+            },
+            p.identifier(),
+            p.mode().vhdl_identifier()?,
+            p.typ().vhdl_identifier()?
+        ));
+    }
+    Ok(indent(declarations.join(";\n").as_str(), level))
+}
+
+/// Renders the declarations of signals with the identifiers and types of the declarations of a
+/// port, e.g. to connect the port of an instance to.
+pub fn signals(port: &Port, level: usize) -> Result<String> {
+    let mut declarations = vec![];
+    for p in split(port) {
+        declarations.push(format!(
+            "signal {} : {};",
+            p.identifier(),
+            p.typ().vhdl_identifier()?
+        ));
+    }
+    Ok(indent(declarations.join("\n").as_str(), level))
+}
+
+/// Renders an instance of a component with label `instance`, of which every port is mapped to
+/// the actual that `actual` returns for the identifier of the port.
+pub fn port_map(
+    instance: &str,
+    component: &Component,
+    actual: impl Fn(&str) -> String,
+    level: usize,
+) -> Result<String> {
+    let associations = component
+        .ports()
+        .iter()
+        .flat_map(split)
+        .map(|p| format!("    {} => {}", p.identifier(), actual(p.identifier())))
+        .collect::<Vec<_>>();
+    let mut result = format!("{} : {}", instance, component.identifier());
+    if !associations.is_empty() {
+        result.push_str(format!("\n  port map(\n{}\n  )", associations.join(",\n")).as_str());
+    }
+    result.push(';');
+    Ok(indent(result.as_str(), level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::test::{records, test_comp};
+    use crate::generator::common::Mode;
+
+    #[test]
+    fn snippets() -> Result<()> {
+        assert_eq!(indent("a\n\n  b", 2), "    a\n\n      b");
+        let port = Port::new_documented("b", Mode::Out, records::rec_rev("b"), Some(" B.".into()));
+        assert_eq!(
+            ports(&port, 1)?,
+            "  -- B.\n  b_dn : out b_dn_type;\n  b_up : in b_up_type"
+        );
+        assert_eq!(
+            signals(&port, 0)?,
+            "signal b_dn : b_dn_type;\nsignal b_up : b_up_type;"
+        );
+        assert_eq!(record_types(&Type::Bit, 0)?, "");
+        assert!(component(&test_comp(), 1)?.starts_with("  component test_comp\n    port(\n"));
+        assert_eq!(
+            port_map("i", &test_comp(), |p| p.to_string(), 0)?,
+            "i : test_comp
+  port map(
+    a_dn => a_dn,
+    a_up => a_up,
+    b_dn => b_dn,
+    b_up => b_up
+  );"
+        );
+        Ok(())
+    }
+}
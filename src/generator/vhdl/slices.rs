@@ -0,0 +1,291 @@
+//! Field slicing helpers.
+//!
+//! Generates a VHDL package with the bit indices of every element field in the flattened `data`
+//! signal of the canonical components, along with functions that slice a field out of the `data`
+//! vector for a given lane, such that hand-written code does not have to derive offsets by hand.
+//!
+//! For streams with multiple element lanes, it also generates functions that derive which lanes
+//! carry an element from the `stai`, `endi` and `strb` signals, and how many lanes do. These
+//! drive the `lane_valid` and `lane_count` fields of the fancy stream records.
+
+use crate::design::{Library, Streamlet};
+use crate::generator::mangle::Mangler;
+use crate::physical::PhysicalStream;
+use crate::traits::Identify;
+use crate::{cat, Result};
+
+/// Generate trait for field slicing helpers.
+pub trait DeclareFieldSlices {
+    /// Generate a package with the field slicing constants and functions and the lane validity
+    /// functions of self, or None if there is nothing to declare.
+    fn declare_field_slices(&self) -> Result<Option<String>>;
+}
+
+/// A list of declarations for a package and a list of bodies for its package body.
+type Slices = (Vec<String>, Vec<String>);
+
+/// Returns the slicing declarations and bodies for the element fields of a physical stream, where
+/// all identifiers are prefixed with `prefix`. Streams of which the element consists of a single
+/// field without a name need no slicing, and result in nothing.
+fn stream_slices(prefix: &str, stream: &PhysicalStream) -> Result<Slices> {
+    let fields = stream.element_fields();
+    if fields.keys().all(|path| path.is_empty()) {
+        return Ok((vec![], vec![]));
+    }
+
+    let ranges = fields.bit_ranges()?;
+    let width = cat!(prefix, "element_width");
+    let mut decls = vec![format!(
+        "  constant {} : natural := {};",
+        width,
+        ranges.last().map(|(_, range)| range.end).unwrap_or(0)
+    )];
+    let mut bodies = vec![];
+
+    for (path, range) in ranges {
+        let field = cat!(prefix, path.join("_"));
+        let (high, low) = (cat!(field, "high"), cat!(field, "low"));
+        let signature = format!(
+            "function {}(data : std_logic_vector; lane : natural := 0) return std_logic_vector",
+            cat!("f_get", field)
+        );
+        // Fields are at least one bit wide, so the range is never empty.
+        decls.push(format!(
+            "  constant {} : natural := {};",
+            high,
+            range.end - 1
+        ));
+        decls.push(format!("  constant {} : natural := {};", low, range.start));
+        decls.push(format!("  {};", signature));
+        bodies.push(format!(
+            "  {} is
+  begin
+    return data(data'low + lane * {w} + {} downto data'low + lane * {w} + {});
+  end function;",
+            signature,
+            high,
+            low,
+            w = width
+        ));
+    }
+
+    Ok((decls, bodies))
+}
+
+/// Returns the declarations and bodies of the lane validity functions of a physical stream, where
+/// all identifiers are prefixed with `prefix`. Streams with a single lane, or without `stai`,
+/// `endi` and `strb` signals, always use all lanes, and result in nothing.
+fn lane_helpers(prefix: &str, stream: &PhysicalStream) -> Slices {
+    let n = stream.element_lanes().get();
+    let signals = stream.signal_list();
+    let present = [signals.stai(), signals.endi(), signals.strb()]
+        .iter()
+        .flatten()
+        .map(|s| s.identifier().to_string())
+        .collect::<Vec<_>>();
+    if n == 1 || present.is_empty() {
+        return (vec![], vec![]);
+    }
+
+    let params = present
+        .iter()
+        .map(|s| format!("{} : std_logic_vector", s))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let args = present.join(", ");
+    let valid = cat!("f", prefix, "lane_valid");
+    let count = cat!("f", prefix, "lane_count");
+    let valid_signature = format!("function {}({}) return std_logic_vector", valid, params);
+    let count_signature = format!("function {}({}) return natural", count, params);
+
+    let mut conditions = Vec::new();
+    if signals.strb().is_some() {
+        conditions.push("strb(strb'low + i) = '1'".to_string());
+    }
+    if signals.stai().is_some() {
+        conditions.push("i >= to_integer(unsigned(stai))".to_string());
+    }
+    if signals.endi().is_some() {
+        conditions.push("i <= to_integer(unsigned(endi))".to_string());
+    }
+
+    (
+        vec![
+            format!("  {};", valid_signature),
+            format!("  {};", count_signature),
+        ],
+        vec![
+            format!(
+                "  {} is
+    variable result : std_logic_vector({} downto 0);
+  begin
+    for i in 0 to {} loop
+      if {} then
+        result(i) := '1';
+      else
+        result(i) := '0';
+      end if;
+    end loop;
+    return result;
+  end function;",
+                valid_signature,
+                n - 1,
+                n - 1,
+                conditions.join(" and ")
+            ),
+            format!(
+                "  {} is
+    constant lanes : std_logic_vector({} downto 0) := {}({});
+    variable result : natural := 0;
+  begin
+    for i in lanes'range loop
+      if lanes(i) = '1' then
+        result := result + 1;
+      end if;
+    end loop;
+    return result;
+  end function;",
+                count_signature,
+                n - 1,
+                valid,
+                args
+            ),
+        ],
+    )
+}
+
+/// Returns the slicing declarations and bodies of all physical streams of a streamlet. The
+/// prefixes of the streams are derived with `mangler`, such that they are unique within the
+/// package.
+fn streamlet_slices(streamlet: &Streamlet, mangler: &mut Mangler) -> Result<Slices> {
+    let mut result: Slices = (vec![], vec![]);
+    for interface in streamlet.interfaces() {
+        for (path, stream) in interface.typ().synthesize().streams() {
+            let prefix = mangler.mangle(&[
+                streamlet.identifier().to_string(),
+                interface.identifier().to_string(),
+                path.to_string(),
+            ])?;
+            for (decls, bodies) in [
+                stream_slices(&prefix, stream)?,
+                lane_helpers(&prefix, stream),
+            ] {
+                result.0.extend(decls);
+                result.1.extend(bodies);
+            }
+        }
+    }
+    Ok(result)
+}
+
+impl DeclareFieldSlices for Library {
+    fn declare_field_slices(&self) -> Result<Option<String>> {
+        let mut decls = vec![];
+        let mut bodies = vec![];
+        let mut mangler = Mangler::new();
+        for streamlet in self.streamlets() {
+            let (d, b) = streamlet_slices(&streamlet, &mut mangler)?;
+            decls.extend(d);
+            bodies.extend(b);
+        }
+
+        if decls.is_empty() {
+            return Ok(None);
+        }
+
+        let package = cat!(self.identifier(), "fields");
+        Ok(Some(format!(
+            "library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+package {p} is
+
+{}
+
+end {p};
+
+package body {p} is
+
+{}
+
+end {p};
+",
+            decls.join("\n"),
+            bodies.join("\n\n"),
+            p = package
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 8), ("b", 2)], 2, 0, 1, vec![])?;
+        let (decls, bodies) = stream_slices("x", &stream)?;
+        assert_eq!(
+            decls,
+            vec![
+                "  constant x_element_width : natural := 10;",
+                "  constant x_a_high : natural := 7;",
+                "  constant x_a_low : natural := 0;",
+                "  function f_get_x_a(data : std_logic_vector; lane : natural := 0) return std_logic_vector;",
+                "  constant x_b_high : natural := 9;",
+                "  constant x_b_low : natural := 8;",
+                "  function f_get_x_b(data : std_logic_vector; lane : natural := 0) return std_logic_vector;",
+            ]
+        );
+        assert_eq!(
+            bodies[1],
+            "  function f_get_x_b(data : std_logic_vector; lane : natural := 0) return std_logic_vector is
+  begin
+    return data(data'low + lane * x_element_width + x_b_high downto data'low + lane * x_element_width + x_b_low);
+  end function;"
+        );
+
+        let stream = PhysicalStream::new(
+            crate::logical::LogicalType::try_new_bits(8)?.fields(),
+            crate::Positive::new(2).unwrap(),
+            0,
+            1,
+            crate::physical::Fields::new_empty(),
+        );
+        assert_eq!(stream_slices("x", &stream)?, (vec![], vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn lanes() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 8)], 4, 0, 1, vec![])?;
+        assert_eq!(lane_helpers("x", &stream), (vec![], vec![]));
+
+        let stream = PhysicalStream::try_new(vec![("a", 8)], 4, 0, 6, vec![])?;
+        let (decls, bodies) = lane_helpers("x", &stream);
+        assert_eq!(
+            decls,
+            vec![
+                "  function f_x_lane_valid(stai : std_logic_vector; endi : std_logic_vector) return std_logic_vector;",
+                "  function f_x_lane_count(stai : std_logic_vector; endi : std_logic_vector) return natural;",
+            ]
+        );
+        assert_eq!(
+            bodies[0],
+            "  function f_x_lane_valid(stai : std_logic_vector; endi : std_logic_vector) return std_logic_vector is
+    variable result : std_logic_vector(3 downto 0);
+  begin
+    for i in 0 to 3 loop
+      if i >= to_integer(unsigned(stai)) and i <= to_integer(unsigned(endi)) then
+        result(i) := '1';
+      else
+        result(i) := '0';
+      end if;
+    end loop;
+    return result;
+  end function;"
+        );
+        Ok(())
+    }
+}
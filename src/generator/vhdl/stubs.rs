@@ -0,0 +1,83 @@
+//! Architecture stubs.
+//!
+//! Generates a design file for every component with an architecture in which the user
+//! implements the component. Stubs are owned by the user: the code between the
+//! `-- BEGIN USER` and `-- END USER` markers is preserved when they are generated again, using
+//! [`regions::preserve`].
+//!
+//! [`regions::preserve`]: ../../regions/fn.preserve.html
+
+use crate::generator::common::Component;
+use crate::generator::vhdl::context::use_clauses;
+use crate::generator::vhdl::DeclareEntity;
+use crate::traits::Identify;
+use crate::Result;
+
+/// The comment token that starts user region markers in VHDL.
+pub const COMMENT: &str = "--";
+
+/// Generate trait for architecture stubs.
+pub trait DeclareStub {
+    /// Generate a design file with an architecture stub for self, with user regions for the
+    /// declarations and the body of the architecture. If `entity` is set, the file also declares
+    /// the entity, using the types declared in the package with the given identifier.
+    fn declare_stub(&self, package: &str, entity: bool) -> Result<String>;
+}
+
+impl DeclareStub for Component {
+    fn declare_stub(&self, package: &str, entity: bool) -> Result<String> {
+        let mut result = String::new();
+        if entity {
+            result.push_str(self.declare_entity(package)?.as_str());
+            result.push_str("\n\n");
+        } else {
+            result.push_str(use_clauses(package).as_str());
+        }
+        result.push_str(
+            format!(
+                "architecture behavioral of {} is
+  -- BEGIN USER declarations
+  -- END USER declarations
+begin
+  -- BEGIN USER body
+  -- END USER body
+end behavioral;
+",
+                self.identifier()
+            )
+            .as_str(),
+        );
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::common::test::test_comp;
+
+    #[test]
+    fn stub() -> Result<()> {
+        assert_eq!(
+            test_comp().declare_stub("test", false)?,
+            "library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.test.all;
+
+architecture behavioral of test_comp is
+  -- BEGIN USER declarations
+  -- END USER declarations
+begin
+  -- BEGIN USER body
+  -- END USER body
+end behavioral;
+"
+        );
+        assert!(test_comp()
+            .declare_stub("test", true)?
+            .contains("end test_comp;\n\narchitecture behavioral of test_comp is\n"));
+        Ok(())
+    }
+}
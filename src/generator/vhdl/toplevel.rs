@@ -0,0 +1,785 @@
+//! Top-level wrapper generation.
+//!
+//! Generates a VHDL design file with a single entity that instantiates the canonical components
+//! of a selection of the streamlets of a library, and exposes all of their ports as ports of the
+//! top-level entity, such that projects targeting a board or FPGA can use the generated library
+//! through one entity. The clock and reset are shared by all instances.
+//!
+//! To reduce the number of top-level ports, the physical streams of several interfaces can be
+//! time-multiplexed onto a single [bus](ToplevelBus). A bus has a `valid` and `ready` handshake,
+//! an `id` that selects the member that is transferring, and a `data` signal that carries the
+//! other downstream signals of the member, packed starting at the least significant bit. Buses
+//! of interfaces that flow into the instances are demultiplexed by `id`. Buses of interfaces
+//! that flow out of the instances are multiplexed by a round-robin arbiter, which moves to the
+//! next member after every transfer, or when the current member has nothing to transfer.
+
+use crate::design::{Library, Streamlet};
+use crate::generator::common::convert::{Componentify, CANON_SUFFIX};
+use crate::generator::common::{
+    ClockReset, Component, IdentifierStyle, Mode, Port, ResetPolarity, ResetStyle, Type,
+};
+use crate::generator::mangle::Mangler;
+use crate::generator::vhdl::{DeclareEntity, DeclareType};
+use crate::traits::{Document, Identify};
+use crate::util::log2_ceil;
+use crate::{cat, Error, NonNegative, Positive, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A bus that time-multiplexes the physical streams of interfaces onto one set of top-level
+/// ports.
+///
+/// Buses are parsed from `<name>=<streamlet>.<interface>,<streamlet>.<interface>...`. Every
+/// member interface must consist of a single physical stream, and the streams of all members
+/// must flow in the same direction.
+///
+/// # Example
+///
+/// ```
+/// use tydi::generator::vhdl::toplevel::ToplevelBus;
+///
+/// let bus: ToplevelBus = "ctrl=x.a,y.b".parse()?;
+/// assert_eq!(bus.name(), "ctrl");
+/// assert_eq!(bus.members(), &[("x".to_string(), "a".to_string()), ("y".to_string(), "b".to_string())]);
+/// # Ok::<(), tydi::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToplevelBus {
+    /// The name of the bus, which prefixes its ports.
+    name: String,
+    /// The identifiers of the streamlets and interfaces of the members.
+    members: Vec<(String, String)>,
+}
+
+impl ToplevelBus {
+    /// Returns the name of the bus.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the identifiers of the streamlets and interfaces of the members of the bus.
+    pub fn members(&self) -> &[(String, String)] {
+        self.members.as_slice()
+    }
+
+    /// Returns the number of bits of the `id` signal of the bus.
+    fn id_width(&self) -> NonNegative {
+        log2_ceil(Positive::new(self.members.len() as NonNegative).unwrap())
+    }
+}
+
+impl FromStr for ToplevelBus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::ConfigError(format!(
+                "invalid bus \"{}\", expected <name>=<streamlet>.<interface>,...",
+                s
+            ))
+        };
+        let (name, members) = s.split_once('=').ok_or_else(invalid)?;
+        let members = members
+            .split(',')
+            .map(|member| {
+                member
+                    .trim()
+                    .split_once('.')
+                    .map(|(streamlet, interface)| (streamlet.to_string(), interface.to_string()))
+                    .ok_or_else(invalid)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if name.trim().is_empty() {
+            return Err(invalid());
+        }
+        if members.len() < 2 {
+            return Err(Error::ConfigError(format!(
+                "bus {} needs at least two members",
+                name.trim()
+            )));
+        }
+        Ok(ToplevelBus {
+            name: name.trim().to_string(),
+            members,
+        })
+    }
+}
+
+impl fmt::Display for ToplevelBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            self.name,
+            self.members
+                .iter()
+                .map(|(streamlet, interface)| format!("{}.{}", streamlet, interface))
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+/// The internal signals of the physical stream of a bus member.
+struct Member {
+    /// Whether the stream flows into the instance.
+    input: bool,
+    /// The identifier of the valid signal.
+    valid: String,
+    /// The identifier of the ready signal.
+    ready: String,
+    /// The identifiers, types and widths of the other downstream signals.
+    payload: Vec<(String, Type, NonNegative)>,
+}
+
+impl Member {
+    /// Returns the number of bits of the packed downstream signals.
+    fn width(&self) -> NonNegative {
+        self.payload.iter().map(|(_, _, width)| width).sum()
+    }
+}
+
+/// Generate trait for top-level wrappers.
+pub trait DeclareToplevel {
+    /// Generate a design file with a top-level entity and architecture that instantiates the
+    /// streamlets of self with the given identifiers, or all streamlets if there are none.
+    /// If `prefix` is set, the top-level ports of every instance are prefixed with the
+    /// identifier of its streamlet. The shared clock and reset are named after `clock_reset`,
+    /// and the other ports of the components are converted to `style`. The physical streams of
+    /// the members of `buses` are time-multiplexed onto the ports of their buses.
+    /// Returns None if there is nothing to instantiate.
+    fn declare_toplevel(
+        &self,
+        streamlets: &[String],
+        prefix: bool,
+        clock_reset: &ClockReset,
+        style: IdentifierStyle,
+        buses: &[ToplevelBus],
+    ) -> Result<Option<String>>;
+}
+
+/// Returns the instantiation of the canonical component of a streamlet, along with the
+/// top-level ports its ports are mapped to, and the internal signals that the ports in
+/// `internal` are mapped to instead, by port identifier. Prefixed port and signal names are
+/// derived with `mangler`.
+fn instantiate(
+    streamlet: &Streamlet,
+    prefix: bool,
+    clock_reset: &ClockReset,
+    style: IdentifierStyle,
+    internal: &[String],
+    mangler: &mut Mangler,
+) -> Result<(String, Vec<Port>, HashMap<String, Port>)> {
+    let component = streamlet
+        .canonical(CANON_SUFFIX)
+        .with_clock_reset(clock_reset.clone())?
+        .with_identifier_style(style)?;
+    let mut ports = vec![];
+    let mut signals = HashMap::new();
+    let mut map = vec![];
+    for port in component.ports() {
+        let shared = clock_reset.is_clock_or_reset(port);
+        let internal = internal.iter().any(|p| p == port.identifier());
+        let outer = if shared || !(prefix || internal) {
+            port.identifier().to_string()
+        } else {
+            mangler.mangle(&[streamlet.identifier(), port.identifier()])?
+        };
+        map.push(format!("      {} => {}", port.identifier(), outer));
+        let outer = Port::new_documented(outer, port.mode(), port.typ(), port.doc());
+        if internal {
+            signals.insert(port.identifier().to_string(), outer);
+        } else if !shared {
+            ports.push(outer);
+        }
+    }
+    Ok((
+        format!(
+            "  {}_inst : {}\n    port map(\n{}\n    );",
+            streamlet.identifier(),
+            component.identifier(),
+            map.join(",\n")
+        ),
+        ports,
+        signals,
+    ))
+}
+
+/// Returns the identifiers of the canonical ports of the physical stream of an interface that
+/// is a member of `bus`, in signal list order. Returns an error if there is no such interface,
+/// or if it does not consist of a single physical stream.
+fn member_ports(
+    bus: &ToplevelBus,
+    streamlet: &Streamlet,
+    interface: &str,
+    style: IdentifierStyle,
+) -> Result<Vec<(String, NonNegative)>> {
+    let interface = streamlet
+        .interfaces()
+        .find(|i| i.identifier() == interface)
+        .ok_or_else(|| {
+            Error::BackEndError(format!(
+                "Bus {} refers to unknown interface {}.{}",
+                bus.name(),
+                streamlet.identifier(),
+                interface
+            ))
+        })?;
+    let synth = interface.typ().synthesize();
+    let mut streams = synth.streams();
+    let result = match (synth.signals().next(), streams.next(), streams.next()) {
+        (None, Some((path, stream)), None) if path.is_empty() => Ok(stream
+            .signal_list()
+            .into_iter()
+            .map(|signal| {
+                (
+                    style.convert(&cat!(interface.identifier(), signal.identifier())),
+                    signal.bit_count(),
+                )
+            })
+            .collect()),
+        _ => Err(Error::BackEndError(format!(
+            "Bus {} member {}.{} must consist of a single physical stream",
+            bus.name(),
+            streamlet.identifier(),
+            interface.identifier()
+        ))),
+    };
+    result
+}
+
+/// Returns a VHDL bit string literal of `value` with `width` bits.
+fn bits(value: usize, width: NonNegative) -> String {
+    format!("\"{:0width$b}\"", value, width = width as usize)
+}
+
+/// Returns a conditional signal assignment to `target` of the first value of `cases` of which
+/// the condition holds, or `default` otherwise.
+fn select(target: &str, cases: &[(String, String)], default: &str) -> String {
+    let mut result = format!("  {} <=\n", target);
+    for (condition, value) in cases {
+        result.push_str(format!("    {} when {} else\n", value, condition).as_str());
+    }
+    result.push_str(format!("    {};", default).as_str());
+    result
+}
+
+/// Returns the internal signal declarations and the statements of the top-level architecture
+/// that connect the members of a bus to its top-level ports, which are named by `port`.
+fn connect_bus(
+    bus: &ToplevelBus,
+    members: &[Member],
+    port: impl Fn(&str) -> String,
+    clock_reset: &ClockReset,
+) -> (Vec<String>, String) {
+    let id_width = bus.id_width();
+    let width = members.iter().map(Member::width).max().unwrap_or(0);
+    let mut declarations = vec![];
+    let mut statements = vec![];
+
+    if members[0].input {
+        let selected = |index: usize| format!("{} = {}", port("id"), bits(index, id_width));
+        for (index, member) in members.iter().enumerate() {
+            statements.push(format!(
+                "  {} <= {} when {} else '0';",
+                member.valid,
+                port("valid"),
+                selected(index)
+            ));
+            let mut offset = 0;
+            for (signal, typ, signal_width) in &member.payload {
+                let slice = match typ {
+                    Type::Bit => format!("{}", offset),
+                    _ => format!("{} downto {}", offset + signal_width - 1, offset),
+                };
+                statements.push(format!("  {} <= {}({});", signal, port("data"), slice));
+                offset += signal_width;
+            }
+        }
+        statements.push(select(
+            &port("ready"),
+            &members
+                .iter()
+                .enumerate()
+                .map(|(index, member)| (selected(index), member.ready.clone()))
+                .collect::<Vec<_>>(),
+            "'0'",
+        ));
+        return (declarations, statements.join("\n"));
+    }
+
+    let sel = port("sel");
+    declarations.push(format!(
+        "  signal {} : natural range 0 to {};",
+        sel,
+        members.len() - 1
+    ));
+    let selected = |index: usize| format!("{} = {}", sel, index);
+    let cases = |f: &dyn Fn(usize, &Member) -> String| {
+        members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| (selected(index), f(index, member)))
+            .collect::<Vec<_>>()
+    };
+    statements.push(select(
+        &port("valid"),
+        &cases(&|_, member| member.valid.clone()),
+        "'0'",
+    ));
+    statements.push(select(
+        &port("id"),
+        &cases(&|index, _| bits(index, id_width)),
+        "(others => '0')",
+    ));
+    if width > 0 {
+        statements.push(select(
+            &port("data"),
+            &cases(&|_, member| {
+                let padding = width - member.width();
+                let mut parts = vec![];
+                if padding > 0 {
+                    parts.push(bits(0, padding));
+                }
+                parts.extend(
+                    member
+                        .payload
+                        .iter()
+                        .rev()
+                        .map(|(signal, _, _)| signal.clone()),
+                );
+                parts.join(" & ")
+            }),
+            "(others => '0')",
+        ));
+    }
+    for (index, member) in members.iter().enumerate() {
+        statements.push(format!(
+            "  {} <= {} when {} else '0';",
+            member.ready,
+            port("ready"),
+            selected(index)
+        ));
+    }
+
+    // Move to the next member after a transfer, or if the current member has nothing to
+    // transfer, such that the bus signals are stable while a transfer is pending.
+    let reset = format!(
+        "{} = '{}'",
+        clock_reset.reset(),
+        match clock_reset.polarity() {
+            ResetPolarity::ActiveHigh => '1',
+            ResetPolarity::ActiveLow => '0',
+        }
+    );
+    let mut case = format!("      case {} is\n", sel);
+    for (index, member) in members.iter().enumerate() {
+        case.push_str(
+            format!(
+                "        when {} =>\n          if {} = '0' or {} = '1' then\n            {} <= {};\n          end if;\n",
+                if index + 1 == members.len() {
+                    "others".to_string()
+                } else {
+                    index.to_string()
+                },
+                member.valid,
+                port("ready"),
+                sel,
+                (index + 1) % members.len()
+            )
+            .as_str(),
+        );
+    }
+    case.push_str("      end case;\n");
+    let clock = clock_reset.clock();
+    statements.push(match clock_reset.style() {
+        ResetStyle::Sync => format!(
+            "\n  {}_arbiter : process ({}) is\n  begin\n    if rising_edge({}) then\n{}      if {} then\n        {} <= 0;\n      end if;\n    end if;\n  end process;",
+            bus.name(),
+            clock,
+            clock,
+            case,
+            reset,
+            sel
+        ),
+        ResetStyle::Async => format!(
+            "\n  {}_arbiter : process ({}, {}) is\n  begin\n    if {} then\n      {} <= 0;\n    elsif rising_edge({}) then\n{}    end if;\n  end process;",
+            bus.name(),
+            clock,
+            clock_reset.reset(),
+            reset,
+            sel,
+            clock,
+            case
+        ),
+    });
+    (declarations, statements.join("\n"))
+}
+
+impl DeclareToplevel for Library {
+    fn declare_toplevel(
+        &self,
+        streamlets: &[String],
+        prefix: bool,
+        clock_reset: &ClockReset,
+        style: IdentifierStyle,
+        buses: &[ToplevelBus],
+    ) -> Result<Option<String>> {
+        let selected = self
+            .streamlets()
+            .into_iter()
+            .filter(|s| streamlets.is_empty() || streamlets.iter().any(|n| n == s.identifier()))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Ok(None);
+        }
+
+        // The ports of the bus members, by bus and member.
+        let mut bused: Vec<Vec<Vec<(String, NonNegative)>>> = vec![];
+        for bus in buses {
+            let mut members = vec![];
+            for (streamlet, interface) in bus.members() {
+                if buses
+                    .iter()
+                    .flat_map(|b| b.members())
+                    .filter(|m| m.0 == *streamlet && m.1 == *interface)
+                    .count()
+                    > 1
+                {
+                    return Err(Error::BackEndError(format!(
+                        "Interface {}.{} is a bus member more than once",
+                        streamlet, interface
+                    )));
+                }
+                let streamlet = selected
+                    .iter()
+                    .find(|s| s.identifier() == streamlet)
+                    .ok_or_else(|| {
+                        Error::BackEndError(format!(
+                            "Bus {} refers to streamlet {}, which is not instantiated",
+                            bus.name(),
+                            streamlet
+                        ))
+                    })?;
+                members.push(member_ports(bus, streamlet, interface, style)?);
+            }
+            bused.push(members);
+        }
+
+        let mut ports = clock_reset.ports();
+        let mut instances = vec![];
+        let mut signals = vec![];
+        let mut mangler = Mangler::new();
+        for port in &ports {
+            mangler.reserve(port.identifier())?;
+        }
+        for streamlet in &selected {
+            let internal = buses
+                .iter()
+                .zip(bused.iter())
+                .flat_map(|(bus, members)| bus.members().iter().zip(members.iter()))
+                .filter(|((s, _), _)| s == streamlet.identifier())
+                .flat_map(|(_, member)| member.iter().map(|(port, _)| port.clone()))
+                .collect::<Vec<_>>();
+            let (instance, instance_ports, instance_signals) = instantiate(
+                streamlet,
+                prefix,
+                clock_reset,
+                style,
+                &internal,
+                &mut mangler,
+            )?;
+            for port in instance_ports {
+                if ports.iter().any(|p| p.identifier() == port.identifier()) {
+                    return Err(Error::BackEndError(format!(
+                        "Top-level port name conflict: {}",
+                        port.identifier()
+                    )));
+                }
+                ports.push(port);
+            }
+            instances.push(instance);
+            signals.push(instance_signals);
+        }
+
+        let mut declarations = vec![];
+        let mut statements = vec![];
+        for (bus, members) in buses.iter().zip(bused.iter()) {
+            let members = bus
+                .members()
+                .iter()
+                .zip(members.iter())
+                .map(|((streamlet, _), member)| {
+                    let index = selected
+                        .iter()
+                        .position(|s| s.identifier() == streamlet)
+                        .unwrap();
+                    let signal = |port: &str| signals[index][port].clone();
+                    let valid = signal(member[0].0.as_str());
+                    Member {
+                        input: valid.mode() == Mode::In,
+                        valid: valid.identifier().to_string(),
+                        ready: signal(member[1].0.as_str()).identifier().to_string(),
+                        payload: member[2..]
+                            .iter()
+                            .map(|(port, width)| {
+                                let signal = signal(port.as_str());
+                                (signal.identifier().to_string(), signal.typ(), *width)
+                            })
+                            .collect(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            if members.iter().any(|m| m.input != members[0].input) {
+                return Err(Error::BackEndError(format!(
+                    "The streams of the members of bus {} flow in different directions",
+                    bus.name()
+                )));
+            }
+
+            let mut port = |name: &str| mangler.mangle(&[bus.name(), name]);
+            let names = ["valid", "ready", "id", "data", "sel"]
+                .iter()
+                .map(|name| Ok((*name, port(name)?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            let name = |name: &str| names[name].clone();
+            let width = members.iter().map(Member::width).max().unwrap_or(0);
+            let (input, output) = if members[0].input {
+                (Mode::In, Mode::Out)
+            } else {
+                (Mode::Out, Mode::In)
+            };
+            let mut bus_ports = vec![
+                Port::new(name("valid"), input, Type::Bit),
+                Port::new(name("ready"), output, Type::Bit),
+                Port::new(name("id"), input, Type::bitvec(bus.id_width())),
+            ];
+            if width > 0 {
+                bus_ports.push(Port::new(name("data"), input, Type::bitvec(width)));
+            }
+            for port in bus_ports {
+                if ports.iter().any(|p| p.identifier() == port.identifier()) {
+                    return Err(Error::BackEndError(format!(
+                        "Top-level port name conflict: {}",
+                        port.identifier()
+                    )));
+                }
+                ports.push(port);
+            }
+
+            for member in &members {
+                for signal in [&member.valid, &member.ready] {
+                    declarations.push(format!("  signal {} : std_logic;", signal));
+                }
+                for (signal, typ, _) in &member.payload {
+                    declarations.push(format!("  signal {} : {};", signal, typ.declare(true)?));
+                }
+            }
+            let (bus_declarations, bus_statements) = connect_bus(bus, &members, name, clock_reset);
+            declarations.extend(bus_declarations);
+            statements.push(bus_statements);
+        }
+
+        let toplevel = cat!(self.identifier(), "toplevel");
+        let entity = Component::new(toplevel.clone(), vec![], ports, None);
+
+        let mut body = instances.join("\n\n");
+        if !statements.is_empty() {
+            body.push_str("\n\n");
+            body.push_str(statements.join("\n\n").as_str());
+        }
+        Ok(Some(format!(
+            "{}
+
+architecture structural of {t} is
+{}begin
+
+{}
+
+end structural;
+",
+            entity.declare_entity(self.identifier())?,
+            declarations
+                .iter()
+                .map(|d| format!("{}\n", d))
+                .collect::<String>(),
+            body,
+            t = toplevel
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Name, UniquelyNamedBuilder};
+
+    fn library() -> Result<Library> {
+        Library::from_builder(
+            Name::try_new("lib")?,
+            UniquelyNamedBuilder::new().with_items(vec![
+                crate::parser::nom::streamlet("Streamlet x (a : in Bits<8>)")
+                    .unwrap()
+                    .1,
+                crate::parser::nom::streamlet("Streamlet y (a : out Bits<2>)")
+                    .unwrap()
+                    .1,
+            ]),
+        )
+    }
+
+    #[test]
+    fn toplevel() -> Result<()> {
+        let lib = library()?;
+        assert_eq!(
+            lib.declare_toplevel(
+                &["y".to_string()],
+                false,
+                &ClockReset::default(),
+                IdentifierStyle::default(),
+                &[]
+            )?
+            .unwrap(),
+            "library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.lib.all;
+
+entity lib_toplevel is
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a : out std_logic_vector(1 downto 0)
+  );
+end lib_toplevel;
+
+architecture structural of lib_toplevel is
+begin
+
+  y_inst : y_com
+    port map(
+      clk => clk,
+      rst => rst,
+      a => a
+    );
+
+end structural;
+"
+        );
+        assert!(lib
+            .declare_toplevel(
+                &[],
+                true,
+                &ClockReset::default(),
+                IdentifierStyle::default(),
+                &[]
+            )?
+            .unwrap()
+            .contains("    y_a : out std_logic_vector(1 downto 0)\n"));
+        assert_eq!(
+            lib.declare_toplevel(
+                &[],
+                false,
+                &ClockReset::default(),
+                IdentifierStyle::default(),
+                &[]
+            ),
+            Err(Error::BackEndError(
+                "Top-level port name conflict: a".to_string()
+            ))
+        );
+        assert_eq!(
+            lib.declare_toplevel(
+                &["z".to_string()],
+                false,
+                &ClockReset::default(),
+                IdentifierStyle::default(),
+                &[]
+            )?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn buses() -> Result<()> {
+        let lib = Library::from_builder(
+            Name::try_new("lib")?,
+            UniquelyNamedBuilder::new().with_items(vec![
+                crate::parser::nom::streamlet(
+                    "Streamlet x (a : in Stream<Bits<4>, d=1>, b : out Stream<Bits<2>>)",
+                )
+                .unwrap()
+                .1,
+                crate::parser::nom::streamlet(
+                    "Streamlet y (a : in Stream<Bits<2>>, b : out Stream<Bits<3>>)",
+                )
+                .unwrap()
+                .1,
+            ]),
+        )?;
+        let toplevel = |buses: &[&str]| {
+            lib.declare_toplevel(
+                &[],
+                true,
+                &ClockReset::default(),
+                IdentifierStyle::default(),
+                &buses
+                    .iter()
+                    .map(|b| b.parse())
+                    .collect::<Result<Vec<ToplevelBus>>>()?,
+            )
+        };
+        let vhdl = toplevel(&["ctrl=x.a,y.a"])?.unwrap();
+        assert!(vhdl.contains("    ctrl_data : in std_logic_vector(5 downto 0)\n"));
+        assert!(vhdl.contains("    ctrl_id : in std_logic_vector(0 downto 0);\n"));
+        assert!(!vhdl.contains("    x_a_valid : in std_logic"));
+        assert!(vhdl.contains("  signal x_a_data : std_logic_vector(3 downto 0);\n"));
+        assert!(vhdl.contains("  x_a_last <= ctrl_data(4 downto 4);\n"));
+        assert!(vhdl.contains("  y_a_data <= ctrl_data(1 downto 0);\n"));
+        assert!(vhdl.contains(
+            "  ctrl_ready <=
+    x_a_ready when ctrl_id = \"0\" else
+    y_a_ready when ctrl_id = \"1\" else
+    '0';"
+        ));
+
+        let vhdl = toplevel(&["out=x.b,y.b"])?.unwrap();
+        assert!(vhdl.contains("  signal out_sel : natural range 0 to 1;\n"));
+        assert!(vhdl.contains(
+            "  out_data <=
+    \"0\" & x_b_data when out_sel = 0 else
+    y_b_data when out_sel = 1 else
+    (others => '0');"
+        ));
+        assert!(vhdl.contains("  y_b_ready <= out_ready when out_sel = 1 else '0';\n"));
+        assert!(vhdl.contains(
+            "        when others =>
+          if y_b_valid = '0' or out_ready = '1' then
+            out_sel <= 0;
+          end if;"
+        ));
+
+        assert_eq!(
+            toplevel(&["out=x.b,x.b"]),
+            Err(Error::BackEndError(
+                "Interface x.b is a bus member more than once".to_string()
+            ))
+        );
+        assert_eq!(
+            toplevel(&["mixed=x.a,x.b"]),
+            Err(Error::BackEndError(
+                "The streams of the members of bus mixed flow in different directions".to_string()
+            ))
+        );
+        assert_eq!(
+            "single=x.a".parse::<ToplevelBus>(),
+            Err(Error::ConfigError(
+                "bus single needs at least two members".to_string()
+            ))
+        );
+        Ok(())
+    }
+}
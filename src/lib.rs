@@ -15,16 +15,29 @@
 //! - [`physical`]: for physical stream types as described in the Tydi specification.
 //! - [`logical`]: for logical stream types as described in the Tydi specification.
 //! - [`design`]: for constructs that are not (yet) described in the Tydi specification,
-//!               such as streamlets.
+//!   such as streamlets.
+//! - [`experimental`]: for enabling experimental features.
 //!
 //! ## Features
 //!
 //! The `tydi` crate supports the following (non-default) features:
 //!
+//! - [`arbitrary`] module to generate random types for property-based testing.
 //! - [`cli`] command-line-interface generator tool.
+//! - [`firrtl`] back-end of the [`generator`] module that generates FIRRTL circuits.
 //! - [`generator`] module for generation of HDL templates.
+//! - [`golden`] module of the [`generator`] module with golden-file test utilities for back-ends.
 //! - [`parser`] module with parser for Streamlet Definition Files.
 //!
+//! All other modules, as well as the `arbitrary`, `firrtl`, `generator`, `golden` and `parser`
+//! features, depend on the `std` feature, which is enabled by default. Without it, the crate is
+//! `no_std` and only requires `alloc`, such that the [`physical`] and [`logical`] type
+//! computations can be used on constrained targets:
+//!
+//! ```toml
+//! tydi = { version = "0.0.7", default-features = false }
+//! ```
+//!
 //! # Tools
 //!
 //! ## `tydi` command-line-interface
@@ -65,19 +78,34 @@
 //! [`logical`]: ./logical/index.html
 //! [`generator`]: ./generator/index.html
 //! [`design`]: ./design/index.html
+//! [`experimental`]: ./experimental/index.html
 //! [`cli`]: ./design/index.html
 //! [`parser`]: ./parser/index.html
+//! [`firrtl`]: ./generator/firrtl/index.html
+//! [`golden`]: ./generator/golden/index.html
 //! [`tydi` command-line-interface]: #tydi-command-line-interface
 
 #![doc(html_favicon_url = "https://abs-tudelft.github.io/tydi/tydi_logo.svg")]
 #![doc(html_logo_url = "https://abs-tudelft.github.io/tydi/tydi_logo.svg")]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 
 // Crate utils
 pub(crate) mod util;
 
 // Core
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "std")]
 pub mod design;
 mod error;
+#[cfg(feature = "std")]
+pub mod experimental;
 pub mod logical;
 pub mod physical;
 mod traits;
@@ -90,14 +118,18 @@ pub mod parser;
 
 // Root re-exports
 // TODO(mb): discuss
-pub use error::{Error, Result};
+#[cfg(feature = "std")]
+pub use error::IoError;
+pub use error::{Context, Error, Result};
 pub use traits::{Document, Identify, Reverse, Reversed};
-pub use util::{Logger, UniquelyNamedBuilder};
+#[cfg(feature = "std")]
+pub use util::Logger;
+pub use util::UniquelyNamedBuilder;
 
 // Types for positive and non-negative integers.
 
 /// Positive integer.
-pub type Positive = std::num::NonZeroU32;
+pub type Positive = core::num::NonZeroU32;
 /// Non-negative integer.
 pub type NonNegative = u32;
 /// Positive real.
@@ -119,7 +151,7 @@ where
     }
 }
 
-use std::ops::Mul;
+use core::ops::Mul;
 impl<T> Mul for NonZeroReal<T>
 where
     T: Copy + Mul<Output = T> + Into<f64>,
@@ -200,7 +232,7 @@ impl From<&Name> for String {
     }
 }
 
-use std::ops::Deref;
+use core::ops::Deref;
 impl Deref for Name {
     type Target = str;
     fn deref(&self) -> &str {
@@ -208,7 +240,7 @@ impl Deref for Name {
     }
 }
 
-use std::convert::TryFrom;
+use core::convert::TryFrom;
 impl TryFrom<&str> for Name {
     type Error = Error;
     fn try_from(str: &str) -> Result<Self> {
@@ -223,7 +255,7 @@ impl TryFrom<String> for Name {
     }
 }
 
-use std::str::FromStr;
+use core::str::FromStr;
 impl FromStr for Name {
     type Err = Error;
     fn from_str(str: &str) -> Result<Self> {
@@ -243,7 +275,7 @@ impl PartialEq<str> for Name {
     }
 }
 
-use std::fmt;
+use core::fmt;
 impl fmt::Display for Name {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -256,9 +288,10 @@ impl fmt::Display for Name {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PathName(Vec<Name>);
 
-use std::convert::TryInto;
+use core::convert::TryInto;
 impl PathName {
-    pub(crate) fn new_empty() -> Self {
+    /// Returns an empty path name (∅).
+    pub fn new_empty() -> Self {
         PathName(Vec::new())
     }
 
@@ -281,23 +314,37 @@ impl PathName {
         self.0.is_empty()
     }
 
+    /// Appends a name to the end of this path. Equivalent to [`push_back`].
+    ///
+    /// [`push_back`]: #method.push_back
     pub fn push(&mut self, name: impl Into<Name>) {
+        self.push_back(name)
+    }
+
+    /// Appends a name to the end of this path.
+    pub fn push_back(&mut self, name: impl Into<Name>) {
         self.0.push(name.into())
     }
 
-    pub(crate) fn with_parents(&self, path: impl Into<PathName>) -> PathName {
+    /// Prepends a name to the start of this path.
+    pub fn push_front(&mut self, name: impl Into<Name>) {
+        self.0.insert(0, name.into())
+    }
+
+    /// Returns this path, prefixed with `path`.
+    pub fn with_parents(&self, path: impl Into<PathName>) -> PathName {
         let parent = path.into();
         let mut result: Vec<Name> = Vec::with_capacity(self.len() + parent.len());
-        result.extend(parent.0.into_iter());
-        result.extend(self.0.clone().into_iter());
+        result.extend(parent.0);
+        result.extend(self.0.clone());
         PathName::new(result.into_iter())
     }
 
-    pub(crate) fn with_parent(&self, name: impl Into<Name>) -> PathName {
-        let mut result: Vec<Name> = Vec::with_capacity(self.len() + 1);
-        result.push(name.into());
-        result.extend(self.0.clone().into_iter());
-        PathName::new(result.into_iter())
+    /// Returns this path, prefixed with `name`.
+    pub fn with_parent(&self, name: impl Into<Name>) -> PathName {
+        let mut result = self.clone();
+        result.push_front(name);
+        result
     }
 
     pub fn len(&self) -> usize {
@@ -315,22 +362,66 @@ impl PathName {
             Some(PathName(self.0[..self.len() - 1].to_vec()))
         }
     }
+
+    /// Returns true if the names of `prefix` are the first names of this path. Every path starts
+    /// with the empty path.
+    pub fn starts_with(&self, prefix: &PathName) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Returns this path with `prefix` removed from its start, or None if it does not start with
+    /// `prefix`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::PathName;
+    ///
+    /// let path = PathName::try_new(vec!["a", "b", "c"])?;
+    /// assert_eq!(
+    ///     path.strip_prefix(&PathName::try_new(vec!["a"])?),
+    ///     Some(PathName::try_new(vec!["b", "c"])?)
+    /// );
+    /// assert_eq!(path.strip_prefix(&PathName::try_new(vec!["b"])?), None);
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    pub fn strip_prefix(&self, prefix: &PathName) -> Option<PathName> {
+        if self.starts_with(prefix) {
+            Some(PathName(self.0[prefix.len()..].to_vec()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the names of this path joined with `sep`, e.g. `a.b` for a separator of `.`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::{Name, PathName};
+    ///
+    /// let mut path: PathName = vec!["b", "c"].into_iter().collect();
+    /// path.push_front(Name::try_new("a")?);
+    /// path.extend(vec![Name::try_new("d")?]);
+    /// assert_eq!(path.join("."), "a.b.c.d");
+    /// assert_eq!(path.to_string(), "a__b__c__d");
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    pub fn join(&self, sep: &str) -> String {
+        let mut result = String::new();
+        for (index, name) in self.0.iter().enumerate() {
+            if index > 0 {
+                result.push_str(sep);
+            }
+            result.push_str(name);
+        }
+        result
+    }
 }
 
 impl fmt::Display for PathName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut result = String::new();
-        let mut names = self.0.iter().map(|x| x.as_ref());
-        if let Some(x) = names.next() {
-            result.push_str(x);
-            names.for_each(|name| {
-                result.push_str("__");
-                result.push_str(name);
-            });
-        } else {
-            result.push_str("");
-        }
-        write!(f, "{}", result)
+        write!(f, "{}", self.join("__"))
     }
 }
 
@@ -342,14 +433,14 @@ impl AsRef<[Name]> for PathName {
 
 impl<'a> IntoIterator for &'a PathName {
     type Item = &'a Name;
-    type IntoIter = std::slice::Iter<'a, Name>;
+    type IntoIter = core::slice::Iter<'a, Name>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
     }
 }
 
-use std::iter::FromIterator;
+use core::iter::FromIterator;
 
 impl FromIterator<Name> for PathName {
     fn from_iter<I: IntoIterator<Item = Name>>(iter: I) -> Self {
@@ -357,6 +448,28 @@ impl FromIterator<Name> for PathName {
     }
 }
 
+/// Collects names into a path.
+///
+/// # Panics
+///
+/// Panics if one of the names is not a valid [`Name`]. Use [`PathName::try_new`] for names
+/// that are not known to be valid.
+impl<'a> FromIterator<&'a str> for PathName {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        PathName(
+            iter.into_iter()
+                .map(|name| Name::try_new(name).expect("invalid name in path"))
+                .collect(),
+        )
+    }
+}
+
+impl Extend<Name> for PathName {
+    fn extend<I: IntoIterator<Item = Name>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
 impl From<Name> for PathName {
     fn from(name: Name) -> Self {
         PathName(vec![name])
@@ -6,14 +6,20 @@
 
 use crate::{
     physical::{BitCount, Complexity, Fields, PhysicalStream},
-    util::log2_ceil,
+    util::{log2_ceil, FnvHasher, IndexMap},
     Error, Name, NonNegative, PathName, Positive, PositiveReal, Result, Reverse,
 };
-use indexmap::IndexMap;
-use std::str::FromStr;
-use std::{
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::hash::Hasher;
+use core::str::FromStr;
+use core::{
     convert::{TryFrom, TryInto},
-    error,
+    error, fmt,
 };
 
 /// Direction of a stream.
@@ -21,10 +27,11 @@ use std::{
 /// [Reference]
 ///
 /// [Reference]: https://abs-tudelft.github.io/tydi/specification/logical.html#stream
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Direction {
     /// Forward indicates that the child stream flows in the same direction as
     /// its parent, complementing the data of its parent in some way.
+    #[default]
     Forward,
     /// Reverse indicates that the child stream acts as a response channel for
     /// the parent stream. If there is no parent stream, Forward indicates that
@@ -36,12 +43,6 @@ pub enum Direction {
     Reverse,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::Forward
-    }
-}
-
 impl FromStr for Direction {
     type Err = Error;
 
@@ -57,6 +58,15 @@ impl FromStr for Direction {
     }
 }
 
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Forward => write!(f, "Forward"),
+            Direction::Reverse => write!(f, "Reverse"),
+        }
+    }
+}
+
 impl Reverse for Direction {
     /// Reverse this direction.
     ///
@@ -84,11 +94,12 @@ impl Reverse for Direction {
 
 /// The synchronicity of the elements in the child stream with respect to the
 /// elements in the parent stream.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum Synchronicity {
     /// Indicating that there is a one-to-one relation between the parent and
     /// child elements, and the dimensionality information of the parent stream
     /// is redundantly carried by the child stream as well.
+    #[default]
     Sync,
     /// Indicating that there is a one-to-one relation between the parent and
     /// child elements, and the dimensionality information of the parent stream
@@ -104,12 +115,6 @@ pub enum Synchronicity {
     FlatDesync,
 }
 
-impl Default for Synchronicity {
-    fn default() -> Self {
-        Synchronicity::Sync
-    }
-}
-
 impl FromStr for Synchronicity {
     type Err = Error;
 
@@ -127,6 +132,17 @@ impl FromStr for Synchronicity {
     }
 }
 
+impl fmt::Display for Synchronicity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Synchronicity::Sync => write!(f, "Sync"),
+            Synchronicity::Flatten => write!(f, "Flatten"),
+            Synchronicity::Desync => write!(f, "Desync"),
+            Synchronicity::FlatDesync => write!(f, "FlatDesync"),
+        }
+    }
+}
+
 /// The stream-manipulating logical stream type.
 ///
 /// Defines a new physical stream.
@@ -220,6 +236,68 @@ impl Stream {
         }
     }
 
+    /// Returns a new Stream, checking that the combination of properties is
+    /// legal according to the specification. Returns an error when the user
+    /// type contains stream-manipulating nodes, or when the stream has
+    /// dimensionality but no signals of its own, and all its child streams
+    /// are Flatten or FlatDesync, such that its last information is not
+    /// carried by any physical stream.
+    ///
+    /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#stream)
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new(
+        data: LogicalType,
+        throughput: PositiveReal,
+        dimensionality: NonNegative,
+        synchronicity: Synchronicity,
+        complexity: impl Into<Complexity>,
+        direction: Direction,
+        user: Option<LogicalType>,
+        keep: bool,
+    ) -> Result<Self> {
+        if let Some(user) = &user {
+            if user.has_streams() {
+                return Err(Error::InvalidArgument(format!(
+                    "user type of a stream must consist of element-manipulating nodes only, found {}",
+                    user
+                )));
+            }
+        }
+        let stream = Stream::new(
+            data,
+            throughput,
+            dimensionality,
+            synchronicity,
+            complexity,
+            direction,
+            user,
+            keep,
+        );
+        let children = stream.data.child_streams();
+        if stream.dimensionality > 0
+            && !stream.keep
+            && stream.data.split_streams().signals.is_null()
+            && stream
+                .user
+                .as_ref()
+                .map(|user| user.is_null())
+                .unwrap_or(true)
+            && !children.is_empty()
+            && children.iter().all(|child| {
+                matches!(
+                    child.synchronicity,
+                    Synchronicity::Flatten | Synchronicity::FlatDesync
+                )
+            })
+        {
+            return Err(Error::InvalidArgument(format!(
+                "dimensionality of a stream without signals of its own must be carried by a child stream that is not Flatten or FlatDesync, found {}",
+                stream
+            )));
+        }
+        Ok(stream)
+    }
+
     pub fn new_basic(data: LogicalType) -> Self {
         Stream {
             data: Box::new(data),
@@ -257,15 +335,74 @@ impl Stream {
         self.throughput
     }
 
+    /// Returns the logical type of the user data carried by this stream, if
+    /// any.
+    pub fn user(&self) -> Option<&LogicalType> {
+        self.user.as_deref()
+    }
+
     /// Returns true if this stream is null i.e. it results in no signals.
     ///
     /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#null-detection-function)
     pub fn is_null(&self) -> bool {
         self.data.is_null()
-            && (self.user.is_some() && self.user.as_ref().unwrap().is_null())
+            && self
+                .user
+                .as_ref()
+                .map(|user| user.is_null())
+                .unwrap_or(true)
             && !self.keep
     }
 
+    /// Return this stream with a different throughput ratio.
+    pub fn with_throughput(mut self, throughput: PositiveReal) -> Self {
+        self.throughput = throughput;
+        self
+    }
+
+    /// Return this stream with a different dimensionality.
+    pub fn with_dimensionality(mut self, dimensionality: NonNegative) -> Self {
+        self.dimensionality = dimensionality;
+        self
+    }
+
+    /// Return this stream with a different synchronicity.
+    pub fn with_synchronicity(mut self, synchronicity: Synchronicity) -> Self {
+        self.synchronicity = synchronicity;
+        self
+    }
+
+    /// Return this stream with a different complexity.
+    pub fn with_complexity(mut self, complexity: impl Into<Complexity>) -> Self {
+        self.complexity = complexity.into();
+        self
+    }
+
+    /// Return this stream with a different direction.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Return this stream with a different user type. Returns an error when
+    /// the user type contains stream-manipulating nodes.
+    pub fn with_user(mut self, user: LogicalType) -> Result<Self> {
+        if user.has_streams() {
+            return Err(Error::InvalidArgument(format!(
+                "user type of a stream must consist of element-manipulating nodes only, found {}",
+                user
+            )));
+        }
+        self.user = Some(Box::new(user));
+        Ok(self)
+    }
+
+    /// Return this stream with a different keep flag.
+    pub fn with_keep(mut self, keep: bool) -> Self {
+        self.keep = keep;
+        self
+    }
+
     /// Set the throughput ratio of this stream.
     fn set_throughput(&mut self, throughput: PositiveReal) {
         self.throughput = throughput;
@@ -282,6 +419,56 @@ impl Stream {
     }
 }
 
+impl fmt::Display for Stream {
+    /// Display a stream in the syntax of Streamlet Definition Files. Only
+    /// properties that differ from their defaults are displayed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{Direction, LogicalType, Stream, Synchronicity};
+    /// use tydi::PositiveReal;
+    ///
+    /// let stream = Stream::new(
+    ///     LogicalType::try_new_bits(8)?,
+    ///     PositiveReal::new(2.)?,
+    ///     1,
+    ///     Synchronicity::Sync,
+    ///     7,
+    ///     Direction::Forward,
+    ///     None,
+    ///     false,
+    /// );
+    /// assert_eq!(stream.to_string(), "Stream<Bits<8>, t=2, d=1, c=7>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stream<{}", self.data)?;
+        if self.throughput.get() != 1. {
+            write!(f, ", t={}", self.throughput.get())?;
+        }
+        if self.dimensionality != 0 {
+            write!(f, ", d={}", self.dimensionality)?;
+        }
+        if self.synchronicity != Synchronicity::default() {
+            write!(f, ", s={}", self.synchronicity)?;
+        }
+        if self.complexity != Complexity::default() {
+            write!(f, ", c={}", self.complexity)?;
+        }
+        if self.direction != Direction::default() {
+            write!(f, ", r={}", self.direction)?;
+        }
+        if let Some(user) = &self.user {
+            write!(f, ", u={}", user)?;
+        }
+        if self.keep {
+            write!(f, ", x=true")?;
+        }
+        write!(f, ">")
+    }
+}
+
 impl From<Stream> for LogicalType {
     /// Wraps this stream in a [`LogicalType`].
     ///
@@ -309,7 +496,7 @@ impl Group {
             ),
         >,
     ) -> Result<Self> {
-        let mut map = IndexMap::new();
+        let mut map = IndexMap::default();
         for (name, stream) in group
             .into_iter()
             .map(
@@ -334,6 +521,26 @@ impl Group {
     }
 }
 
+/// Write the fields of a group or union, separated by commas.
+fn fmt_fields(f: &mut fmt::Formatter<'_>, fields: &IndexMap<Name, LogicalType>) -> fmt::Result {
+    let mut fields = fields.iter().peekable();
+    while let Some((name, typ)) = fields.next() {
+        write!(f, "{}: {}", name, typ)?;
+        if fields.peek().is_some() {
+            write!(f, ", ")?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Group<")?;
+        fmt_fields(f, &self.0)?;
+        write!(f, ">")
+    }
+}
+
 impl From<Group> for LogicalType {
     /// Wraps this group in a [`LogicalType`].
     ///
@@ -343,16 +550,23 @@ impl From<Group> for LogicalType {
     }
 }
 
-///
+/// The Union stream type, of which one variant is valid at a time. The valid
+/// variant is indicated by a tag field, which is named `tag` by default.
 ///
 /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#union)
 #[derive(Debug, Clone, PartialEq)]
-pub struct Union(IndexMap<Name, LogicalType>);
+pub struct Union {
+    /// The variants of the union.
+    fields: IndexMap<Name, LogicalType>,
+    /// The name of the tag field.
+    tag: Name,
+}
 
 impl Union {
-    /// Returns a new Union logical stream type. Returns an error when either
-    /// the name or logical stream type conversion fails, or when there are
-    /// duplicate names.
+    /// Returns a new Union logical stream type, with a tag field named `tag`.
+    /// Returns an error when either the name or logical stream type conversion
+    /// fails, when there are duplicate names, or when the name of a variant
+    /// collides with the tag field.
     pub fn try_new(
         union: impl IntoIterator<
             Item = (
@@ -361,7 +575,40 @@ impl Union {
             ),
         >,
     ) -> Result<Self> {
-        let mut map = IndexMap::new();
+        Union::try_new_with_tag("tag", union)
+    }
+
+    /// Returns a new Union logical stream type, with a tag field with the
+    /// given name. Returns an error when either the name or logical stream
+    /// type conversion fails, when there are duplicate names, or when the name
+    /// of a variant collides with the tag field or with the `union` field of
+    /// the union.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{LogicalType, Union};
+    ///
+    /// let variants = || vec![("tag", LogicalType::Null), ("value", LogicalType::Null)];
+    /// assert!(Union::try_new(variants()).is_err());
+    ///
+    /// let union = Union::try_new_with_tag("kind", variants())?;
+    /// assert_eq!(union.tag_name().to_string(), "kind");
+    /// assert_eq!(union.to_string(), "Union<tag: Null, value: Null, tag=kind>");
+    /// assert!(union.with_tag("value").is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn try_new_with_tag(
+        tag: impl TryInto<Name, Error = impl Into<Box<dyn error::Error>>>,
+        union: impl IntoIterator<
+            Item = (
+                impl TryInto<Name, Error = impl Into<Box<dyn error::Error>>>,
+                impl TryInto<LogicalType, Error = impl Into<Box<dyn error::Error>>>,
+            ),
+        >,
+    ) -> Result<Self> {
+        let tag = tag.try_into().map_err(Into::into)?;
+        let mut map = IndexMap::default();
         for (name, stream) in union
             .into_iter()
             .map(
@@ -377,20 +624,68 @@ impl Union {
                 .map(|_| -> Result<()> { Err(Error::UnexpectedDuplicate) })
                 .transpose()?;
         }
-        Ok(Union(map))
+        Union { fields: map, tag }.validate_tag()
+    }
+
+    /// Returns this union with its tag field named `tag`. Returns an error
+    /// when the name conversion fails, or when the name collides with the name
+    /// of a variant or with the `union` field of the union.
+    pub fn with_tag(
+        self,
+        tag: impl TryInto<Name, Error = impl Into<Box<dyn error::Error>>>,
+    ) -> Result<Self> {
+        Union {
+            tag: tag.try_into().map_err(Into::into)?,
+            ..self
+        }
+        .validate_tag()
+    }
+
+    /// Returns this union if its tag field does not collide with the other
+    /// fields of the union.
+    fn validate_tag(self) -> Result<Self> {
+        if self.fields.len() > 1 && (self.fields.contains_key(&self.tag) || self.tag == *"union") {
+            Err(Error::InvalidArgument(format!(
+                "union tag {} collides with a field of the union",
+                self.tag
+            )))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns this union with its variants replaced by `fields`, keeping the
+    /// name of the tag field.
+    fn with_fields(&self, fields: IndexMap<Name, LogicalType>) -> Self {
+        Union {
+            fields,
+            tag: self.tag.clone(),
+        }
+    }
+
+    /// Returns the name of the tag field of this union.
+    pub fn tag_name(&self) -> &Name {
+        &self.tag
     }
 
-    /// Returns the tag name and width of this union.
+    /// Returns the tag name and width of this union, or None if the union has
+    /// a single variant.
     /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html)
     pub fn tag(&self) -> Option<(String, BitCount)> {
-        if self.0.len() > 1 {
-            Some((
-                "tag".to_string(),
+        self.tag_bit_count()
+            .map(|bit_count| (self.tag.to_string(), bit_count))
+    }
+
+    /// Returns the width of the tag field of this union, or None if the union
+    /// has a single variant.
+    fn tag_bit_count(&self) -> Option<BitCount> {
+        if self.fields.len() > 1 {
+            Some(
                 BitCount::new(log2_ceil(
-                    BitCount::new(self.0.len() as NonNegative).unwrap(),
+                    BitCount::new(self.fields.len() as NonNegative).unwrap(),
                 ))
                 .unwrap(),
-            ))
+            )
         } else {
             None
         }
@@ -398,7 +693,18 @@ impl Union {
 
     /// Returns an iterator over the fields of the Union.
     pub fn iter(&self) -> impl Iterator<Item = (&Name, &LogicalType)> {
-        self.0.iter()
+        self.fields.iter()
+    }
+}
+
+impl fmt::Display for Union {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Union<")?;
+        fmt_fields(f, &self.fields)?;
+        if self.tag != *"tag" {
+            write!(f, ", tag={}", self.tag)?;
+        }
+        write!(f, ">")
     }
 }
 
@@ -455,6 +761,30 @@ pub enum LogicalType {
     Stream(Stream),
 }
 
+impl fmt::Display for LogicalType {
+    /// Display a logical stream type in the syntax of Streamlet Definition
+    /// Files, such that it can be parsed again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let group = LogicalType::try_new_group(vec![("a", 4), ("b", 8)])?;
+    /// assert_eq!(group.to_string(), "Group<a: Bits<4>, b: Bits<8>>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalType::Null => write!(f, "Null"),
+            LogicalType::Bits(width) => write!(f, "Bits<{}>", width),
+            LogicalType::Group(group) => group.fmt(f),
+            LogicalType::Union(union) => union.fmt(f),
+            LogicalType::Stream(stream) => stream.fmt(f),
+        }
+    }
+}
+
 impl TryFrom<NonNegative> for LogicalType {
     type Error = Error;
 
@@ -549,6 +879,126 @@ impl LogicalType {
         Union::try_new(union).map(Into::into)
     }
 
+    /// Returns a new Group logical stream type with the given fields.
+    /// Unlike [`try_new_group`], the names and types need no conversion, so
+    /// the only error is a duplicate name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    /// use tydi::Name;
+    ///
+    /// let names = ["a", "b", "c"];
+    /// let group = LogicalType::group_of(
+    ///     names
+    ///         .iter()
+    ///         .map(|n| Ok((Name::try_new(*n)?, LogicalType::try_new_bits(8)?)))
+    ///         .collect::<tydi::Result<Vec<_>>>()?,
+    /// )?;
+    /// assert_eq!(group.to_string(), "Group<a: Bits<8>, b: Bits<8>, c: Bits<8>>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`try_new_group`]: #method.try_new_group
+    pub fn group_of(fields: impl IntoIterator<Item = (Name, LogicalType)>) -> Result<Self> {
+        let mut map = IndexMap::default();
+        for (name, typ) in fields {
+            if map.insert(name, typ).is_some() {
+                return Err(Error::UnexpectedDuplicate);
+            }
+        }
+        Ok(LogicalType::Group(Group(map)))
+    }
+
+    /// Returns a Group with the fields of this Group followed by those of
+    /// `other`. Null acts as an empty group. Returns an error when either type
+    /// is not a Group or Null, or when both have a field with the same name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let a = LogicalType::try_new_group(vec![("a", 4)])?;
+    /// let b = LogicalType::try_new_group(vec![("b", 8)])?;
+    /// assert_eq!(a.concat(&b)?.to_string(), "Group<a: Bits<4>, b: Bits<8>>");
+    /// assert_eq!(LogicalType::Null.concat(&b)?, b);
+    /// assert!(a.concat(&a).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn concat(&self, other: &LogicalType) -> Result<LogicalType> {
+        let fields = |typ: &LogicalType| match typ {
+            LogicalType::Null => Ok(vec![]),
+            LogicalType::Group(Group(fields)) => Ok(fields
+                .iter()
+                .map(|(name, typ)| (name.clone(), typ.clone()))
+                .collect()),
+            _ => Err(Error::InvalidArgument(format!(
+                "only groups can be concatenated, found {}",
+                typ
+            ))),
+        };
+        LogicalType::group_of(fields(self)?.into_iter().chain(fields(other)?))
+    }
+
+    /// Returns a new Stream that carries this logical stream type, with
+    /// default properties. Use the `with_` methods of [`Stream`] to change
+    /// them, and convert it into a [`LogicalType`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::{Direction, LogicalType};
+    ///
+    /// let stream: LogicalType = LogicalType::try_new_bits(8)?
+    ///     .wrap_in_stream()
+    ///     .with_dimensionality(1)
+    ///     .with_direction(Direction::Reverse)
+    ///     .into();
+    /// assert_eq!(stream.to_string(), "Stream<Bits<8>, d=1, r=Reverse>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`Stream`]: ./struct.Stream.html
+    /// [`LogicalType`]: ./enum.LogicalType.html
+    pub fn wrap_in_stream(self) -> Stream {
+        Stream::new_basic(self)
+    }
+
+    /// Returns this Group or Union with the type of every field replaced by
+    /// the result of `f`, which is given the name and type of the field.
+    /// Other types are returned as is. Returns the first error of `f`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let group = LogicalType::try_new_group(vec![("a", 4), ("b", 8)])?;
+    /// let streams = group.map_fields(|_, typ| Ok(typ.clone().wrap_in_stream().into()))?;
+    /// assert_eq!(streams.to_string(), "Group<a: Stream<Bits<4>>, b: Stream<Bits<8>>>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn map_fields(
+        &self,
+        mut f: impl FnMut(&Name, &LogicalType) -> Result<LogicalType>,
+    ) -> Result<LogicalType> {
+        let mut map_fields = |fields: &IndexMap<Name, LogicalType>| {
+            fields
+                .iter()
+                .map(|(name, typ)| Ok((name.clone(), f(name, typ)?)))
+                .collect::<Result<IndexMap<_, _>>>()
+        };
+        Ok(match self {
+            LogicalType::Group(Group(fields)) => LogicalType::Group(Group(map_fields(fields)?)),
+            LogicalType::Union(union) => {
+                LogicalType::Union(union.with_fields(map_fields(&union.fields)?))
+            }
+            _ => self.clone(),
+        })
+    }
+
     /// Returns true if this logical stream consists of only element-
     /// manipulating stream types. This recursively checks all inner stream
     /// types.
@@ -566,13 +1016,55 @@ impl LogicalType {
     pub fn is_element_only(&self) -> bool {
         match self {
             LogicalType::Null | LogicalType::Bits(_) => true,
-            LogicalType::Group(Group(fields)) | LogicalType::Union(Union(fields)) => {
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union { fields, .. }) => {
                 fields.values().all(|stream| stream.is_element_only())
             }
             LogicalType::Stream(stream) => stream.data.is_element_only(),
         }
     }
 
+    /// Returns the child streams of this type that are not nested in other
+    /// streams.
+    fn child_streams(&self) -> Vec<&Stream> {
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => vec![],
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union { fields, .. }) => fields
+                .values()
+                .flat_map(|typ| typ.child_streams())
+                .collect(),
+            LogicalType::Stream(stream) => vec![stream],
+        }
+    }
+
+    /// Returns true if the field at `path` is a Union, where the path consists of the names of
+    /// the fields of Groups and Unions, like the paths of [`SynthesisOptions::unions`].
+    ///
+    /// [`SynthesisOptions::unions`]: ./struct.SynthesisOptions.html#method.unions
+    #[cfg(feature = "std")]
+    pub(crate) fn has_union_at(&self, path: &[Name]) -> bool {
+        match (self, path.split_first()) {
+            (LogicalType::Stream(stream), _) => stream.data.has_union_at(path),
+            (LogicalType::Union(_), None) => true,
+            (LogicalType::Group(Group(fields)), Some((name, rest)))
+            | (LogicalType::Union(Union { fields, .. }), Some((name, rest))) => fields
+                .get(name)
+                .map(|typ| typ.has_union_at(rest))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns true if this logical stream type contains any Stream nodes.
+    fn has_streams(&self) -> bool {
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => false,
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union { fields, .. }) => {
+                fields.values().any(|stream| stream.has_streams())
+            }
+            LogicalType::Stream(_) => true,
+        }
+    }
+
     /// Returns true if and only if this logical stream does not result in any
     /// signals.
     ///
@@ -581,7 +1073,7 @@ impl LogicalType {
         match self {
             LogicalType::Null => true,
             LogicalType::Group(Group(fields)) => fields.values().all(|stream| stream.is_null()),
-            LogicalType::Union(Union(fields)) => {
+            LogicalType::Union(Union { fields, .. }) => {
                 fields.len() == 1 && fields.values().all(|stream| stream.is_null())
             }
             LogicalType::Stream(stream) => stream.is_null(),
@@ -589,13 +1081,119 @@ impl LogicalType {
         }
     }
 
+    /// Returns a fingerprint of this logical stream type: a 64-bit hash that
+    /// is stable across runs and platforms, such that build systems can
+    /// detect changes to types. The order of fields is significant, since it
+    /// determines the layout of the signals.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let ab = LogicalType::try_new_group(vec![("a", 4), ("b", 8)])?;
+    /// let ba = LogicalType::try_new_group(vec![("b", 8), ("a", 4)])?;
+    /// assert_eq!(ab.fingerprint(), ab.clone().fingerprint());
+    /// assert_ne!(ab.fingerprint(), ba.fingerprint());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        write!(hasher, "{}", self).expect("hashing cannot fail");
+        hasher.finish()
+    }
+
+    /// Returns the normalized form of this logical stream type.
+    ///
+    /// Normalization replaces all types that do not result in any signals
+    /// with Null, removes Null fields from groups, collapses groups and
+    /// unions with a single field into the type of that field, and removes
+    /// Null user types from streams. When `sort` is true, the fields of
+    /// groups and unions are also sorted by name. Note that sorting union
+    /// fields changes the values of the union tag.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let bits = LogicalType::try_new_bits(4)?;
+    /// let group = LogicalType::try_new_group(vec![
+    ///     ("a", LogicalType::try_new_group(vec![("b", bits.clone())])?),
+    ///     ("c", LogicalType::Null),
+    /// ])?;
+    /// assert_eq!(group.normalize(false), bits);
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn normalize(&self, sort: bool) -> LogicalType {
+        let normalize_fields = |fields: &IndexMap<Name, LogicalType>, drop_null: bool| {
+            let mut fields = fields
+                .iter()
+                .map(|(name, typ)| (name.clone(), typ.normalize(sort)))
+                .filter(|(_, typ)| !(drop_null && typ.is_null()))
+                .collect::<IndexMap<_, _>>();
+            if sort {
+                fields.sort_keys();
+            }
+            fields
+        };
+
+        if self.is_null() {
+            return LogicalType::Null;
+        }
+
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => self.clone(),
+            LogicalType::Group(Group(fields)) => {
+                let mut fields = normalize_fields(fields, true);
+                if fields.len() == 1 {
+                    fields.pop().unwrap().1
+                } else {
+                    LogicalType::Group(Group(fields))
+                }
+            }
+            LogicalType::Union(union) => {
+                let mut fields = normalize_fields(&union.fields, false);
+                if fields.len() == 1 {
+                    fields.pop().unwrap().1
+                } else {
+                    LogicalType::Union(union.with_fields(fields))
+                }
+            }
+            LogicalType::Stream(stream) => LogicalType::Stream(Stream {
+                data: Box::new(stream.data.normalize(sort)),
+                user: stream
+                    .user
+                    .as_ref()
+                    .map(|user| user.normalize(sort))
+                    .filter(|user| !user.is_null())
+                    .map(Box::new),
+                ..stream.clone()
+            }),
+        }
+    }
+
+    /// Returns true if this logical stream type is equivalent to the other
+    /// logical stream type, i.e. if their normalized forms with sorted fields
+    /// are equal.
+    pub fn equivalent(&self, other: &LogicalType) -> bool {
+        self.normalize(true) == other.normalize(true)
+    }
+
     /// Splits a logical stream type into simplified stream types.
     ///
+    /// This is the reference implementation of the split function of the specification, which
+    /// back-ends use to lower logical stream types. See [`synthesize`] to lower all the way to
+    /// physical streams.
+    ///
     /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#split-function)
-    pub(crate) fn split_streams(&self) -> SplitStreams {
+    ///
+    /// [`synthesize`]: #method.synthesize
+    pub fn split_streams(&self) -> SplitStreams {
         match self {
             LogicalType::Stream(stream_in) => {
-                let mut streams = IndexMap::new();
+                let mut streams = IndexMap::default();
 
                 let split = stream_in.data.split_streams();
                 let (element, rest) = (split.signals, split.streams);
@@ -630,8 +1228,10 @@ impl LogicalType {
                         {
                             stream.set_synchronicity(Synchronicity::FlatDesync);
                         }
-                        if stream.synchronicity != Synchronicity::Flatten
-                            && stream_in.synchronicity != Synchronicity::FlatDesync
+                        if !matches!(
+                            stream.synchronicity,
+                            Synchronicity::Flatten | Synchronicity::FlatDesync
+                        ) && stream_in.synchronicity != Synchronicity::FlatDesync
                         {
                             stream.set_dimensionality(
                                 stream.dimensionality + stream_in.dimensionality,
@@ -650,9 +1250,9 @@ impl LogicalType {
             }
             LogicalType::Null | LogicalType::Bits(_) => SplitStreams {
                 signals: self.clone(),
-                streams: IndexMap::new(),
+                streams: IndexMap::default(),
             },
-            LogicalType::Group(Group(fields)) | LogicalType::Union(Union(fields)) => {
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union { fields, .. }) => {
                 let signals = fields
                     .into_iter()
                     .map(|(name, stream)| (name.clone(), stream.split_streams().signals))
@@ -661,16 +1261,15 @@ impl LogicalType {
                 SplitStreams {
                     signals: match self {
                         LogicalType::Group(_) => LogicalType::Group(Group(signals)),
-                        LogicalType::Union(_) => LogicalType::Union(Union(signals)),
+                        LogicalType::Union(union) => LogicalType::Union(union.with_fields(signals)),
                         _ => unreachable!(),
                     },
                     streams: fields
                         .into_iter()
                         .flat_map(|(name, stream)| {
                             stream.split_streams().streams.into_iter().map(
-                                move |(mut path_name, stream_)| {
-                                    path_name.push(name.clone());
-                                    (path_name, stream_)
+                                move |(path_name, stream_)| {
+                                    (path_name.with_parent(name.clone()), stream_)
                                 },
                             )
                         })
@@ -680,21 +1279,80 @@ impl LogicalType {
         }
     }
 
-    /// Flattens a logical stream type consisting of Null, Bits, Group and
-    /// Union stream types into a [`Fields`].
+    /// Returns the streams of this logical stream type that are pruned by [`split_streams`],
+    /// because they are null, in order.
     ///
-    /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#field-conversion-function)
+    /// # Examples
     ///
-    /// [`Fields`]: ./struct.Fields.html
-    pub(crate) fn fields(&self) -> Fields {
-        let mut fields = Fields::new_empty();
+    /// ```rust
+    /// use tydi::logical::PruneReason;
+    /// use tydi::PathName;
+    ///
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type(
+    ///     "Group<a: Stream<Null>, b: Stream<Stream<Bits<8>>>, c: Stream<Null, x=true>>",
+    /// )
+    /// .unwrap();
+    /// let pruned = typ.pruned_streams();
+    /// assert_eq!(pruned.len(), 2);
+    /// assert_eq!(pruned[0].path(), &PathName::try_new(vec!["a"])?);
+    /// assert_eq!(pruned[0].reason(), PruneReason::Null);
+    /// assert_eq!(pruned[1].path(), &PathName::try_new(vec!["b"])?);
+    /// assert_eq!(pruned[1].reason(), PruneReason::ChildStreams);
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    ///
+    /// [`split_streams`]: #method.split_streams
+    pub fn pruned_streams(&self) -> Vec<PrunedStream> {
         match self {
-            LogicalType::Null | LogicalType::Stream(_) => fields,
-            LogicalType::Bits(b) => {
-                fields.insert(PathName::new_empty(), *b).unwrap();
-                fields
-            }
-            LogicalType::Group(Group(inner)) => {
+            LogicalType::Null | LogicalType::Bits(_) => vec![],
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union { fields, .. }) => fields
+                .iter()
+                .flat_map(|(name, typ)| {
+                    typ.pruned_streams()
+                        .into_iter()
+                        .map(move |pruned| PrunedStream {
+                            path: pruned.path.with_parent(name.clone()),
+                            ..pruned
+                        })
+                })
+                .collect(),
+            LogicalType::Stream(stream) => {
+                let mut result = vec![];
+                // This mirrors the condition under which split_streams keeps the stream.
+                if stream.data.split_streams().signals.is_null()
+                    && stream.user.as_ref().is_none_or(|user| user.is_null())
+                    && !stream.keep
+                {
+                    result.push(PrunedStream {
+                        path: PathName::new_empty(),
+                        reason: if stream.data.is_null() {
+                            PruneReason::Null
+                        } else {
+                            PruneReason::ChildStreams
+                        },
+                    });
+                }
+                result.extend(stream.data.pruned_streams());
+                result
+            }
+        }
+    }
+
+    /// Flattens a logical stream type consisting of Null, Bits, Group and
+    /// Union stream types into a [`Fields`].
+    ///
+    /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#field-conversion-function)
+    ///
+    /// [`Fields`]: ../physical/struct.Fields.html
+    pub fn fields(&self) -> Fields {
+        let mut fields = Fields::new_empty();
+        match self {
+            LogicalType::Null | LogicalType::Stream(_) => fields,
+            LogicalType::Bits(b) => {
+                fields.insert(PathName::new_empty(), *b).unwrap();
+                fields
+            }
+            LogicalType::Group(Group(inner)) => {
                 inner.iter().for_each(|(name, stream)| {
                     stream.fields().iter().for_each(|(path_name, bit_count)| {
                         fields
@@ -704,16 +1362,11 @@ impl LogicalType {
                 });
                 fields
             }
-            LogicalType::Union(Union(inner)) => {
-                if inner.len() > 1 {
+            LogicalType::Union(union) => {
+                let inner = &union.fields;
+                if let Some(bit_count) = union.tag_bit_count() {
                     fields
-                        .insert(
-                            PathName::try_new(vec!["tag"]).unwrap(),
-                            BitCount::new(log2_ceil(
-                                BitCount::new(inner.len() as NonNegative).unwrap(),
-                            ))
-                            .unwrap(),
-                        )
+                        .insert(PathName::from(union.tag.clone()), bit_count)
                         .unwrap();
                 }
                 let b = inner.iter().fold(0, |acc, (_, stream)| {
@@ -737,33 +1390,427 @@ impl LogicalType {
         }
     }
 
-    pub(crate) fn synthesize(&self) -> LogicalStream {
-        let split = self.split_streams();
+    /// Lowers a logical stream type into the signals and physical streams it consists of.
+    ///
+    /// This is the reference lowering used by all back-ends: the type is split with
+    /// [`split_streams`], after which the fields of the remaining signals and of the element of
+    /// every stream are derived with [`fields`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::PathName;
+    ///
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type(
+    ///     "Group<a: Bits<1>, b: Stream<Bits<8>, t=2>>",
+    /// )
+    /// .unwrap();
+    /// let lowered = typ.synthesize();
+    /// assert_eq!(lowered.signal_fields().values().map(|b| b.get()).sum::<u32>(), 1);
+    ///
+    /// let b = lowered.stream(&PathName::try_new(vec!["b"])?).unwrap();
+    /// assert_eq!(b.element_lanes().get(), 2);
+    /// assert_eq!(lowered.streams().count(), 1);
+    /// # Ok::<(), tydi::Error>(())
+    /// ```
+    ///
+    /// [`split_streams`]: #method.split_streams
+    /// [`fields`]: #method.fields
+    pub fn synthesize(&self) -> LogicalStream {
+        self.synthesize_with(&SynthesisOptions::default())
+    }
+
+    /// Lowers a logical stream type like [`synthesize`], converting the throughput of every
+    /// stream to element lanes as selected by `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{Rounding, SynthesisOptions};
+    /// use tydi::PathName;
+    ///
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type("Stream<Bits<8>, t=2.5>").unwrap();
+    /// let lanes = |options| {
+    ///     typ.synthesize_with(&options)
+    ///         .stream(&PathName::new_empty())
+    ///         .unwrap()
+    ///         .element_lanes()
+    ///         .get()
+    /// };
+    /// assert_eq!(lanes(SynthesisOptions::new()), 3);
+    /// assert_eq!(lanes(SynthesisOptions::new().with_rounding(Rounding::Down)), 2);
+    /// ```
+    ///
+    /// [`synthesize`]: #method.synthesize
+    pub fn synthesize_with(&self, options: &SynthesisOptions) -> LogicalStream {
+        let split = self.restructure(options).split_streams();
         let (signals, rest) = (split.signals.fields(), split.streams);
         LogicalStream {
             signals,
             streams: rest
                 .into_iter()
                 .map(|(path_name, stream)| match stream {
-                    LogicalType::Stream(stream) => (
-                        path_name,
-                        PhysicalStream::new(
-                            stream.data.fields(),
-                            Positive::new(stream.throughput.get().ceil() as NonNegative).unwrap(),
-                            stream.dimensionality,
-                            stream.complexity,
-                            stream
-                                .user
-                                .map(|stream| stream.fields())
-                                .unwrap_or_else(Fields::new_empty),
-                        ),
-                    ),
+                    LogicalType::Stream(stream) => {
+                        let lanes = options.element_lanes(&path_name, stream.throughput);
+                        (
+                            path_name,
+                            PhysicalStream::new(
+                                stream.data.fields(),
+                                lanes,
+                                stream.dimensionality,
+                                stream.complexity,
+                                stream
+                                    .user
+                                    .map(|stream| stream.fields())
+                                    .unwrap_or_else(Fields::new_empty),
+                            ),
+                        )
+                    }
                     _ => unreachable!(),
                 })
                 .collect(),
         }
     }
 
+    /// Returns this type with the throughput of every stream changed such that [`synthesize`]
+    /// results in the element lanes selected by `options`, i.e. `resolve_throughput(options)`
+    /// followed by [`synthesize`] is equivalent to [`synthesize_with`]`(options)`. Fields are
+    /// ordered, Unions are lowered and user signals are split off as selected by `options`, see
+    /// [`sort_fields`], [`lower_unions`] and [`split_user`].
+    ///
+    /// This applies synthesis options to types that are lowered with [`synthesize`], such as
+    /// the interfaces of streamlets that are passed to back-ends.
+    ///
+    /// [`synthesize`]: #method.synthesize
+    /// [`synthesize_with`]: #method.synthesize_with
+    /// [`sort_fields`]: #method.sort_fields
+    /// [`lower_unions`]: #method.lower_unions
+    /// [`split_user`]: #method.split_user
+    pub fn resolve_throughput(&self, options: &SynthesisOptions) -> LogicalType {
+        self.restructure(options)
+            .resolve_throughput_at(options, &PathName::new_empty(), &[], &[])
+    }
+
+    /// Returns this type with the fields of all Groups and Unions in the order selected by
+    /// `options`, its Unions lowered as selected by `options`, and its user signals split off if
+    /// `options` select so.
+    fn restructure(&self, options: &SynthesisOptions) -> LogicalType {
+        let typ = match options.field_order() {
+            FieldOrder::Declaration => self.lower_unions(options),
+            FieldOrder::Name => self.sort_fields().lower_unions(options),
+        };
+        if options.split_user() {
+            typ.split_user()
+        } else {
+            typ
+        }
+    }
+
+    /// Returns this type with the user signals of every stream moved to a child stream of its
+    /// own, named `user`, instead of widening the physical stream of the parent.
+    ///
+    /// Like user signals, the child stream carries one element for every transfer of its parent:
+    /// it has a single element lane, no dimensionality, and is [`FlatDesync`] with its parent,
+    /// with the same complexity. The child stream is added as a field named `user` of the Group
+    /// of the data of the parent, such that the paths of the element fields of the parent do not
+    /// change. If the data is not a Group, or if it has a field named `user`, it is wrapped in a
+    /// Group with fields named `data` and `user` instead, which prefixes the paths of the element
+    /// fields of the parent with `data`. Parent streams are kept if only their user signals made
+    /// them non-null.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type(
+    ///     "Stream<Group<a: Bits<8>>, u=Bits<2>>",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     typ.split_user().to_string(),
+    ///     "Stream<Group<a: Bits<8>, user: Stream<Bits<2>, s=FlatDesync>>>"
+    /// );
+    /// let streams = typ.split_user().synthesize();
+    /// assert_eq!(
+    ///     streams.streams().map(|(path, _)| path.to_string()).collect::<Vec<_>>(),
+    ///     vec!["", "user"]
+    /// );
+    /// ```
+    ///
+    /// [`FlatDesync`]: ./enum.Synchronicity.html#variant.FlatDesync
+    pub fn split_user(&self) -> LogicalType {
+        self.split_user_at(&[])
+    }
+
+    /// Splits the user signals of the streams of this type, given the throughput of its parent
+    /// streams, outermost first.
+    fn split_user_at(&self, parents: &[f64]) -> LogicalType {
+        let split_fields = |fields: &IndexMap<Name, LogicalType>| -> IndexMap<Name, LogicalType> {
+            fields
+                .iter()
+                .map(|(name, typ)| (name.clone(), typ.split_user_at(parents)))
+                .collect()
+        };
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => self.clone(),
+            LogicalType::Group(Group(fields)) => LogicalType::Group(Group(split_fields(fields))),
+            LogicalType::Union(union) => {
+                LogicalType::Union(union.with_fields(split_fields(&union.fields)))
+            }
+            LogicalType::Stream(stream) => {
+                let mut result = stream.clone();
+                let parents = [parents, &[stream.throughput.get()]].concat();
+                let data = stream.data.split_user_at(&parents);
+                match stream.user.as_deref() {
+                    Some(user) if !user.is_null() => {
+                        // The child stream has an effective throughput of one element per
+                        // transfer of its parent. If rounding errors make it exceed one, aim
+                        // for the middle of the interval that rounds up to one instead.
+                        let product = parents.iter().product::<f64>();
+                        let mut throughput = 1. / product;
+                        if effective_throughput(throughput, &parents).ceil() != 1. {
+                            throughput = 0.5 / product;
+                        }
+                        let user_stream = LogicalType::from(Stream::new(
+                            user.clone(),
+                            PositiveReal::new(throughput).unwrap(),
+                            0,
+                            Synchronicity::FlatDesync,
+                            stream.complexity.clone(),
+                            Direction::Forward,
+                            None,
+                            false,
+                        ));
+                        let user_name = Name::try_new("user").unwrap();
+                        result.keep = stream.keep || data.split_streams().signals.is_null();
+                        result.user = None;
+                        result.data = Box::new(match data {
+                            LogicalType::Group(Group(mut fields))
+                                if !fields.contains_key(&user_name) =>
+                            {
+                                fields.insert(user_name, user_stream);
+                                LogicalType::Group(Group(fields))
+                            }
+                            data => {
+                                let mut fields = IndexMap::default();
+                                fields.insert(Name::try_new("data").unwrap(), data);
+                                fields.insert(user_name, user_stream);
+                                LogicalType::Group(Group(fields))
+                            }
+                        });
+                    }
+                    _ => result.data = Box::new(data),
+                }
+                LogicalType::Stream(result)
+            }
+        }
+    }
+
+    /// Returns this type with the fields of all Groups and Unions, including those of the user
+    /// types of streams, sorted by name. The order of fields determines the layout of elements
+    /// and the values of the tags of Unions, so this gives types with the same fields the same
+    /// layout regardless of the order in which the fields were declared.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type(
+    ///     "Group<b: Bits<2>, a: Union<y: Null, x: Bits<1>>>",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     typ.sort_fields().to_string(),
+    ///     "Group<a: Union<x: Bits<1>, y: Null>, b: Bits<2>>"
+    /// );
+    /// ```
+    pub fn sort_fields(&self) -> LogicalType {
+        let sort = |fields: &IndexMap<Name, LogicalType>| -> IndexMap<Name, LogicalType> {
+            let mut fields = fields
+                .iter()
+                .map(|(name, typ)| (name.clone(), typ.sort_fields()))
+                .collect::<Vec<_>>();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            fields.into_iter().collect()
+        };
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => self.clone(),
+            LogicalType::Group(Group(fields)) => LogicalType::Group(Group(sort(fields))),
+            LogicalType::Union(union) => LogicalType::Union(union.with_fields(sort(&union.fields))),
+            LogicalType::Stream(stream) => {
+                let mut result = stream.clone();
+                result.data = Box::new(stream.data.sort_fields());
+                result.user = stream
+                    .user
+                    .as_ref()
+                    .map(|user| Box::new(user.sort_fields()));
+                LogicalType::Stream(result)
+            }
+        }
+    }
+
+    /// Returns this type with the Unions that `options` select to lower with
+    /// [`UnionLowering::Streams`] rewritten, such that every variant with element content is
+    /// wrapped in a child stream of its own. The element of the stream of the Union then only
+    /// consists of the tag, instead of the tag and the widest variant. Only Unions within a
+    /// stream are rewritten.
+    ///
+    /// The child stream of a variant is synchronous with the stream of the Union, and has the
+    /// same complexity. It carries an element for each element of the stream of the Union of
+    /// which the tag selects the variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{SynthesisOptions, UnionLowering};
+    /// use tydi::PathName;
+    ///
+    /// let (_, typ) = tydi::parser::nom::logical_stream_type(
+    ///     "Stream<Union<a: Bits<8>, b: Bits<4>, c: Null>>",
+    /// )
+    /// .unwrap();
+    /// let widths = |options| {
+    ///     typ.synthesize_with(&options)
+    ///         .streams()
+    ///         .map(|(path, stream)| {
+    ///             let width = stream.element_fields().values().map(|b| b.get()).sum::<u32>();
+    ///             (path.to_string(), width)
+    ///         })
+    ///         .collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(widths(SynthesisOptions::new()), vec![("".to_string(), 10)]);
+    /// assert_eq!(
+    ///     widths(SynthesisOptions::new().with_union_lowering(UnionLowering::Streams)),
+    ///     vec![("".to_string(), 2), ("a".to_string(), 8), ("b".to_string(), 4)]
+    /// );
+    /// ```
+    ///
+    /// [`UnionLowering::Streams`]: ./enum.UnionLowering.html#variant.Streams
+    pub fn lower_unions(&self, options: &SynthesisOptions) -> LogicalType {
+        self.lower_unions_at(options, &PathName::new_empty(), None)
+    }
+
+    /// Lowers the Unions of this type at `path`, within a stream with the given complexity, if
+    /// any.
+    fn lower_unions_at(
+        &self,
+        options: &SynthesisOptions,
+        path: &PathName,
+        complexity: Option<&Complexity>,
+    ) -> LogicalType {
+        let lower_fields = |fields: &IndexMap<Name, LogicalType>| -> IndexMap<Name, LogicalType> {
+            fields
+                .iter()
+                .map(|(name, typ)| {
+                    let mut path = path.clone();
+                    path.push(name.clone());
+                    (
+                        name.clone(),
+                        typ.lower_unions_at(options, &path, complexity),
+                    )
+                })
+                .collect()
+        };
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => self.clone(),
+            LogicalType::Group(Group(fields)) => LogicalType::Group(Group(lower_fields(fields))),
+            LogicalType::Union(union) => {
+                let fields = lower_fields(&union.fields);
+                let fields = match complexity {
+                    Some(complexity)
+                        if options.union_lowering_at(path) == UnionLowering::Streams =>
+                    {
+                        fields
+                            .into_iter()
+                            .map(|(name, typ)| {
+                                if typ.split_streams().signals.is_null() {
+                                    (name, typ)
+                                } else {
+                                    let variant = Stream::new(
+                                        typ,
+                                        PositiveReal::new(1.).unwrap(),
+                                        0,
+                                        Synchronicity::Sync,
+                                        complexity.clone(),
+                                        Direction::Forward,
+                                        None,
+                                        false,
+                                    );
+                                    (name, variant.into())
+                                }
+                            })
+                            .collect()
+                    }
+                    _ => fields,
+                };
+                LogicalType::Union(union.with_fields(fields))
+            }
+            LogicalType::Stream(stream) => {
+                let mut result = stream.clone();
+                result.data = Box::new(stream.data.lower_unions_at(
+                    options,
+                    path,
+                    Some(&stream.complexity),
+                ));
+                LogicalType::Stream(result)
+            }
+        }
+    }
+
+    /// Resolves the throughput of the streams of this type, at `path`, given the throughput of
+    /// its parent streams, outermost first, before and after resolving them.
+    fn resolve_throughput_at(
+        &self,
+        options: &SynthesisOptions,
+        path: &PathName,
+        original: &[f64],
+        resolved: &[f64],
+    ) -> LogicalType {
+        let effective = effective_throughput;
+        let resolve_fields = |fields: &IndexMap<Name, LogicalType>| {
+            fields
+                .iter()
+                .map(|(name, typ)| {
+                    let mut path = path.clone();
+                    path.push(name.clone());
+                    (
+                        name.clone(),
+                        typ.resolve_throughput_at(options, &path, original, resolved),
+                    )
+                })
+                .collect()
+        };
+        match self {
+            LogicalType::Null | LogicalType::Bits(_) => self.clone(),
+            LogicalType::Group(Group(fields)) => LogicalType::Group(Group(resolve_fields(fields))),
+            LogicalType::Union(union) => {
+                LogicalType::Union(union.with_fields(resolve_fields(&union.fields)))
+            }
+            LogicalType::Stream(stream) => {
+                let throughput = effective(stream.throughput.get(), original);
+                let lanes = options
+                    .element_lanes(path, PositiveReal::new(throughput).unwrap())
+                    .get() as f64;
+                let parents = resolved.iter().product::<f64>();
+                let mut own = lanes / parents;
+                // If rounding errors make the effective throughput miss the number of lanes,
+                // aim for the middle of the interval that rounds up to it instead.
+                if effective(own, resolved).ceil() != lanes {
+                    own = (lanes - 0.5) / parents;
+                }
+
+                let original = [original, &[stream.throughput.get()]].concat();
+                let resolved = [resolved, &[own]].concat();
+                let mut result = stream.clone();
+                result.set_throughput(PositiveReal::new(own).unwrap());
+                result.data = Box::new(
+                    stream
+                        .data
+                        .resolve_throughput_at(options, path, &original, &resolved),
+                );
+                LogicalType::Stream(result)
+            }
+        }
+    }
+
     pub fn compatible(&self, other: &LogicalType) -> bool {
         self == other
             || match other {
@@ -776,24 +1823,24 @@ impl LogicalType {
                 _ => false,
             }
             || match self {
-                LogicalType::Group(Group(source)) | LogicalType::Union(Union(source)) => {
-                    match other {
-                        LogicalType::Group(Group(sink)) | LogicalType::Union(Union(sink)) => {
-                            source.len() == sink.len()
-                                && source.iter().zip(sink.iter()).all(
-                                    |((name, stream), (name_, stream_))| {
-                                        name == name_ && stream.compatible(stream_)
-                                    },
-                                )
-                        }
-                        _ => false,
+                LogicalType::Group(Group(source))
+                | LogicalType::Union(Union { fields: source, .. }) => match other {
+                    LogicalType::Group(Group(sink))
+                    | LogicalType::Union(Union { fields: sink, .. }) => {
+                        source.len() == sink.len()
+                            && source.iter().zip(sink.iter()).all(
+                                |((name, stream), (name_, stream_))| {
+                                    name == name_ && stream.compatible(stream_)
+                                },
+                            )
                     }
-                }
+                    _ => false,
+                },
                 _ => false,
             }
     }
 
-    pub fn split(&self) -> std::vec::IntoIter<LogicalSplitItem> {
+    pub fn split(&self) -> alloc::vec::IntoIter<LogicalSplitItem> {
         let split_streams = self.split_streams();
         let (signals, streams) = (split_streams.signals, split_streams.streams);
         let mut map = Vec::with_capacity(streams.len() + 1);
@@ -811,7 +1858,7 @@ impl LogicalType {
         map.into_iter()
     }
 
-    pub fn physical(&self) -> std::vec::IntoIter<PhysicalSplitItem> {
+    pub fn physical(&self) -> alloc::vec::IntoIter<PhysicalSplitItem> {
         self.split()
             .map(|item| match item {
                 LogicalSplitItem::Signals(signals) => PhysicalSplitItem::Signals(signals),
@@ -863,17 +1910,14 @@ impl ElementStream {
                     });
                     fields
                 }
-                LogicalType::Union(Union(inner)) => {
-                    if inner.len() > 1 {
+                LogicalType::Union(union) => {
+                    let inner = &union.fields;
+                    if let Some(bit_count) = union.tag_bit_count() {
                         fields
                             .insert(
-                                PathName::try_new(vec!["tag"])
-                                    .unwrap()
+                                PathName::from(union.tag.clone())
                                     .with_parents(self.path_name.clone()),
-                                BitCount::new(log2_ceil(
-                                    BitCount::new(inner.len() as NonNegative).unwrap(),
-                                ))
-                                .unwrap(),
+                                bit_count,
                             )
                             .unwrap();
                     }
@@ -978,36 +2022,411 @@ pub enum PhysicalSplitItem {
     Stream(PhysicalStream),
 }
 
+/// Strategy to round the throughput of a stream to a whole number of element lanes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round up, such that the stream is at least as fast as its throughput requires.
+    #[default]
+    Up,
+    /// Round down, trading throughput for area. Streams always have at least one lane.
+    Down,
+    /// Round to the nearest number of lanes. Streams always have at least one lane.
+    Nearest,
+}
+
+impl FromStr for Rounding {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "up" => Ok(Rounding::Up),
+            "down" => Ok(Rounding::Down),
+            "nearest" => Ok(Rounding::Nearest),
+            _ => Err(Error::InvalidArgument(format!(
+                "{} is not a valid rounding strategy, expected one of: up, down, nearest",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rounding::Up => write!(f, "up"),
+            Rounding::Down => write!(f, "down"),
+            Rounding::Nearest => write!(f, "nearest"),
+        }
+    }
+}
+
+/// Returns the effective throughput of a stream with throughput `own`, given the throughput of
+/// its parent streams, outermost first: the product of them, multiplied in the same order as
+/// [`LogicalType::split_streams`] does.
+///
+/// [`LogicalType::split_streams`]: ./enum.LogicalType.html#method.split_streams
+fn effective_throughput(own: f64, parents: &[f64]) -> f64 {
+    parents.iter().rev().fold(own, |acc, t| acc * t)
+}
+
+/// Strategy to lower the variants of a Union to physical streams.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UnionLowering {
+    /// The element of the stream of the Union consists of the tag and the widest variant, of
+    /// which all variants use the least significant bits, like the specification does.
+    #[default]
+    Overlay,
+    /// Every variant with element content gets a child stream of its own, and the element of
+    /// the stream of the Union only consists of the tag.
+    Streams,
+}
+
+impl FromStr for UnionLowering {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "overlay" => Ok(UnionLowering::Overlay),
+            "streams" => Ok(UnionLowering::Streams),
+            _ => Err(Error::InvalidArgument(format!(
+                "{} is not a valid union lowering, expected one of: overlay, streams",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for UnionLowering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnionLowering::Overlay => write!(f, "overlay"),
+            UnionLowering::Streams => write!(f, "streams"),
+        }
+    }
+}
+
+/// Order of the fields of Groups and Unions, which determines the layout of elements.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// Fields are laid out in the order in which they are declared.
+    #[default]
+    Declaration,
+    /// Fields are laid out in the order of their names, see [`LogicalType::sort_fields`].
+    ///
+    /// [`LogicalType::sort_fields`]: ./enum.LogicalType.html#method.sort_fields
+    Name,
+}
+
+impl FromStr for FieldOrder {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "declaration" => Ok(FieldOrder::Declaration),
+            "name" => Ok(FieldOrder::Name),
+            _ => Err(Error::InvalidArgument(format!(
+                "{} is not a valid field order, expected one of: declaration, name",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FieldOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldOrder::Declaration => write!(f, "declaration"),
+            FieldOrder::Name => write!(f, "name"),
+        }
+    }
+}
+
+/// Options that control how the throughput of streams is converted to element lanes, how fields
+/// are ordered and how Unions are lowered, by [`LogicalType::synthesize_with`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::logical::{Rounding, SynthesisOptions};
+/// use tydi::{PathName, Positive, PositiveReal};
+///
+/// let options = SynthesisOptions::new()
+///     .with_rounding(Rounding::Down)
+///     .with_max_lanes(Positive::new(4).unwrap())
+///     .with_lanes(PathName::try_new(vec!["b"])?, Positive::new(8).unwrap());
+///
+/// let lanes = |path: PathName, throughput| {
+///     options.element_lanes(&path, PositiveReal::new(throughput).unwrap()).get()
+/// };
+/// assert_eq!(lanes(PathName::new_empty(), 2.5), 2);
+/// assert_eq!(lanes(PathName::new_empty(), 0.5), 1);
+/// assert_eq!(lanes(PathName::new_empty(), 16.), 4);
+/// assert_eq!(lanes(PathName::try_new(vec!["b"])?, 2.), 8);
+/// # Ok::<(), tydi::Error>(())
+/// ```
+///
+/// [`LogicalType::synthesize_with`]: ./enum.LogicalType.html#method.synthesize_with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SynthesisOptions {
+    /// The rounding strategy.
+    rounding: Rounding,
+    /// The maximum number of element lanes, if any.
+    max_lanes: Option<Positive>,
+    /// The number of element lanes of specific streams, by path, regardless of their throughput.
+    lanes: IndexMap<PathName, Positive>,
+    /// The lowering of Unions without a lowering of their own.
+    union_lowering: UnionLowering,
+    /// The lowering of specific Unions, by path.
+    unions: IndexMap<PathName, UnionLowering>,
+    /// The order of the fields of Groups and Unions.
+    field_order: FieldOrder,
+    /// Whether to move the user signals of streams to child streams of their own.
+    split_user: bool,
+}
+
+impl SynthesisOptions {
+    /// Returns the default options, which round the throughput of every stream up, like the
+    /// specification does.
+    pub fn new() -> Self {
+        SynthesisOptions::default()
+    }
+
+    /// Returns these options with a different rounding strategy.
+    pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Returns these options with a maximum number of element lanes, which applies to all
+    /// streams without a number of lanes of their own.
+    pub fn with_max_lanes(mut self, max_lanes: Positive) -> Self {
+        self.max_lanes = Some(max_lanes);
+        self
+    }
+
+    /// Returns these options with the number of element lanes of the stream at `path` set to
+    /// `lanes`, regardless of its throughput.
+    pub fn with_lanes(mut self, path: PathName, lanes: Positive) -> Self {
+        self.lanes.insert(path, lanes);
+        self
+    }
+
+    /// Returns these options with a different lowering of Unions, which applies to all Unions
+    /// without a lowering of their own.
+    pub fn with_union_lowering(mut self, lowering: UnionLowering) -> Self {
+        self.union_lowering = lowering;
+        self
+    }
+
+    /// Returns these options with the lowering of the Union at `path` set to `lowering`.
+    pub fn with_union_lowering_at(mut self, path: PathName, lowering: UnionLowering) -> Self {
+        self.unions.insert(path, lowering);
+        self
+    }
+
+    /// Returns these options with a different order of the fields of Groups and Unions.
+    pub fn with_field_order(mut self, order: FieldOrder) -> Self {
+        self.field_order = order;
+        self
+    }
+
+    /// Returns these options with the user signals of streams moved to child streams of their
+    /// own, see [`LogicalType::split_user`].
+    ///
+    /// [`LogicalType::split_user`]: ./enum.LogicalType.html#method.split_user
+    pub fn with_split_user(mut self, split_user: bool) -> Self {
+        self.split_user = split_user;
+        self
+    }
+
+    /// Returns the rounding strategy.
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// Returns the maximum number of element lanes, if any.
+    pub fn max_lanes(&self) -> Option<Positive> {
+        self.max_lanes
+    }
+
+    /// Returns an iterator over the streams with a number of element lanes of their own, by path.
+    pub fn lanes(&self) -> impl Iterator<Item = (&PathName, &Positive)> {
+        self.lanes.iter()
+    }
+
+    /// Returns the lowering of Unions without a lowering of their own.
+    pub fn union_lowering(&self) -> UnionLowering {
+        self.union_lowering
+    }
+
+    /// Returns an iterator over the Unions with a lowering of their own, by path.
+    pub fn unions(&self) -> impl Iterator<Item = (&PathName, &UnionLowering)> {
+        self.unions.iter()
+    }
+
+    /// Returns the order of the fields of Groups and Unions.
+    pub fn field_order(&self) -> FieldOrder {
+        self.field_order
+    }
+
+    /// Returns true if the user signals of streams are moved to child streams of their own.
+    pub fn split_user(&self) -> bool {
+        self.split_user
+    }
+
+    /// Returns the lowering of the Union at `path`.
+    pub fn union_lowering_at(&self, path: &PathName) -> UnionLowering {
+        self.unions
+            .get(path)
+            .copied()
+            .unwrap_or(self.union_lowering)
+    }
+
+    /// Returns the options for the streams under `prefix`: the number of lanes of the streams
+    /// with a path that starts with `prefix` are kept, with the prefix removed from their path.
+    pub fn scoped(&self, prefix: &PathName) -> SynthesisOptions {
+        SynthesisOptions {
+            lanes: self
+                .lanes
+                .iter()
+                .filter_map(|(path, lanes)| path.strip_prefix(prefix).map(|path| (path, *lanes)))
+                .collect(),
+            unions: self
+                .unions
+                .iter()
+                .filter_map(|(path, lowering)| {
+                    path.strip_prefix(prefix).map(|path| (path, *lowering))
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns these options without the number of lanes of specific streams.
+    pub fn without_lanes(&self) -> SynthesisOptions {
+        SynthesisOptions {
+            lanes: IndexMap::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the number of element lanes of the stream at `path` with the given (effective)
+    /// throughput.
+    pub fn element_lanes(&self, path: &PathName, throughput: PositiveReal) -> Positive {
+        if let Some(lanes) = self.lanes.get(path) {
+            return *lanes;
+        }
+        let lanes = match self.rounding {
+            Rounding::Up => throughput.get().ceil(),
+            Rounding::Down => throughput.get().floor(),
+            Rounding::Nearest => throughput.get().round(),
+        } as NonNegative;
+        let lanes = Positive::new(lanes).unwrap_or(Positive::new(1).unwrap());
+        match self.max_lanes {
+            Some(max) => lanes.min(max),
+            None => lanes,
+        }
+    }
+}
+
+/// The reason a stream does not result in a physical stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PruneReason {
+    /// The stream carries no data and no user signals, e.g. `Stream<Null>`.
+    Null,
+    /// All data of the stream is carried by its child streams, e.g. the outer stream of
+    /// `Stream<Stream<Bits<8>>>`, and it carries no user signals.
+    ChildStreams,
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneReason::Null => write!(f, "it carries no data"),
+            PruneReason::ChildStreams => write!(f, "all of its data is carried by child streams"),
+        }
+    }
+}
+
+/// A stream of a logical stream type that does not result in a physical stream, because it is
+/// null after splitting. Such streams can be kept with the keep flag of the stream (`x=true`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrunedStream {
+    path: PathName,
+    reason: PruneReason,
+}
+
+impl PrunedStream {
+    /// Returns the path of the stream, as it would be named by [`LogicalType::split_streams`].
+    ///
+    /// [`LogicalType::split_streams`]: ./enum.LogicalType.html#method.split_streams
+    pub fn path(&self) -> &PathName {
+        &self.path
+    }
+
+    /// Returns the reason the stream was pruned.
+    pub fn reason(&self) -> PruneReason {
+        self.reason
+    }
+}
+
+/// The result of [`LogicalType::split_streams`]: the part of a logical stream type that is not
+/// carried by streams, and the simplified streams, by path.
+///
+/// [`LogicalType::split_streams`]: ./enum.LogicalType.html#method.split_streams
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct SplitStreams {
+pub struct SplitStreams {
     signals: LogicalType,
     streams: IndexMap<PathName, LogicalType>,
 }
 
 impl SplitStreams {
+    /// Returns an iterator over the simplified streams, in order, by path.
     pub fn streams(&self) -> impl Iterator<Item = (&PathName, &LogicalType)> {
         self.streams.iter()
     }
+
+    /// Returns the simplified stream at `path`, if any.
+    pub fn stream(&self, path: &PathName) -> Option<&LogicalType> {
+        self.streams.get(path)
+    }
+
+    /// Returns the part of the type that is not carried by streams.
     pub fn signal(&self) -> &LogicalType {
         &self.signals
     }
 }
 
+/// The result of [`LogicalType::synthesize`]: the signals and physical streams that a logical
+/// stream type consists of.
+///
+/// [`LogicalType::synthesize`]: ./enum.LogicalType.html#method.synthesize
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct LogicalStream {
+pub struct LogicalStream {
     signals: Fields,
     streams: IndexMap<PathName, PhysicalStream>,
 }
 
 impl LogicalStream {
-    #[allow(dead_code)]
+    /// Returns an iterator over the signals that are not part of a stream, in order, by path.
     pub fn signals(&self) -> impl Iterator<Item = (&PathName, &BitCount)> {
         self.signals.iter()
     }
 
+    /// Returns the fields of the signals that are not part of a stream.
+    pub fn signal_fields(&self) -> &Fields {
+        &self.signals
+    }
+
+    /// Returns an iterator over the physical streams, in order, by path.
     pub fn streams(&self) -> impl Iterator<Item = (&PathName, &PhysicalStream)> {
         self.streams.iter()
     }
+
+    /// Returns the physical stream at `path`, if any.
+    pub fn stream(&self, path: &PathName) -> Option<&PhysicalStream> {
+        self.streams.get(path)
+    }
 }
 
 #[cfg(test)]
@@ -1104,6 +2523,217 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn stream_legality() -> Result<()> {
+        let synchronicities = [
+            Synchronicity::Sync,
+            Synchronicity::Flatten,
+            Synchronicity::Desync,
+            Synchronicity::FlatDesync,
+        ];
+        let users = [
+            None,
+            Some(LogicalType::Null),
+            Some(elements::prim(3)),
+            Some(elements::group()),
+        ];
+        for dimensionality in 0..3 {
+            for synchronicity in synchronicities.iter() {
+                for direction in [Direction::Forward, Direction::Reverse].iter() {
+                    for user in users.iter() {
+                        for keep in [false, true].iter() {
+                            assert!(Stream::try_new(
+                                streams::group(),
+                                PositiveReal::new(1.)?,
+                                dimensionality,
+                                *synchronicity,
+                                Complexity::default(),
+                                *direction,
+                                user.clone(),
+                                *keep,
+                            )
+                            .is_ok());
+                        }
+                    }
+                }
+            }
+        }
+
+        let stream_user = Stream::try_new(
+            elements::prim(8),
+            PositiveReal::new(1.)?,
+            0,
+            Synchronicity::Sync,
+            1,
+            Direction::Forward,
+            Some(streams::prim(1)),
+            false,
+        );
+        assert_eq!(
+            stream_user.unwrap_err().to_string(),
+            "Invalid argument: user type of a stream must consist of element-manipulating nodes only, found Stream<Bits<1>>"
+        );
+
+        // The dimensionality of a stream without signals of its own is lost if all its child
+        // streams omit it.
+        let child = |synchronicity| -> Result<LogicalType> {
+            Ok(Stream::try_new(
+                elements::prim(8),
+                PositiveReal::new(1.)?,
+                0,
+                synchronicity,
+                Complexity::default(),
+                Direction::Forward,
+                None,
+                false,
+            )?
+            .into())
+        };
+        let parent = |data, dimensionality, user, keep| {
+            Stream::try_new(
+                data,
+                PositiveReal::new(1.).unwrap(),
+                dimensionality,
+                Synchronicity::Sync,
+                Complexity::default(),
+                Direction::Forward,
+                user,
+                keep,
+            )
+        };
+        for synchronicity in [Synchronicity::Flatten, Synchronicity::FlatDesync].iter() {
+            let flat = child(*synchronicity)?;
+            assert_eq!(
+                parent(flat.clone(), 1, None, false).unwrap_err().to_string(),
+                format!(
+                    "Invalid argument: dimensionality of a stream without signals of its own must be carried by a child stream that is not Flatten or FlatDesync, found Stream<Stream<Bits<8>, s={}>, d=1>",
+                    synchronicity
+                )
+            );
+            let group = LogicalType::try_new_group(vec![("a", flat.clone()), ("b", flat.clone())])?;
+            assert!(parent(group, 2, Some(LogicalType::Null), false).is_err());
+
+            // It is legal without dimensionality, if the stream is kept, if it has signals of
+            // its own, or if another child stream carries it.
+            assert!(parent(flat.clone(), 0, None, false).is_ok());
+            assert!(parent(flat.clone(), 1, None, true).is_ok());
+            assert!(parent(flat.clone(), 1, Some(elements::prim(1)), false).is_ok());
+            let group =
+                LogicalType::try_new_group(vec![("a", flat.clone()), ("b", elements::prim(1))])?;
+            assert!(parent(group, 1, None, false).is_ok());
+            let group = LogicalType::try_new_group(vec![
+                ("a", flat),
+                ("b", child(Synchronicity::Desync)?),
+            ])?;
+            assert!(parent(group, 1, None, false).is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn null_detection() -> Result<()> {
+        let with_user = |user: Option<LogicalType>, keep: bool| {
+            Stream::new(
+                LogicalType::Null,
+                PositiveReal::new(1.).unwrap(),
+                0,
+                Synchronicity::Sync,
+                Complexity::default(),
+                Direction::Forward,
+                user,
+                keep,
+            )
+        };
+        // A stream without user signals is null like one with null user signals.
+        assert!(Stream::new_basic(LogicalType::Null).is_null());
+        assert!(with_user(None, false).is_null());
+        assert!(with_user(Some(LogicalType::Null), false).is_null());
+        assert!(!with_user(Some(LogicalType::try_new_bits(1)?), false).is_null());
+        assert!(!with_user(None, true).is_null());
+        assert!(!Stream::new_basic(LogicalType::try_new_bits(1)?).is_null());
+
+        // Null streams do not result in physical streams.
+        let typ: LogicalType = with_user(None, false).into();
+        assert!(typ.is_null());
+        assert_eq!(typ.synthesize().streams().count(), 0);
+        let typ: LogicalType = with_user(None, true).into();
+        assert_eq!(typ.synthesize().streams().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn normalize() -> Result<()> {
+        let bits = LogicalType::try_new_bits(4)?;
+        let null_stream = LogicalType::from(Stream::new_basic(LogicalType::Null));
+        assert!(null_stream.is_null());
+        assert_eq!(null_stream.normalize(false), LogicalType::Null);
+
+        let ab = LogicalType::try_new_group(vec![
+            ("a", bits.clone()),
+            ("b", LogicalType::Null),
+            ("c", LogicalType::try_new_bits(2)?),
+        ])?;
+        let ba = LogicalType::try_new_group(vec![
+            (
+                "c",
+                LogicalType::try_new_group(vec![("x", elements::prim(2))])?,
+            ),
+            ("a", bits.clone()),
+            ("d", null_stream.clone()),
+        ])?;
+        assert_eq!(
+            ab.normalize(false),
+            LogicalType::try_new_group(vec![("a", bits.clone()), ("c", elements::prim(2))])?
+        );
+        match ba.normalize(true) {
+            LogicalType::Group(group) => assert_eq!(
+                group
+                    .iter()
+                    .map(|(name, _)| name.to_string())
+                    .collect::<Vec<_>>(),
+                vec!["a", "c"]
+            ),
+            _ => unreachable!(),
+        }
+        assert!(ab.equivalent(&ba));
+        assert!(!ab.equivalent(&bits));
+
+        // Null union variants are significant.
+        let union =
+            LogicalType::try_new_union(vec![("b", LogicalType::Null), ("a", bits.clone())])?;
+        assert_eq!(union.normalize(false), union);
+        assert_eq!(
+            union.normalize(true),
+            LogicalType::try_new_union(vec![("a", bits.clone()), ("b", LogicalType::Null)])?
+        );
+        assert_eq!(
+            LogicalType::try_new_union(vec![("a", bits.clone())])?.normalize(false),
+            bits
+        );
+
+        let stream = LogicalType::from(Stream::new(
+            ab.clone(),
+            PositiveReal::new(1.)?,
+            1,
+            Synchronicity::Sync,
+            1,
+            Direction::Forward,
+            Some(LogicalType::Null),
+            false,
+        ));
+        assert!(stream.equivalent(&LogicalType::from(Stream::new(
+            ba,
+            PositiveReal::new(1.)?,
+            1,
+            Synchronicity::Sync,
+            1,
+            Direction::Forward,
+            None,
+            false,
+        ))));
+        Ok(())
+    }
+
     #[test]
     fn union() -> Result<()> {
         let b = LogicalType::try_new_group(vec![("x", 2), ("y", 2)])?;
@@ -1210,7 +2840,11 @@ pub(crate) mod tests {
             None,
             false,
         );
-        let u = LogicalType::try_new_union(vec![("a", 3.try_into()?), ("b", b), ("c", c.into())])?;
+        let u = LogicalType::try_new_union(vec![
+            ("a", 3.try_into()?),
+            ("b", b.clone()),
+            ("c", c.into()),
+        ])?;
         let stream: LogicalType = Stream::new(
             u,
             PositiveReal::new(1.).unwrap(),
@@ -1232,6 +2866,404 @@ pub(crate) mod tests {
             vec![1, 2]
         );
 
+        // FlatDesync child streams do not carry the dimensionality information of their parent.
+        let c = Stream::new(
+            LogicalType::Bits(Positive::new(4).unwrap()),
+            PositiveReal::new(1.).unwrap(),
+            1,
+            Synchronicity::FlatDesync,
+            1,
+            Direction::Forward,
+            None,
+            false,
+        );
+        let u = LogicalType::try_new_union(vec![("a", 3.try_into()?), ("b", b), ("c", c.into())])?;
+        let stream: LogicalType = Stream::new(
+            u,
+            PositiveReal::new(1.).unwrap(),
+            1,
+            Synchronicity::Sync,
+            1,
+            Direction::Forward,
+            None,
+            false,
+        )
+        .into();
+        let logical_stream = stream.synthesize();
+        assert_eq!(
+            logical_stream
+                .streams
+                .values()
+                .map(|physical_stream| physical_stream.dimensionality())
+                .collect::<Vec<_>>(),
+            vec![1, 1]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn union_tag() -> Result<()> {
+        let typ: LogicalType = Stream::new_basic(
+            Union::try_new_with_tag(
+                "kind",
+                vec![
+                    ("tag", LogicalType::try_new_bits(3)?),
+                    ("b", Stream::new_basic(LogicalType::try_new_bits(1)?).into()),
+                ],
+            )?
+            .into(),
+        )
+        .into();
+        let synth = typ.synthesize();
+        assert_eq!(
+            synth
+                .stream(&PathName::new_empty())
+                .unwrap()
+                .element_fields()
+                .iter()
+                .map(|(path, bit_count)| (path.to_string(), bit_count.get()))
+                .collect::<Vec<_>>(),
+            vec![("kind".to_string(), 1), ("union".to_string(), 3)]
+        );
+        assert!(Union::try_new_with_tag("union", vec![("a", 1), ("b", 2)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn synthesis_options() -> Result<()> {
+        let stream = |data: LogicalType, throughput: f64| -> LogicalType {
+            Stream::new(
+                data,
+                PositiveReal::new(throughput).unwrap(),
+                1,
+                Synchronicity::Sync,
+                1,
+                Direction::Forward,
+                None,
+                false,
+            )
+            .into()
+        };
+        let typ = stream(
+            LogicalType::try_new_group(vec![
+                ("a", 8.try_into()?),
+                ("b", stream(4.try_into()?, 1. / 3.)),
+                ("c", stream(stream(2.try_into()?, 0.7), 1.9)),
+            ])?,
+            2.5,
+        );
+        let lanes = |logical_stream: &LogicalStream| {
+            logical_stream
+                .streams()
+                .map(|(_, stream)| stream.element_lanes().get())
+                .collect::<Vec<_>>()
+        };
+
+        let options = [
+            SynthesisOptions::new(),
+            SynthesisOptions::new().with_rounding(Rounding::Down),
+            SynthesisOptions::new().with_rounding(Rounding::Nearest),
+            SynthesisOptions::new().with_max_lanes(Positive::new(2).unwrap()),
+            SynthesisOptions::new()
+                .with_lanes(PathName::try_new(vec!["b"])?, Positive::new(3).unwrap()),
+        ];
+        let expected = [
+            vec![3, 1, 4],
+            vec![2, 1, 3],
+            vec![3, 1, 3],
+            vec![2, 1, 2],
+            vec![3, 3, 4],
+        ];
+        for (options, expected) in options.iter().zip(expected.iter()) {
+            let synthesized = typ.synthesize_with(options);
+            assert_eq!(&lanes(&synthesized), expected);
+            assert_eq!(typ.resolve_throughput(options).synthesize(), synthesized);
+        }
+
+        let options = SynthesisOptions::new()
+            .with_lanes(
+                PathName::try_new(vec!["x", "y", "b"])?,
+                Positive::new(3).unwrap(),
+            )
+            .with_lanes(PathName::try_new(vec!["z"])?, Positive::new(2).unwrap());
+        assert_eq!(
+            options.scoped(&PathName::try_new(vec!["x", "y"])?),
+            SynthesisOptions::new()
+                .with_lanes(PathName::try_new(vec!["b"])?, Positive::new(3).unwrap())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn union_lowering() -> Result<()> {
+        let typ: LogicalType = Stream::new(
+            LogicalType::try_new_group(vec![
+                (
+                    "u",
+                    LogicalType::try_new_union(vec![
+                        ("a", LogicalType::try_new_bits(8)?),
+                        ("b", LogicalType::Null),
+                        (
+                            "c",
+                            LogicalType::try_new_union(vec![
+                                ("d", LogicalType::try_new_bits(4)?),
+                                ("e", Stream::new_basic(LogicalType::try_new_bits(2)?).into()),
+                            ])?,
+                        ),
+                    ])?,
+                ),
+                ("f", LogicalType::try_new_bits(1)?),
+            ])?,
+            PositiveReal::new(1.).unwrap(),
+            1,
+            Synchronicity::Sync,
+            5,
+            Direction::Forward,
+            None,
+            false,
+        )
+        .into();
+        let streams = |options: &SynthesisOptions| {
+            let synthesized = typ.synthesize_with(options);
+            assert_eq!(typ.resolve_throughput(options).synthesize(), synthesized);
+            synthesized
+                .streams()
+                .map(|(path, stream)| {
+                    (
+                        path.to_string(),
+                        stream
+                            .element_fields()
+                            .iter()
+                            .map(|(path, bit_count)| (path.to_string(), bit_count.get()))
+                            .collect::<Vec<_>>(),
+                        stream.complexity().clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let u = PathName::try_new(vec!["u"])?;
+        let c = PathName::try_new(vec!["u", "c"])?;
+
+        assert_eq!(
+            streams(&SynthesisOptions::new()),
+            vec![
+                (
+                    "".to_string(),
+                    vec![
+                        ("u__tag".to_string(), 2),
+                        ("u__union".to_string(), 8),
+                        ("f".to_string(), 1)
+                    ],
+                    Complexity::new_major(5)
+                ),
+                (
+                    "u__c__e".to_string(),
+                    vec![("".to_string(), 2)],
+                    Complexity::default()
+                ),
+            ]
+        );
+        let options = SynthesisOptions::new().with_union_lowering(UnionLowering::Streams);
+        assert_eq!(options.union_lowering_at(&c), UnionLowering::Streams);
+        assert_eq!(
+            streams(&options),
+            vec![
+                (
+                    "".to_string(),
+                    vec![("u__tag".to_string(), 2), ("f".to_string(), 1)],
+                    Complexity::new_major(5)
+                ),
+                (
+                    "u__a".to_string(),
+                    vec![("".to_string(), 8)],
+                    Complexity::new_major(5)
+                ),
+                (
+                    "u__c".to_string(),
+                    vec![("tag".to_string(), 1)],
+                    Complexity::new_major(5)
+                ),
+                (
+                    "u__c__d".to_string(),
+                    vec![("".to_string(), 4)],
+                    Complexity::new_major(5)
+                ),
+                (
+                    "u__c__e".to_string(),
+                    vec![("".to_string(), 2)],
+                    Complexity::default()
+                ),
+            ]
+        );
+        let options = SynthesisOptions::new()
+            .with_union_lowering_at(u, UnionLowering::Streams)
+            .with_union_lowering_at(c.clone(), UnionLowering::Overlay);
+        assert_eq!(
+            streams(&options)
+                .into_iter()
+                .map(|(path, fields, _)| (path, fields))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "".to_string(),
+                    vec![("u__tag".to_string(), 2), ("f".to_string(), 1)]
+                ),
+                ("u__a".to_string(), vec![("".to_string(), 8)]),
+                (
+                    "u__c".to_string(),
+                    vec![("tag".to_string(), 1), ("union".to_string(), 4)]
+                ),
+                ("u__c__e".to_string(), vec![("".to_string(), 2)]),
+            ]
+        );
+        assert_eq!(
+            options.scoped(&PathName::try_new(vec!["u"])?),
+            SynthesisOptions::new()
+                .with_union_lowering_at(PathName::new_empty(), UnionLowering::Streams)
+                .with_union_lowering_at(PathName::try_new(vec!["c"])?, UnionLowering::Overlay)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn field_order() -> Result<()> {
+        let stream = |data: LogicalType| -> LogicalType { Stream::new_basic(data).into() };
+        let declared = stream(LogicalType::try_new_group(vec![
+            ("b", LogicalType::try_new_bits(2)?),
+            (
+                "a",
+                LogicalType::try_new_union(vec![
+                    ("y", LogicalType::Null),
+                    ("x", LogicalType::try_new_bits(1)?),
+                ])?,
+            ),
+        ])?);
+        let sorted = stream(LogicalType::try_new_group(vec![
+            (
+                "a",
+                LogicalType::try_new_union(vec![
+                    ("x", LogicalType::try_new_bits(1)?),
+                    ("y", LogicalType::Null),
+                ])?,
+            ),
+            ("b", LogicalType::try_new_bits(2)?),
+        ])?);
+        let layout = |typ: &LogicalType, options: &SynthesisOptions| {
+            typ.synthesize_with(options)
+                .streams()
+                .flat_map(|(_, stream)| {
+                    stream
+                        .element_fields()
+                        .iter()
+                        .map(|(path, _)| path.to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Fields are laid out in the order in which they are declared by default.
+        let options = SynthesisOptions::new();
+        assert_eq!(layout(&declared, &options), vec!["b", "a__tag", "a__union"]);
+        // Maps of fields compare equal regardless of their order, so compare layouts instead.
+        assert_ne!(layout(&declared, &options), layout(&sorted, &options));
+
+        let options = SynthesisOptions::new().with_field_order(FieldOrder::Name);
+        assert_eq!(layout(&declared, &options), vec!["a__tag", "a__union", "b"]);
+        assert_eq!(layout(&sorted, &options), layout(&declared, &options));
+        assert_eq!(declared.sort_fields().to_string(), sorted.to_string());
+        assert_eq!(sorted.sort_fields().to_string(), sorted.to_string());
+        assert_eq!(
+            declared.resolve_throughput(&options).to_string(),
+            sorted.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "parser")]
+    fn split_user() -> Result<()> {
+        let parse = |input: &str| crate::parser::nom::logical_stream_type(input).unwrap().1;
+        let streams = |typ: &LogicalType, options: &SynthesisOptions| {
+            let synthesized = typ.synthesize_with(options);
+            assert_eq!(typ.resolve_throughput(options).synthesize(), synthesized);
+            synthesized
+                .streams()
+                .map(|(path, stream)| {
+                    let fields = |fields: &Fields| {
+                        fields
+                            .iter()
+                            .map(|(path, bit_count)| (path.to_string(), bit_count.get()))
+                            .collect::<Vec<_>>()
+                    };
+                    (
+                        path.to_string(),
+                        fields(stream.element_fields()),
+                        fields(stream.user()),
+                        stream.element_lanes().get(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let typ = parse(
+            "Stream<Group<a: Bits<8>, b: Stream<Bits<4>, u=Bits<1>>>, t=2, u=Group<c: Bits<2>>>",
+        );
+        let options = SynthesisOptions::new();
+        assert_eq!(
+            streams(&typ, &options),
+            vec![
+                (
+                    "".to_string(),
+                    vec![("a".to_string(), 8)],
+                    vec![("c".to_string(), 2)],
+                    2
+                ),
+                (
+                    "b".to_string(),
+                    vec![("".to_string(), 4)],
+                    vec![("".to_string(), 1)],
+                    2
+                ),
+            ]
+        );
+        let options = options.with_split_user(true);
+        assert_eq!(
+            streams(&typ, &options),
+            vec![
+                ("".to_string(), vec![("a".to_string(), 8)], vec![], 2),
+                ("b".to_string(), vec![("data".to_string(), 4)], vec![], 2),
+                ("b__user".to_string(), vec![("".to_string(), 1)], vec![], 1),
+                ("user".to_string(), vec![("c".to_string(), 2)], vec![], 1),
+            ]
+        );
+
+        // Streams that are only non-null because of their user signals are kept.
+        let typ = parse("Stream<Null, d=1, u=Bits<3>>");
+        assert_eq!(
+            streams(&typ, &options),
+            vec![
+                ("".to_string(), vec![], vec![], 1),
+                ("user".to_string(), vec![("".to_string(), 3)], vec![], 1),
+            ]
+        );
+        let synthesized = typ.synthesize_with(&options);
+        let user = synthesized
+            .stream(&PathName::try_new(vec!["user"])?)
+            .unwrap();
+        // User signals are per transfer, so the child stream has no dimensions of its own.
+        assert_eq!(user.dimensionality(), 0);
+        assert_eq!(typ.split_user().split_user(), typ.split_user());
+
+        // The child stream keeps one element lane if its parent gets more lanes.
+        let typ = parse("Stream<Bits<8>, t=3, u=Bits<1>>");
+        let options = options.with_lanes(PathName::new_empty(), Positive::new(5).unwrap());
+        assert_eq!(
+            streams(&typ, &options),
+            vec![
+                ("".to_string(), vec![("data".to_string(), 8)], vec![], 5),
+                ("user".to_string(), vec![("".to_string(), 1)], vec![], 1),
+            ]
+        );
         Ok(())
     }
 }
@@ -0,0 +1,360 @@
+//! Symbol index for editor tooling.
+//!
+//! Indexes the definitions of named types, streamlets and interfaces in Streamlet Definition
+//! Files, and the references to named types, along with the byte ranges at which their names
+//! appear in the source. This allows language servers to list the symbols of a project, find the
+//! references to a named type, and resolve the symbol at a position, without a parser of their
+//! own. Declarations with syntax errors are skipped, like [`list_of_declarations_recovering`]
+//! does.
+//!
+//! # Example
+//!
+//! ```
+//! use tydi::parser::index::{Index, SymbolKind};
+//! use tydi::{Name, PathName};
+//!
+//! let source = "Type pixel = Bits<24>\nStreamlet x (a : in pixel)";
+//! let index = Index::from_sources(vec![(Name::try_new("lib")?, source)]);
+//! let pixel = PathName::try_new(vec!["lib", "pixel"])?;
+//! assert_eq!(index.references(&pixel).count(), 1);
+//!
+//! // Resolve the reference to pixel in the interface of x.
+//! let offset = source.rfind("pixel").unwrap();
+//! let definition = index.resolve("lib", offset).unwrap();
+//! assert_eq!(definition.kind(), SymbolKind::Type);
+//! assert_eq!(definition.path(), &pixel);
+//! assert_eq!(definition.span().start(), 5);
+//! # Ok::<(), tydi::Error>(())
+//! ```
+//!
+//! [`list_of_declarations_recovering`]: ../nom/fn.list_of_declarations_recovering.html
+
+use crate::error::Error::FileIOError;
+use crate::parser::nom::parse_recovering;
+use crate::{Name, PathName, Result};
+use std::path::Path;
+
+/// A range of bytes in a source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The offset of the first byte.
+    start: usize,
+    /// The offset after the last byte.
+    end: usize,
+}
+
+impl Span {
+    /// Returns the offset of the first byte of this span.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the offset after the last byte of this span.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns true if the byte at `offset` is part of this span.
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.start..self.end).contains(&offset)
+    }
+}
+
+/// The kind of a symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// A named type.
+    Type,
+    /// A streamlet.
+    Streamlet,
+    /// An interface of a streamlet.
+    Interface,
+}
+
+/// The definition of a symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition {
+    kind: SymbolKind,
+    path: PathName,
+    span: Span,
+}
+
+impl Definition {
+    /// Returns the kind of the symbol.
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    /// Returns the path of the symbol, starting with the name of its library, e.g.
+    /// `lib.streamlet.interface`.
+    pub fn path(&self) -> &PathName {
+        &self.path
+    }
+
+    /// Returns the span of the name of the symbol in the source of its library.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A reference to a named type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+    target: PathName,
+    span: Span,
+}
+
+impl Reference {
+    /// Returns the path of the named type that is referred to, starting with the name of its
+    /// library.
+    pub fn target(&self) -> &PathName {
+        &self.target
+    }
+
+    /// Returns the span of the reference in the source of the library of its target.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A token of a declaration: a name or number, or a single punctuation character.
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+/// Returns the tokens of `source`, skipping whitespace and comments.
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut offset = 0;
+    while let Some(c) = source[offset..].chars().next() {
+        let rest = &source[offset..];
+        let length = if c.is_whitespace() {
+            c.len_utf8()
+        } else if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            rest.find("*/").map_or(rest.len(), |end| end + 2)
+        } else {
+            let length = if c.is_alphanumeric() || c == '_' {
+                rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len())
+            } else {
+                c.len_utf8()
+            };
+            tokens.push(Token {
+                text: &rest[..length],
+                start: offset,
+            });
+            length
+        };
+        offset += length;
+    }
+    tokens
+}
+
+/// Symbol index of Streamlet Definition Files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Index {
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+}
+
+impl Index {
+    /// Construct an index of the sources of libraries, given with the names of the libraries.
+    pub fn from_sources<S: AsRef<str>>(sources: impl IntoIterator<Item = (Name, S)>) -> Self {
+        let mut index = Index::default();
+        for (library, source) in sources {
+            index.add_source(library, source.as_ref());
+        }
+        index
+    }
+
+    /// Construct an index of Streamlet Definition Files. Libraries are named after the stems of
+    /// their file names, like [`Library::from_file`] does.
+    ///
+    /// [`Library::from_file`]: ../../design/struct.Library.html#method.from_file
+    pub fn from_files(paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<Self> {
+        let mut index = Index::default();
+        for path in paths {
+            let path = path.as_ref();
+            let library = Name::try_new(
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .ok_or_else(|| FileIOError("Invalid file name.".to_string()))?,
+            )?;
+            let source = std::fs::read_to_string(path)?;
+            index.add_source(library, source.as_str());
+        }
+        Ok(index)
+    }
+
+    /// Index the declarations in the source of a library.
+    fn add_source(&mut self, library: Name, source: &str) {
+        let mut types: Vec<&str> = vec![];
+        parse_recovering(source, |declaration| {
+            let base = declaration.as_ptr() as usize - source.as_ptr() as usize;
+            let tokens = tokenize(declaration);
+            let span = |token: &Token| Span {
+                start: base + token.start,
+                end: base + token.start + token.text.len(),
+            };
+            let path = |names: &[&str]| {
+                // The parser only accepts declarations with valid names.
+                PathName::try_new(std::iter::once(library.as_ref()).chain(names.iter().copied()))
+                    .unwrap()
+            };
+
+            let mut idents = tokens.iter().filter(|t| {
+                t.text
+                    .starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            });
+            let (keyword, name) = match (idents.next(), idents.next()) {
+                (Some(keyword), Some(name)) => (keyword, name),
+                _ => unreachable!(),
+            };
+            let streamlet = keyword.text == "Streamlet";
+            self.definitions.push(Definition {
+                kind: if streamlet {
+                    SymbolKind::Streamlet
+                } else {
+                    SymbolKind::Type
+                },
+                path: path(&[name.text]),
+                span: span(name),
+            });
+
+            // The depth of angle brackets and parentheses.
+            let (mut angles, mut parens) = (0, 0);
+            let text = |index: usize| tokens.get(index).map(|t| t.text);
+            for (index, token) in tokens.iter().enumerate() {
+                match token.text {
+                    "<" => angles += 1,
+                    ">" => angles -= 1,
+                    "(" => parens += 1,
+                    ")" => parens -= 1,
+                    _ => {}
+                }
+                if token.start <= name.start
+                    || !token
+                        .text
+                        .starts_with(|c: char| c.is_alphabetic() || c == '_')
+                {
+                    continue;
+                }
+                let previous = index.checked_sub(1).and_then(text);
+                match text(index + 1) {
+                    // Interface names, which are followed by their mode.
+                    Some(":") if streamlet && angles == 0 && parens == 1 => {
+                        self.definitions.push(Definition {
+                            kind: SymbolKind::Interface,
+                            path: path(&[name.text, token.text]),
+                            span: span(token),
+                        });
+                        continue;
+                    }
+                    // Field names, stream options and type constructors.
+                    Some(":") | Some("=") | Some("<") => continue,
+                    _ => {}
+                }
+                // Interface modes.
+                if streamlet && angles == 0 && previous == Some(":") {
+                    continue;
+                }
+                // Stream option values other than user types.
+                if previous == Some("=") && index.checked_sub(2).and_then(text) != Some("u") {
+                    continue;
+                }
+                if types.contains(&token.text) {
+                    self.references.push(Reference {
+                        target: path(&[token.text]),
+                        span: span(token),
+                    });
+                }
+            }
+
+            if !streamlet {
+                types.push(name.text);
+            }
+        });
+    }
+
+    /// Returns an iterator over the definitions of all symbols, in the order in which they are
+    /// declared.
+    pub fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.definitions.iter()
+    }
+
+    /// Returns the definition of the symbol at `path`, if there is one.
+    pub fn definition(&self, path: &PathName) -> Option<&Definition> {
+        self.definitions.iter().find(|d| &d.path == path)
+    }
+
+    /// Returns an iterator over the references to the named type at `path`.
+    pub fn references<'a>(&'a self, path: &'a PathName) -> impl Iterator<Item = &'a Reference> {
+        self.references.iter().filter(move |r| &r.target == path)
+    }
+
+    /// Returns the definition of the symbol at byte `offset` in the source of `library`: the
+    /// symbol that is defined there, or the named type that is referred to there.
+    pub fn resolve(&self, library: &str, offset: usize) -> Option<&Definition> {
+        let in_library = |path: &PathName| path.as_ref().first().is_some_and(|n| n == library);
+        self.definitions
+            .iter()
+            .find(|d| in_library(&d.path) && d.span.contains(offset))
+            .or_else(|| {
+                self.references
+                    .iter()
+                    .find(|r| in_library(&r.target) && r.span.contains(offset))
+                    .and_then(|r| self.definition(&r.target))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index() -> Result<()> {
+        let source = "/// A pixel.
+Type pixel = Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>
+Type frame = Stream<pixel, d=2, u=pixel> // Not a reference: pixel
+Type broken = Bits<>
+Streamlet filter (
+  /// Input.
+  in : in frame,
+  out : out Stream<Group<pixel: pixel>>
+)";
+        let index = Index::from_sources(vec![(Name::try_new("lib")?, source)]);
+        assert_eq!(
+            index
+                .definitions()
+                .map(|d| (d.kind(), d.path().join(".")))
+                .collect::<Vec<_>>(),
+            vec![
+                (SymbolKind::Type, "lib.pixel".to_string()),
+                (SymbolKind::Type, "lib.frame".to_string()),
+                (SymbolKind::Streamlet, "lib.filter".to_string()),
+                (SymbolKind::Interface, "lib.filter.in".to_string()),
+                (SymbolKind::Interface, "lib.filter.out".to_string()),
+            ]
+        );
+
+        let pixel = PathName::try_new(vec!["lib", "pixel"])?;
+        let references = index
+            .references(&pixel)
+            .map(|r| source[..r.span().start()].lines().count())
+            .collect::<Vec<_>>();
+        assert_eq!(references, vec![3, 3, 8]);
+
+        let frame = source.rfind("frame").unwrap();
+        assert_eq!(
+            index.resolve("lib", frame).map(|d| d.path().join(".")),
+            Some("lib.frame".to_string())
+        );
+        assert!(index.resolve("other", frame).is_none());
+        assert!(index.resolve("lib", 0).is_none());
+        Ok(())
+    }
+}
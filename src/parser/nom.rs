@@ -5,14 +5,16 @@ use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity, Union
 use crate::physical::Complexity;
 use crate::{Name, PositiveReal};
 
+use indexmap::IndexMap;
+use nom::error::{ErrorKind, ParseError, VerboseError};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until, take_while},
-    character::complete::{digit1, multispace1, none_of, one_of},
-    combinator::{map, map_res, opt, recognize},
+    character::complete::{digit1, multispace1, one_of},
+    combinator::{map, map_res, not, opt, recognize},
     multi::{many0, many1, separated_list0},
     number::complete::float,
-    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
 };
 use std::collections::HashMap;
 
@@ -48,14 +50,28 @@ use std::collections::HashMap;
 //     }
 // }
 
-type Result<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
+type Result<I, T> = nom::IResult<I, T, VerboseError<I>>;
+
+/// Named logical stream types, which can be referred to by name after they are declared.
+pub type NamedTypes = IndexMap<Name, LogicalType>;
+
+/// Whitespace, line comments and delimited comments, which may appear anywhere whitespace is
+/// allowed. Doc comments are not skipped, since they are part of what they document.
+fn space(input: &str) -> Result<&str, &str> {
+    alt((multispace1, comment_line, comment_delimited))(input)
+}
 
 fn ws0(input: &str) -> Result<&str, Vec<&str>> {
-    many0(multispace1)(input)
+    many0(space)(input)
 }
 
 fn ws1(input: &str) -> Result<&str, Vec<&str>> {
-    many1(multispace1)(input)
+    many1(space)(input)
+}
+
+/// An optional trailing comma at the end of a list.
+fn trailing_comma(input: &str) -> Result<&str, Option<&str>> {
+    opt(w(tag(",")))(input)
 }
 
 fn w<'a, T>(
@@ -85,7 +101,7 @@ pub fn take_until_newline_or_eof(input: &str) -> Result<&str, &str> {
 /// it produces an empty str.
 pub fn comment_line(input: &str) -> Result<&str, &str> {
     map(
-        tuple((tag("//"), none_of("/"), take_until_newline_or_eof)),
+        tuple((tag("//"), not(tag("/")), take_until_newline_or_eof)),
         |_| "",
     )(input)
 }
@@ -139,27 +155,81 @@ pub fn bits(input: &str) -> Result<&str, LogicalType> {
 }
 
 pub fn logical_stream_type(input: &str) -> Result<&str, LogicalType> {
-    alt((null, bits, group, union, stream))(input)
+    logical_stream_type_in(&NamedTypes::new(), input)
+}
+
+/// Parses a logical stream type that may refer to previously declared named types.
+pub fn logical_stream_type_in<'a>(
+    types: &NamedTypes,
+    input: &'a str,
+) -> Result<&'a str, LogicalType> {
+    alt((
+        null,
+        bits,
+        |i| group_in(types, i),
+        |i| union_in(types, i),
+        |i| stream_in(types, i),
+        map(|i| reference(types, i), |(_, t)| t),
+    ))(input)
 }
 
-fn fields(input: &str) -> Result<&str, Vec<(Name, LogicalType)>> {
-    separated_list0(
-        w(tag(",")),
-        separated_pair(w(name), w(tag(":")), w(logical_stream_type)),
+/// Parses a reference to a named type, returning its name and the type it refers to.
+pub fn reference<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, (Name, LogicalType)> {
+    map_res(name, |n: Name| {
+        types.get(&n).cloned().map(|t| (n, t)).ok_or(())
+    })(input)
+}
+
+fn fields<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, Vec<(Name, LogicalType)>> {
+    terminated(
+        separated_list0(
+            w(tag(",")),
+            separated_pair(
+                w(name),
+                w(tag(":")),
+                w(|i| logical_stream_type_in(types, i)),
+            ),
+        ),
+        trailing_comma,
     )(input)
 }
 
 pub fn group(input: &str) -> Result<&str, LogicalType> {
+    group_in(&NamedTypes::new(), input)
+}
+
+fn group_in<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, LogicalType> {
     map_res(
-        delimited(w(tag("Group<")), w(fields), tag(">")),
+        delimited(w(tag("Group<")), w(|i| fields(types, i)), tag(">")),
         |fields: Vec<(Name, LogicalType)>| Group::try_new(fields).map(Into::into).map_err(|_| ()),
     )(input)
 }
 
 pub fn union(input: &str) -> Result<&str, LogicalType> {
+    union_in(&NamedTypes::new(), input)
+}
+
+fn union_in<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, LogicalType> {
     map_res(
-        delimited(w(tag("Union<")), w(fields), tag(">")),
-        |fields: Vec<(Name, LogicalType)>| Union::try_new(fields).map(Into::into).map_err(|_| ()),
+        delimited(
+            w(tag("Union<")),
+            pair(
+                w(|i| fields(types, i)),
+                opt(terminated(
+                    preceded(pair(w(tag("tag")), w(tag("="))), w(name)),
+                    trailing_comma,
+                )),
+            ),
+            tag(">"),
+        ),
+        |(fields, tag_name): (Vec<(Name, LogicalType)>, Option<Name>)| {
+            match tag_name {
+                Some(tag_name) => Union::try_new_with_tag(tag_name, fields),
+                None => Union::try_new(fields),
+            }
+            .map(Into::into)
+            .map_err(|_| ())
+        },
     )(input)
 }
 
@@ -188,10 +258,14 @@ pub fn direction(input: &str) -> Result<&str, Direction> {
 }
 
 pub fn stream(input: &str) -> Result<&str, LogicalType> {
+    stream_in(&NamedTypes::new(), input)
+}
+
+fn stream_in<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, LogicalType> {
     map_res(
         tuple((
             w(tag("Stream<")),
-            w(logical_stream_type),
+            w(|i| logical_stream_type_in(types, i)),
             opt(preceded(
                 w(tag(",")),
                 map(
@@ -206,7 +280,7 @@ pub fn stream(input: &str) -> Result<&str, LogicalType> {
                                 recognize(synchronicity),
                                 recognize(complexity),
                                 recognize(direction),
-                                recognize(logical_stream_type),
+                                recognize(|i| logical_stream_type_in(types, i)),
                                 recognize(bool),
                             ))),
                         ),
@@ -214,9 +288,10 @@ pub fn stream(input: &str) -> Result<&str, LogicalType> {
                     |opts| opts.into_iter().collect::<HashMap<char, &str>>(),
                 ),
             )),
+            trailing_comma,
             tag(">"),
         )),
-        |(_, data, opt, _)| -> std::result::Result<LogicalType, ()> {
+        |(_, data, opt, _, _)| -> std::result::Result<LogicalType, ()> {
             let throughput = PositiveReal::new(
                 opt.as_ref()
                     .and_then(|opts| opts.get(&'t').map(|x| x.parse().ok()))
@@ -256,7 +331,7 @@ pub fn stream(input: &str) -> Result<&str, LogicalType> {
                 .as_ref()
                 .and_then(|opts| {
                     opts.get(&'u')
-                        .map(|x| logical_stream_type(x).ok().map(|(_, x)| x))
+                        .map(|x| logical_stream_type_in(types, x).ok().map(|(_, x)| x))
                 })
                 .unwrap_or(Option::None);
 
@@ -266,7 +341,7 @@ pub fn stream(input: &str) -> Result<&str, LogicalType> {
                 .flatten()
                 .unwrap_or(false);
 
-            Ok(Stream::new(
+            Stream::try_new(
                 data,
                 throughput,
                 dimensionality,
@@ -276,7 +351,8 @@ pub fn stream(input: &str) -> Result<&str, LogicalType> {
                 user,
                 keep,
             )
-            .into())
+            .map(Into::into)
+            .map_err(|_| ())
         },
     )(input)
 }
@@ -286,32 +362,51 @@ pub fn mode(input: &str) -> Result<&str, Mode> {
 }
 
 pub fn interface(input: &str) -> Result<&str, Interface> {
+    interface_in(&NamedTypes::new(), input)
+}
+
+/// Parses an interface of which the type may refer to previously declared named types.
+pub fn interface_in<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, Interface> {
     map_res(
         tuple((
             w(doc),
             w(name),
             w(tag(":")),
             mode,
-            multispace1,
-            logical_stream_type,
+            ws1,
+            alt((
+                map(|i| reference(types, i), |(n, t)| (t, Some(n))),
+                map(|i| logical_stream_type_in(types, i), |t| (t, None)),
+            )),
         )),
-        |(d, n, _, m, _, t): (Option<String>, Name, _, Mode, _, LogicalType)| {
-            Interface::try_new(n, m, t, d.as_deref()).map_err(|_| ())
+        |(d, n, _, m, _, (t, tn)): (_, Name, _, Mode, _, (LogicalType, Option<Name>))| {
+            Interface::try_new(n, m, t, d.as_deref())
+                .map(|i| match tn {
+                    Some(tn) => i.with_type_name(tn),
+                    None => i,
+                })
+                .map_err(|_| ())
         },
     )(input)
 }
 
 pub fn streamlet(input: &str) -> Result<&str, Streamlet> {
+    streamlet_in(&NamedTypes::new(), input)
+}
+
+/// Parses a streamlet of which the interface types may refer to previously declared named types.
+pub fn streamlet_in<'a>(types: &NamedTypes, input: &'a str) -> Result<&'a str, Streamlet> {
     map_res(
         tuple((
             w(doc),
             w(tag("Streamlet")),
             w(name),
             w(tag("(")),
-            separated_list0(w(tag(",")), w(interface)),
+            separated_list0(w(tag(",")), w(|i| interface_in(types, i))),
+            trailing_comma,
             tag(")"),
         )),
-        |(d, _, n, _, il, _): (Option<String>, _, Name, _, Vec<Interface>, _)| {
+        |(d, _, n, _, il, _, _): (Option<String>, _, Name, _, Vec<Interface>, _, _)| {
             Streamlet::from_builder(n, il.into_iter().collect(), d.as_deref())
         },
     )(input)
@@ -324,10 +419,230 @@ pub fn list_of_streamlets(input: &str) -> Result<&str, Vec<Streamlet>> {
     )(input)
 }
 
+/// Parses a named type declaration, e.g. `Type pixel = Bits<24>`.
+pub fn type_declaration<'a>(
+    types: &NamedTypes,
+    input: &'a str,
+) -> Result<&'a str, (Name, LogicalType)> {
+    map(
+        tuple((w(doc), w(tag("Type")), w(name), w(tag("=")), |i| {
+            logical_stream_type_in(types, i)
+        })),
+        |(_, _, n, _, t)| (n, t),
+    )(input)
+}
+
+/// Parses a list of named type declarations and streamlets. Named types must be declared before
+/// they are referred to.
+pub fn list_of_declarations(input: &str) -> Result<&str, (NamedTypes, Vec<Streamlet>)> {
+    let mut types = NamedTypes::new();
+    let mut streamlets = Vec::new();
+    let (mut input, _) = ws0(input)?;
+    let mut rest = input;
+    loop {
+        if let Ok((remaining, (n, t))) = type_declaration(&types, input) {
+            if types.contains_key(&n) {
+                return Err(nom::Err::Failure(VerboseError::from_error_kind(
+                    input,
+                    ErrorKind::Verify,
+                )));
+            }
+            types.insert(n, t);
+            rest = remaining;
+        } else if let Ok((remaining, streamlet)) = streamlet_in(&types, input) {
+            streamlets.push(streamlet);
+            rest = remaining;
+        } else {
+            break;
+        }
+        match ws1(rest) {
+            Ok((remaining, _)) => input = remaining,
+            Err(_) => break,
+        }
+    }
+    Ok((rest, (types, streamlets)))
+}
+
+/// A syntax error in a Streamlet Definition File.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The line of the error, starting at 1.
+    line: usize,
+    /// The column of the error, starting at 1.
+    column: usize,
+    /// Description of the error.
+    message: String,
+}
+
+impl Diagnostic {
+    /// Returns the line of the error, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column of the error, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the description of the error.
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Returns a diagnostic with `message` at the start of `at`, which is a suffix of `source`.
+fn diagnostic(source: &str, at: &str, message: String) -> Diagnostic {
+    let before = &source[..source.len() - at.len()];
+    Diagnostic {
+        line: before.matches('\n').count() + 1,
+        column: before.chars().rev().take_while(|&c| c != '\n').count() + 1,
+        message,
+    }
+}
+
+/// Returns `input` after the documentation and whitespace at its start.
+fn skip_doc(input: &str) -> &str {
+    let input = doc(input).map_or(input, |(i, _)| i);
+    ws0(input).map_or(input, |(i, _)| i)
+}
+
+/// Returns the keyword of the declaration that starts at `input`, after its documentation.
+fn declaration_keyword(input: &str) -> Option<&'static str> {
+    let input = skip_doc(input);
+    ["Streamlet", "Type"].iter().copied().find(|keyword| {
+        input.starts_with(keyword)
+            && input[keyword.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_whitespace())
+    })
+}
+
+/// Returns the input from the start of the first line after the line of the declaration that
+/// starts at `input` that starts another declaration, or an empty string if there is none.
+fn synchronize(input: &str) -> &str {
+    let mut rest = skip_doc(input);
+    while let Some(newline) = rest.find('\n') {
+        rest = rest[newline + 1..].trim_start_matches([' ', '\t']);
+        if declaration_keyword(rest).is_some() {
+            return rest;
+        }
+    }
+    ""
+}
+
+/// Parses a list of named type declarations and streamlets like [`list_of_declarations`], but
+/// recovers from syntax errors instead of stopping at the first one.
+///
+/// When a declaration cannot be parsed, a diagnostic is recorded and parsing continues at the
+/// next line that starts a declaration with `Type` or `Streamlet`, or its documentation. Returns
+/// the declarations that were parsed, and the diagnostics in the order in which they were found.
+///
+/// # Example
+///
+/// ```
+/// use tydi::parser::nom::list_of_declarations_recovering;
+///
+/// let (types, streamlets, diagnostics) = list_of_declarations_recovering(
+///     "Type a = Bits<>
+/// Streamlet x (a : in Bits<8>)
+/// Streamlet y (a : on Null)",
+/// );
+/// assert!(types.is_empty());
+/// assert_eq!(streamlets.len(), 1);
+/// assert_eq!(diagnostics.len(), 2);
+/// assert_eq!(diagnostics[1].line(), 3);
+/// ```
+pub fn list_of_declarations_recovering(
+    input: &str,
+) -> (NamedTypes, Vec<Streamlet>, Vec<Diagnostic>) {
+    parse_recovering(input, |_| {})
+}
+
+/// Implements [`list_of_declarations_recovering`], calling `f` with the source of every
+/// declaration that is accepted, including its documentation.
+pub(crate) fn parse_recovering<'a>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str),
+) -> (NamedTypes, Vec<Streamlet>, Vec<Diagnostic>) {
+    let mut types = NamedTypes::new();
+    let mut streamlets = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut rest = ws0(input).map_or(input, |(i, _)| i);
+    while !rest.is_empty() {
+        let keyword = declaration_keyword(rest);
+        let result = match keyword {
+            Some("Type") => type_declaration(&types, rest).map(|(i, (n, t))| {
+                if types.contains_key(&n) {
+                    diagnostics.push(diagnostic(
+                        input,
+                        rest,
+                        format!("type {} is declared more than once", n),
+                    ));
+                } else {
+                    types.insert(n, t);
+                    f(&rest[..rest.len() - i.len()]);
+                }
+                i
+            }),
+            _ => streamlet_in(&types, rest).map(|(i, streamlet)| {
+                streamlets.push(streamlet);
+                f(&rest[..rest.len() - i.len()]);
+                i
+            }),
+        };
+        rest = match result {
+            Ok(remaining) => remaining,
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                // Report the error where the parser got furthest, after any documentation.
+                let at = skip_doc(
+                    e.errors
+                        .iter()
+                        .map(|(i, _)| *i)
+                        .min_by_key(|i| i.len())
+                        .unwrap_or(rest),
+                );
+                let found = at
+                    .split_whitespace()
+                    .next()
+                    .map(|token| format!(", found \"{}\"", token))
+                    .unwrap_or_else(|| ", found end of file".to_string());
+                diagnostics.push(diagnostic(
+                    input,
+                    at,
+                    match keyword {
+                        Some("Type") => format!("invalid type declaration{}", found),
+                        Some(_) => format!("invalid streamlet declaration{}", found),
+                        None => format!("expected a type declaration or streamlet{}", found),
+                    },
+                ));
+                synchronize(rest)
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!(),
+        };
+        rest = ws0(rest).map_or(rest, |(i, _)| i);
+    }
+    (types, streamlets, diagnostics)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::design::streamlet::tests::streamlets;
+    use crate::traits::Identify;
     use crate::util::UniquelyNamedBuilder;
 
     #[test]
@@ -412,6 +727,22 @@ mod tests {
                 .into()
             ))
         );
+        assert_eq!(
+            union("Union<tag: Null, b: Bits<5>, tag = kind,>"),
+            Ok((
+                "",
+                Union::try_new_with_tag(
+                    "kind",
+                    vec![
+                        ("tag", LogicalType::Null),
+                        ("b", LogicalType::try_new_bits(5).unwrap())
+                    ]
+                )
+                .unwrap()
+                .into()
+            ))
+        );
+        assert!(union("Union<tag: Null, b: Bits<5>>").is_err());
     }
 
     #[test]
@@ -506,6 +837,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_round_trip() {
+        for input in [
+            "Null",
+            "Bits<3>",
+            "Group<a: Null, b: Union<c: Bits<1>, d: Group<>>>",
+            "Stream<Bits<8>, t=0.5, d=2, s=FlatDesync, c=4.2, r=Reverse, u=Group<u0: Bits<1>>, x=true>",
+            "Stream<Group<a: Stream<Bits<1>, s=Desync>, b: Bits<2>>, d=1>",
+        ] {
+            let (rest, typ) = logical_stream_type(input).unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(typ.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn parse_stream_illegal_user() {
+        assert!(stream("Stream<Bits<1>, u=Stream<Bits<1>>>").is_err());
+    }
+
     #[test]
     fn parse_streamlet() {
         assert_eq!(
@@ -541,6 +892,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_comments_trailing_commas() {
+        assert_eq!(
+            streamlet(concat!(
+                "Streamlet test ( // interfaces\n",
+                "  a : in /* type */ Group<\n",
+                "    a : Bits</* width */ 1>, // first\n",
+                "    b : Bits<2>, // last\n",
+                "  >,\n",
+                "  //\n",
+                "  c : out Stream<Null, d=1, /* keep */ x=true,>,\n",
+                ")",
+            )),
+            streamlet("Streamlet test (a : in Group<a : Bits<1>, b : Bits<2>>, c : out Stream<Null, d=1, x=true>)")
+        );
+        assert_eq!(
+            union("Union<a:Null,>"),
+            Ok((
+                "",
+                Union::try_new(vec![("a", LogicalType::Null)])
+                    .unwrap()
+                    .into()
+            ))
+        );
+        assert!(group("Group<a:Null,,>").is_err());
+    }
+
     #[test]
     fn parse_streamlet_docstring() {
         assert_eq!(
@@ -584,6 +962,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_list_of_declarations() {
+        let (rest, (types, streamlets)) = list_of_declarations(concat!(
+            "/// A pixel.\n",
+            "Type pixel = Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>\n",
+            "Type pixels = Stream<pixel, d=1>\n",
+            "Streamlet a ( a: in pixels, b: out Group<p: pixel>)",
+        ))
+        .unwrap();
+        assert_eq!(rest, "");
+        let pixel = LogicalType::try_new_group(vec![("r", 8), ("g", 8), ("b", 8)]).unwrap();
+        let pixels = LogicalType::from(Stream::new(
+            pixel.clone(),
+            PositiveReal::new(1.).unwrap(),
+            1,
+            Synchronicity::Sync,
+            Complexity::default(),
+            Direction::Forward,
+            None,
+            false,
+        ));
+        assert_eq!(types.get(&Name::try_new("pixels").unwrap()), Some(&pixels));
+        assert_eq!(
+            streamlets,
+            vec![Streamlet::from_builder(
+                Name::try_new("a").unwrap(),
+                UniquelyNamedBuilder::new().with_items(vec![
+                    Interface::try_new("a", Mode::In, pixels, None)
+                        .unwrap()
+                        .with_type_name(Name::try_new("pixels").unwrap()),
+                    Interface::try_new(
+                        "b",
+                        Mode::Out,
+                        LogicalType::try_new_group(vec![("p", pixel)]).unwrap(),
+                        None
+                    )
+                    .unwrap(),
+                ]),
+                None
+            )
+            .unwrap()]
+        );
+
+        // Types must be declared before use, and only once.
+        assert_eq!(
+            list_of_declarations("Streamlet a ( a: in pixel)\nType pixel = Bits<8>")
+                .unwrap()
+                .1
+                 .1,
+            vec![]
+        );
+        assert!(list_of_declarations("Type a = Null\nType a = Null").is_err());
+    }
+
+    #[test]
+    fn parse_list_of_declarations_recovering() {
+        let (types, streamlets, diagnostics) = list_of_declarations_recovering(concat!(
+            "Type a = Null\n",
+            "Type a = Null\n",
+            "/// Doc\n",
+            "Streamlet x (\n",
+            "  /// Doc\n",
+            "  a : inn Null\n",
+            ")\n",
+            "Streamlet y (a : in a)\n",
+            "garbage"
+        ));
+        assert_eq!(types.len(), 1);
+        assert_eq!(
+            streamlets
+                .iter()
+                .map(|s| s.identifier())
+                .collect::<Vec<_>>(),
+            vec!["y"]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "line 2, column 1: type a is declared more than once",
+                "line 6, column 3: invalid streamlet declaration, found \"a\"",
+                "line 9, column 1: expected a type declaration or streamlet, found \"garbage\"",
+            ]
+        );
+    }
+
     #[test]
     fn parse_list_of_streamlets() {
         assert_eq!(
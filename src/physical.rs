@@ -50,13 +50,22 @@
 //! [Tydi specification]: https://abs-tudelft.github.io/tydi/specification/physical.html
 
 use crate::traits::Identify;
-use crate::{util::log2_ceil, Error, NonNegative, PathName, Positive, Result};
-use indexmap::IndexMap;
-use std::str::FromStr;
-use std::{
+use crate::{
+    util::{log2_ceil, FnvHasher, IndexMap},
+    Error, Name, NonNegative, PathName, Positive, Result,
+};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::hash::Hasher;
+use core::str::FromStr;
+use core::{
     cmp::Ordering,
     convert::{TryFrom, TryInto},
     fmt,
+    ops::Range,
 };
 
 /// Positive number of bits.
@@ -175,7 +184,7 @@ impl FromStr for Complexity {
                 // convert slices to nonnegatives after trimming whitespace
                 .map(|d| d.trim().parse::<NonNegative>())
                 // convert to result with vector of nonnegatives
-                .collect::<std::result::Result<Vec<_>, std::num::ParseIntError>>()
+                .collect::<core::result::Result<Vec<_>, core::num::ParseIntError>>()
                 // convert potential error to tydi error
                 .map_err(|e| Error::InvalidArgument(e.to_string()))?,
         )
@@ -250,6 +259,73 @@ impl Complexity {
     pub fn major(&self) -> NonNegative {
         self.level[0]
     }
+
+    /// Returns true if a source may release `valid` between innermost sequences
+    /// (C >= 2). Below this level, `valid` must remain asserted until the
+    /// outermost sequence is completed.
+    pub fn allows_stall_between_inner_sequences(&self) -> bool {
+        self.major() >= 2
+    }
+
+    /// Returns true if a source may release `valid` between any two transfers
+    /// (C >= 3). Below this level, `valid` must remain asserted until the
+    /// innermost sequence is completed.
+    pub fn allows_stall_within_inner_sequences(&self) -> bool {
+        self.major() >= 3
+    }
+
+    /// Returns true if a source may send transfers without data (C >= 4), other
+    /// than those that are needed to transfer empty sequences.
+    pub fn allows_empty_transfers(&self) -> bool {
+        self.major() >= 4
+    }
+
+    /// Returns true if a source may postpone `last` flags to a later transfer
+    /// (C >= 4). Below this level, the `last` flags of a sequence are
+    /// transferred along with its last element.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::Complexity;
+    ///
+    /// assert!(!Complexity::new_major(3).allows_postponed_last());
+    /// assert!(Complexity::new(vec![4, 1])?.allows_postponed_last());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn allows_postponed_last(&self) -> bool {
+        self.major() >= 4
+    }
+
+    /// Returns true if a source may leave lanes at the end of any transfer
+    /// unused (C >= 5), indicated by the end index. Below this level, only the
+    /// last transfer of an innermost sequence may have unused lanes.
+    pub fn allows_partial_transfers(&self) -> bool {
+        self.major() >= 5
+    }
+
+    /// Returns true if a source may leave lanes at the start of a transfer
+    /// unused (C >= 6), indicated by the start index.
+    pub fn allows_start_index(&self) -> bool {
+        self.major() >= 6
+    }
+
+    /// Returns true if streams of this complexity have a strobe signal,
+    /// regardless of their dimensionality (C >= 7). Below C = 8, a source
+    /// must drive all strobe bits of a transfer to the same value, see
+    /// [`requires_contiguous_lanes`].
+    ///
+    /// [`requires_contiguous_lanes`]: #method.requires_contiguous_lanes
+    pub fn has_strobe_signal(&self) -> bool {
+        self.major() >= 7
+    }
+
+    /// Returns true if the active lanes of a transfer must be contiguous
+    /// (C < 8), such that the strobe signal can only enable or disable all
+    /// lanes at once.
+    pub fn requires_contiguous_lanes(&self) -> bool {
+        self.major() < 8
+    }
 }
 
 impl fmt::Display for Complexity {
@@ -286,7 +362,8 @@ impl Fields {
     pub fn new(iter: impl IntoIterator<Item = (PathName, BitCount)>) -> Result<Self> {
         let fields = iter.into_iter();
         let (lower, upper) = fields.size_hint();
-        let mut map = IndexMap::with_capacity(upper.unwrap_or(lower));
+        let mut map =
+            IndexMap::with_capacity_and_hasher(upper.unwrap_or(lower), Default::default());
 
         for (path_name, bit_count) in fields {
             map.insert(path_name, bit_count)
@@ -298,7 +375,7 @@ impl Fields {
     }
 
     pub(crate) fn new_empty() -> Self {
-        Fields(IndexMap::new())
+        Fields(IndexMap::default())
     }
 
     pub(crate) fn insert(&mut self, path_name: PathName, bit_count: BitCount) -> Result<()> {
@@ -320,6 +397,126 @@ impl Fields {
     pub fn values(&self) -> impl Iterator<Item = &BitCount> {
         self.0.values()
     }
+
+    /// Returns these fields with all paths rebased under the given parent
+    /// name.
+    pub fn with_parent(&self, name: impl Into<Name>) -> Self {
+        let name = name.into();
+        Fields(
+            self.0
+                .iter()
+                .map(|(path_name, bit_count)| (path_name.with_parent(name.clone()), *bit_count))
+                .collect(),
+        )
+    }
+
+    /// Returns the concatenation of these fields and the other fields.
+    /// Returns an error if both contain a field with the same path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::Fields;
+    /// use tydi::{Name, PathName, Positive};
+    ///
+    /// let a = Fields::new(vec![(PathName::new_empty(), Positive::new(8).unwrap())])?;
+    /// let b = Fields::new(vec![(PathName::new_empty(), Positive::new(4).unwrap())])?;
+    /// let ab = a
+    ///     .with_parent(Name::try_new("a")?)
+    ///     .concat(&b.with_parent(Name::try_new("b")?))?;
+    /// assert_eq!(
+    ///     ab.keys().map(|p| p.to_string()).collect::<Vec<_>>(),
+    ///     vec!["a", "b"]
+    /// );
+    /// assert!(a.concat(&b).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn concat(&self, other: &Fields) -> Result<Self> {
+        Fields::new(
+            self.0
+                .iter()
+                .chain(other.0.iter())
+                .map(|(path_name, bit_count)| (path_name.clone(), *bit_count)),
+        )
+    }
+
+    /// Returns the fields of which the path starts with one of the given
+    /// paths, in their original order. Returns an error if a given path does
+    /// not select any field.
+    pub fn project<'a>(&self, paths: impl IntoIterator<Item = &'a PathName>) -> Result<Self> {
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        let selects = |prefix: &PathName, path_name: &PathName| path_name.starts_with(prefix);
+        if let Some(path) = paths
+            .iter()
+            .find(|path| !self.0.keys().any(|path_name| selects(path, path_name)))
+        {
+            return Err(Error::InvalidArgument(format!(
+                "no field with path \"{}\"",
+                path
+            )));
+        }
+        Ok(Fields(
+            self.0
+                .iter()
+                .filter(|(path_name, _)| paths.iter().any(|path| selects(path, path_name)))
+                .map(|(path_name, bit_count)| (path_name.clone(), *bit_count))
+                .collect(),
+        ))
+    }
+
+    /// Returns an iterator over the path, offset and width of every field
+    /// in canonical packing order: in a vector containing all fields, the
+    /// first field occupies the least significant bits. Returns an error if
+    /// an offset does not fit in a [`NonNegative`], like [`bit_ranges`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::Fields;
+    /// use tydi::{PathName, Positive};
+    ///
+    /// let fields = Fields::new(vec![
+    ///     (PathName::try_new(vec!["a"])?, Positive::new(8).unwrap()),
+    ///     (PathName::try_new(vec!["b", "c"])?, Positive::new(4).unwrap()),
+    /// ])?;
+    /// assert_eq!(
+    ///     fields
+    ///         .iter_leaves()?
+    ///         .map(|(path, offset, width)| (path.to_string(), offset, width.get()))
+    ///         .collect::<Vec<_>>(),
+    ///     vec![("a".to_string(), 0, 8), ("b__c".to_string(), 8, 4)]
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    /// [`bit_ranges`]: #method.bit_ranges
+    pub fn iter_leaves(&self) -> Result<impl Iterator<Item = (&PathName, NonNegative, BitCount)>> {
+        Ok(self
+            .bit_ranges()?
+            .into_iter()
+            .zip(self.0.values())
+            .map(|((path_name, range), bit_count)| (path_name, range.start, *bit_count)))
+    }
+
+    /// Returns the bit range of every field in a vector containing all
+    /// fields, where the first field occupies the least significant bits.
+    /// Returns an error if a range does not fit in a [`NonNegative`].
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    pub fn bit_ranges(&self) -> Result<Vec<(&PathName, Range<NonNegative>)>> {
+        let mut offset: NonNegative = 0;
+        self.0
+            .iter()
+            .map(|(path_name, bit_count)| {
+                let start = offset;
+                offset = start.checked_add(bit_count.get()).ok_or_else(|| {
+                    Error::WidthError(format!("bit range of field \"{}\" overflows", path_name))
+                })?;
+                Ok((path_name, start..offset))
+            })
+            .collect()
+    }
 }
 
 impl<'a> IntoIterator for &'a Fields {
@@ -331,6 +528,16 @@ impl<'a> IntoIterator for &'a Fields {
     }
 }
 
+/// Returns the sum of bit counts, or None if it does not fit in a
+/// [`NonNegative`].
+///
+/// [`NonNegative`]: ../type.NonNegative.html
+pub(crate) fn checked_sum(counts: impl IntoIterator<Item = NonNegative>) -> Option<NonNegative> {
+    counts
+        .into_iter()
+        .try_fold(0 as NonNegative, |acc, x| acc.checked_add(x))
+}
+
 /// Physical stream.
 ///
 /// A physical stream carries a stream of elements, dimensionality information
@@ -459,12 +666,26 @@ impl PhysicalStream {
     /// Returns the bit count of the data (element) fields in this physical
     /// stream. The bit count is equal to the combined bit count of all fields
     /// multiplied by the number of lanes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit count does not fit in a [`NonNegative`]. Use
+    /// [`checked_data_bit_count`] to detect this.
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    /// [`checked_data_bit_count`]: #method.checked_data_bit_count
     pub fn data_bit_count(&self) -> NonNegative {
-        self.element_fields
-            .values()
-            .map(|b| b.get())
-            .sum::<NonNegative>()
-            * self.element_lanes.get()
+        self.checked_data_bit_count()
+            .expect("data bit count overflow")
+    }
+
+    /// Returns the bit count of the data (element) fields in this physical
+    /// stream, or None if it does not fit in a [`NonNegative`].
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    pub fn checked_data_bit_count(&self) -> Option<NonNegative> {
+        checked_sum(self.element_fields.values().map(|b| b.get()))?
+            .checked_mul(self.element_lanes.get())
     }
 
     /// Returns the number of last bits in this physical stream. The number of
@@ -476,7 +697,7 @@ impl PhysicalStream {
     /// Returns the number of `stai` (start index) bits in this physical
     /// stream.
     pub fn stai_bit_count(&self) -> NonNegative {
-        if self.complexity.major() >= 6 && self.element_lanes.get() > 1 {
+        if self.complexity.allows_start_index() && self.element_lanes.get() > 1 {
             log2_ceil(self.element_lanes)
         } else {
             0
@@ -485,7 +706,7 @@ impl PhysicalStream {
 
     /// Returns the number of `endi` (end index) bits in this physical stream.
     pub fn endi_bit_count(&self) -> NonNegative {
-        if (self.complexity.major() >= 5 || self.dimensionality >= 1)
+        if (self.complexity.allows_partial_transfers() || self.dimensionality >= 1)
             && self.element_lanes.get() > 1
         {
             log2_ceil(self.element_lanes)
@@ -496,7 +717,7 @@ impl PhysicalStream {
 
     /// Returns the number of `strb` (strobe) bits in this physical stream.
     pub fn strb_bit_count(&self) -> NonNegative {
-        if self.complexity.major() >= 7 || self.dimensionality >= 1 {
+        if self.complexity.has_strobe_signal() || self.dimensionality >= 1 {
             self.element_lanes.get()
         } else {
             0
@@ -504,32 +725,152 @@ impl PhysicalStream {
     }
 
     /// Returns the bit count of the user fields in this physical stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit count does not fit in a [`NonNegative`]. Use
+    /// [`checked_user_bit_count`] to detect this.
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    /// [`checked_user_bit_count`]: #method.checked_user_bit_count
     pub fn user_bit_count(&self) -> NonNegative {
-        self.user.values().map(|b| b.get()).sum::<NonNegative>()
+        self.checked_user_bit_count()
+            .expect("user bit count overflow")
+    }
+
+    /// Returns the bit count of the user fields in this physical stream, or
+    /// None if it does not fit in a [`NonNegative`].
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    pub fn checked_user_bit_count(&self) -> Option<NonNegative> {
+        checked_sum(self.user.values().map(|b| b.get()))
+    }
+
+    /// Returns a physical stream carrying the element and user fields of
+    /// this and the other physical stream, rebased under the names `name`
+    /// and `other_name` respectively. The complexity of the result is the
+    /// highest of both. Returns an error if the streams have a different
+    /// number of element lanes or dimensionality.
+    pub fn concat(
+        &self,
+        name: impl Into<Name>,
+        other: &PhysicalStream,
+        other_name: impl Into<Name>,
+    ) -> Result<Self> {
+        if self.element_lanes != other.element_lanes {
+            return Err(Error::InvalidArgument(format!(
+                "cannot concatenate streams with {} and {} element lanes",
+                self.element_lanes, other.element_lanes
+            )));
+        }
+        if self.dimensionality != other.dimensionality {
+            return Err(Error::InvalidArgument(format!(
+                "cannot concatenate streams with dimensionality {} and {}",
+                self.dimensionality, other.dimensionality
+            )));
+        }
+        let (name, other_name) = (name.into(), other_name.into());
+        Ok(PhysicalStream::new(
+            self.element_fields
+                .with_parent(name.clone())
+                .concat(&other.element_fields.with_parent(other_name.clone()))?,
+            self.element_lanes,
+            self.dimensionality,
+            core::cmp::max(&self.complexity, &other.complexity).clone(),
+            self.user
+                .with_parent(name)
+                .concat(&other.user.with_parent(other_name))?,
+        ))
+    }
+
+    /// Returns this physical stream with only the element fields of which
+    /// the path starts with one of the given paths. Returns an error if a
+    /// given path does not select any element field.
+    pub fn project<'a>(&self, paths: impl IntoIterator<Item = &'a PathName>) -> Result<Self> {
+        Ok(PhysicalStream {
+            element_fields: self.element_fields.project(paths)?,
+            ..self.clone()
+        })
     }
 
     /// Returns the signal list for this physical stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit count of a signal does not fit in a [`NonNegative`].
+    /// Use [`checked_signal_list`] to detect this.
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    /// [`checked_signal_list`]: #method.checked_signal_list
     pub fn signal_list(&self) -> SignalList {
+        self.checked_signal_list()
+            .expect("signal bit count overflow")
+    }
+
+    /// Returns the signal list for this physical stream, or None if the bit
+    /// count of a signal does not fit in a [`NonNegative`].
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    pub fn checked_signal_list(&self) -> Option<SignalList> {
         let opt = |x| if x == 0 { None } else { Some(x) };
-        SignalList {
-            data: opt(self.data_bit_count()),
+        Some(SignalList {
+            data: opt(self.checked_data_bit_count()?),
             last: opt(self.last_bit_count()),
             stai: opt(self.stai_bit_count()),
             endi: opt(self.endi_bit_count()),
             strb: opt(self.strb_bit_count()),
-            user: opt(self.user_bit_count()),
-        }
+            user: opt(self.checked_user_bit_count()?),
+        })
     }
 
     /// Returns the combined bit count of all signals in this physical stream.
     /// This excludes the `valid` and `ready` signals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit count does not fit in a [`NonNegative`]. Use
+    /// [`checked_bit_count`] to detect this.
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    /// [`checked_bit_count`]: #method.checked_bit_count
     pub fn bit_count(&self) -> NonNegative {
-        self.data_bit_count()
-            + self.last_bit_count()
-            + self.stai_bit_count()
-            + self.endi_bit_count()
-            + self.strb_bit_count()
-            + self.user_bit_count()
+        self.checked_bit_count().expect("bit count overflow")
+    }
+
+    /// Returns the combined bit count of all signals in this physical stream,
+    /// or None if it does not fit in a [`NonNegative`]. This excludes the
+    /// `valid` and `ready` signals.
+    ///
+    /// [`NonNegative`]: ../type.NonNegative.html
+    pub fn checked_bit_count(&self) -> Option<NonNegative> {
+        checked_sum(vec![
+            self.checked_data_bit_count()?,
+            self.last_bit_count(),
+            self.stai_bit_count(),
+            self.endi_bit_count(),
+            self.strb_bit_count(),
+            self.checked_user_bit_count()?,
+        ])
+    }
+
+    /// Returns a fingerprint of this physical stream: a 64-bit hash that is
+    /// stable across runs and platforms, such that build systems can detect
+    /// changes to streams.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        write!(
+            hasher,
+            "n={};d={};c={};",
+            self.element_lanes, self.dimensionality, self.complexity
+        )
+        .expect("hashing cannot fail");
+        for (prefix, fields) in [("e", &self.element_fields), ("u", &self.user)] {
+            for (path_name, bit_count) in fields.iter() {
+                write!(hasher, "{}:{}={};", prefix, path_name, bit_count)
+                    .expect("hashing cannot fail");
+            }
+        }
+        hasher.finish()
     }
 }
 
@@ -545,12 +886,16 @@ impl From<PhysicalStream> for SignalList {
     }
 }
 
+/// The side of a physical stream that drives a signal, which determines its direction.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Origin {
+    /// The signal is driven by the source, like `data`.
     Source,
+    /// The signal is driven by the sink, like `ready`.
     Sink,
 }
 
+/// The width of a signal.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Width {
     /// Non-vectorized single bit.
@@ -559,6 +904,22 @@ pub enum Width {
     Vector(NonNegative),
 }
 
+impl Width {
+    /// Returns the number of bits of this width.
+    pub fn bit_count(&self) -> NonNegative {
+        match self {
+            Width::Scalar => 1,
+            Width::Vector(width) => *width,
+        }
+    }
+}
+
+/// A signal of a physical stream, with its name, origin and width.
+///
+/// All back-ends derive the canonical ports of streams from the signals of their
+/// [`SignalList`], such that they agree on the signals that make up a stream.
+///
+/// [`SignalList`]: ./struct.SignalList.html
 #[derive(Debug, Clone, PartialEq)]
 pub struct Signal {
     name: String,
@@ -609,14 +970,22 @@ impl Signal {
         self.origin == Origin::Sink
     }
 
+    /// Returns the side of the physical stream that drives the signal.
     pub fn origin(&self) -> Origin {
         self.origin
     }
 
+    /// Returns the width of the signal.
     pub fn width(&self) -> Width {
         self.width
     }
 
+    /// Returns the number of bits of the signal.
+    pub fn bit_count(&self) -> NonNegative {
+        self.width.bit_count()
+    }
+
+    /// Returns this signal with a different name.
     pub fn with_name(&self, name: String) -> Signal {
         Signal {
             name,
@@ -715,9 +1084,9 @@ impl SignalList {
     }
 }
 
-impl<'a> IntoIterator for &'a SignalList {
+impl IntoIterator for &SignalList {
     type Item = Signal;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = alloc::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
         [
@@ -731,8 +1100,7 @@ impl<'a> IntoIterator for &'a SignalList {
             self.user(),
         ]
         .iter()
-        .filter(|o| o.is_some())
-        .map(|s| s.clone().unwrap())
+        .filter_map(|s| s.clone())
         .collect::<Vec<_>>()
         .into_iter()
     }
@@ -741,12 +1109,12 @@ impl<'a> IntoIterator for &'a SignalList {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn complexity() -> Result<()> {
-        use std::convert::TryInto;
+        use core::convert::TryInto;
 
         let empty = Complexity::new(vec![]);
         assert_eq!(
@@ -791,6 +1159,16 @@ mod tests {
         assert_eq!(c31.major(), 3);
         assert_eq!(c4.major(), 4);
 
+        assert!(!c.allows_stall_between_inner_sequences());
+        assert!(c3.allows_stall_within_inner_sequences());
+        assert!(!c31.allows_postponed_last());
+        assert!(c401.allows_empty_transfers());
+        assert!(!c401.allows_partial_transfers());
+        assert!(!Complexity::new_major(6).has_strobe_signal());
+        assert!(Complexity::new_major(7).has_strobe_signal());
+        assert!(Complexity::new_major(7).requires_contiguous_lanes());
+        assert!(!Complexity::new_major(8).requires_contiguous_lanes());
+
         assert_eq!(c4.level(), &[4]);
         assert_eq!(c400.level(), &[4, 0, 0]);
         Ok(())
@@ -887,6 +1265,93 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn checked_bit_count() -> Result<()> {
+        let physical_stream =
+            PhysicalStream::try_new(vec![("a", 1 << 30), ("b", 1 << 30)], 2, 0, 0, vec![])?;
+        assert_eq!(physical_stream.checked_data_bit_count(), None);
+        assert_eq!(physical_stream.checked_bit_count(), None);
+        assert!(physical_stream.checked_signal_list().is_none());
+        assert!(physical_stream.element_fields().bit_ranges().is_ok());
+
+        let fields = Fields::new(vec![
+            (
+                PathName::try_new(vec!["a"])?,
+                Positive::new(u32::MAX).unwrap(),
+            ),
+            (PathName::try_new(vec!["b"])?, Positive::new(1).unwrap()),
+        ])?;
+        assert_eq!(
+            fields.bit_ranges().unwrap_err().to_string(),
+            "Width error: bit range of field \"b\" overflows"
+        );
+        let physical_stream =
+            PhysicalStream::try_new(vec![("a", 1 << 31), ("b", 1 << 31)], 1, 0, 0, vec![])?;
+        assert!(physical_stream.element_fields().iter_leaves().is_err());
+
+        let physical_stream =
+            PhysicalStream::try_new(vec![("a", u32::MAX as usize)], 1, 1, 0, vec![])?;
+        assert_eq!(physical_stream.checked_data_bit_count(), Some(u32::MAX));
+        assert_eq!(physical_stream.checked_bit_count(), None);
+        assert_eq!(
+            physical_stream.checked_signal_list().map(|s| s.data()),
+            Some(Some(Signal::vec(
+                "data",
+                Origin::Source,
+                Positive::new(u32::MAX).unwrap()
+            )))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn concat_project() -> Result<()> {
+        let a = PhysicalStream::try_new(vec![("x", 8), ("y", 2)], 2, 1, 4, vec![("u", 1)])?;
+        let b = PhysicalStream::try_new(vec![("x", 3)], 2, 1, 6, vec![])?;
+        let ab = a.concat(Name::try_new("a")?, &b, Name::try_new("b")?)?;
+        assert_eq!(
+            ab.element_fields()
+                .bit_ranges()?
+                .into_iter()
+                .map(|(p, r)| (p.to_string(), r))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a__x".to_string(), 0..8),
+                ("a__y".to_string(), 8..10),
+                ("b__x".to_string(), 10..13)
+            ]
+        );
+        assert_eq!(ab.complexity(), &Complexity::new_major(6));
+        assert_eq!(ab.user().keys().next().unwrap().to_string(), "a__u");
+        assert_eq!(ab.signal_list().data().unwrap().width(), Width::Vector(26));
+        assert!(a
+            .concat(Name::try_new("a")?, &a, Name::try_new("a")?)
+            .is_err());
+        assert!(a
+            .concat(
+                Name::try_new("a")?,
+                &PhysicalStream::try_new(vec![("x", 3)], 1, 1, 6, vec![])?,
+                Name::try_new("b")?
+            )
+            .is_err());
+
+        let p = ab.project(&[PathName::try_new(vec!["b"])?, "y".try_into()?]);
+        assert!(p.is_err());
+        let p = ab.project(&[
+            PathName::try_new(vec!["b"])?,
+            PathName::try_new(vec!["a", "y"])?,
+        ])?;
+        assert_eq!(
+            p.element_fields()
+                .keys()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a__y", "b__x"]
+        );
+        assert_eq!(p.data_bit_count(), 10);
+        Ok(())
+    }
+
     #[test]
     fn signal_list() -> Result<()> {
         let physical_stream = PhysicalStream::new(
@@ -934,6 +1399,8 @@ mod tests {
             Width::Vector(0)
         );
 
+        assert_eq!(signal_list.valid().bit_count(), 1);
+        assert_eq!(signal_list.data().unwrap().bit_count(), 10);
         assert_eq!(signal_list.opt_bit_count(), Some(17));
         assert_eq!(signal_list.bit_count(), 17);
         assert_eq!(signal_list, SignalList::from(physical_stream));
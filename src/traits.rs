@@ -1,5 +1,7 @@
 //! Tydi common traits.
 
+use alloc::string::String;
+
 /// In-place reverse.
 ///
 /// # Implementing `Reverse`
@@ -1,17 +1,92 @@
 use crate::traits::Identify;
 use crate::{Error, Result};
 use crate::{NonNegative, Positive};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use colored::Colorize;
+use core::fmt;
+use core::hash::{BuildHasherDefault, Hasher};
+use core::iter::FromIterator;
+#[cfg(feature = "std")]
 use log::{Level, Metadata, Record};
-use std::collections::HashSet;
-use std::iter::FromIterator;
 
 /// Returns ⌈log2(x)⌉.
 pub(crate) const fn log2_ceil(x: Positive) -> NonNegative {
-    8 * std::mem::size_of::<NonNegative>() as NonNegative
+    8 * core::mem::size_of::<NonNegative>() as NonNegative
         - (x.get() - 1).leading_zeros() as NonNegative
 }
 
+/// A 64-bit FNV-1a hasher. Unlike the hashers of the standard library, it does not require
+/// a source of randomness, such that it is available without the standard library.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+impl fmt::Write for FnvHasher {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// An insertion-ordered map that does not depend on the standard library for hashing.
+pub(crate) type IndexMap<K, V> = indexmap::IndexMap<K, V, BuildHasherDefault<FnvHasher>>;
+
+/// Returns the Levenshtein distance between two strings.
+#[cfg(feature = "std")]
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diag
+            } else {
+                1 + diag.min(above).min(row[j])
+            };
+            diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns the candidate closest to the input, if it is close enough to be a
+/// likely typo.
+#[cfg(feature = "std")]
+pub(crate) fn suggest<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(input, c), c))
+        .filter(|(d, c)| *d <= c.len().div_ceil(3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,6 +97,25 @@ mod tests {
             assert_eq!((i.get() as f64).log2().ceil() as NonNegative, log2_ceil(i));
         }
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn edit_distance_fn() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("suffix", "suffix"), 0);
+        assert_eq!(edit_distance("sufix", "suffix"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn suggest_fn() {
+        let keys = ["suffix", "abstraction"];
+        assert_eq!(suggest("sufix", keys), Some("suffix"));
+        assert_eq!(suggest("abstarction", keys), Some("abstraction"));
+        assert_eq!(suggest("output", keys), None);
+    }
 }
 
 /// A builder for lists of things requiring unique names.
@@ -61,7 +155,7 @@ impl<T: Identify> UniquelyNamedBuilder<T> {
     /// Finalize the builder, checking whether all names are unique.
     /// Returns Ok() if names were unique and an Err() otherwise.
     pub fn finish(self) -> Result<Vec<T>> {
-        let set: HashSet<&str> = self.items.iter().map(|item| item.identifier()).collect();
+        let set: BTreeSet<&str> = self.items.iter().map(|item| item.identifier()).collect();
         if self.items.len() != set.len() {
             Err(Error::UnexpectedDuplicate)
         } else {
@@ -85,8 +179,10 @@ impl<T: Identify> Default for UniquelyNamedBuilder<T> {
 }
 
 /// Simple logger for Tydi.
+#[cfg(feature = "std")]
 pub struct Logger;
 
+#[cfg(feature = "std")]
 impl log::Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
         metadata.level() <= Level::Debug
@@ -0,0 +1,89 @@
+/// Integration tests using the Chisel back-end.
+extern crate tydi;
+
+#[cfg(test)]
+mod tests {
+    use tydi::generator::chisel::Declare;
+    use tydi::generator::common::convert::Componentify;
+    use tydi::generator::common::Package;
+    use tydi::Name;
+    use tydi::UniquelyNamedBuilder;
+
+    #[test]
+    fn streamlet_decoupled() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet x ( a : in Stream<Group<op: Bits<4>, data: Bits<8>>, d=1>, b : out Stream<Bits<32>> )",
+        )
+        .unwrap();
+        let lib = tydi::design::library::Library::from_builder(
+            Name::try_new("t").unwrap(),
+            UniquelyNamedBuilder::new().with_items(vec![streamlet]),
+        )
+        .unwrap();
+
+        let package = Package {
+            identifier: "t".to_string(),
+            components: lib
+                .streamlets()
+                .iter()
+                .filter_map(|s| s.fancy(None))
+                .collect(),
+        };
+        assert_eq!(
+            package.declare().unwrap(),
+            "package t
+
+import chisel3._
+import chisel3.util._
+
+class x_a_data extends Bundle {
+  val op = UInt(4.W)
+  val data = UInt(8.W)
+}
+
+class x_a extends Bundle {
+  val data = new x_a_data
+  val last = UInt(1.W)
+  val strb = UInt(1.W)
+}
+
+class x_b extends Bundle {
+  val data = UInt(32.W)
+}
+
+abstract class x extends Module {
+  val io = IO(new Bundle {
+    val a = Flipped(Decoupled(new x_a))
+    val b = Decoupled(new x_b)
+  })
+}
+"
+        );
+    }
+
+    #[test]
+    fn streamlet_request_response() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet x (
+                a : in Stream<Group<req: Bits<8>, resp: Stream<Bits<16>, r=Reverse>>>,
+                b : out Stream<Group<req: Bits<8>, resp: Stream<Bits<16>, r=Reverse>>>
+            )",
+        )
+        .unwrap();
+
+        // Reversed streams flow against the direction of the interface, so the response of an
+        // input is an output, and vice versa.
+        let component = streamlet.fancy(None).unwrap();
+        assert_eq!(
+            component.declare().unwrap(),
+            "abstract class x extends Module {
+  val io = IO(new Bundle {
+    val a = Flipped(Decoupled(new x_a))
+    val a_resp = Decoupled(new x_a_resp)
+    val b = Decoupled(new x_b)
+    val b_resp = Flipped(Decoupled(new x_b_resp))
+  })
+}"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "golden")]
+
+/// Golden-file tests of the output of the back-ends.
+///
+/// Run with `UPDATE_GOLDEN=1` to update the golden files in `tests/golden` after intended changes
+/// to the generated sources.
+extern crate tydi;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use tydi::design::{Library, Project};
+    use tydi::generator::chisel::ChiselBackEnd;
+    use tydi::generator::config::GenerationConfig;
+    use tydi::generator::golden::check_dir;
+    use tydi::generator::vhdl::VHDLBackEnd;
+    use tydi::generator::GenerateProject;
+    use tydi::{Name, Result, UniquelyNamedBuilder};
+
+    fn project() -> Result<Project> {
+        let streamlets = vec![
+            "Streamlet bits (a : in Bits<1>, b : out Group<c: Bits<2>, d: Bits<3>>)",
+            "Streamlet stream (a : in Stream<Group<op: Bits<4>, data: Bits<8>>, t=2, d=1, c=7, u=Bits<3>>, b : out Stream<Bits<32>, x=true>)",
+            "Streamlet union (a : in Stream<Union<x: Bits<8>, y: Null>>)",
+        ];
+        Project::from_builder(
+            Name::try_new("proj")?,
+            UniquelyNamedBuilder::new().with_items(vec![Library::from_builder(
+                Name::try_new("lib")?,
+                UniquelyNamedBuilder::new().with_items(
+                    streamlets
+                        .into_iter()
+                        .map(|s| tydi::parser::nom::streamlet(s).unwrap().1),
+                ),
+            )?]),
+        )
+    }
+
+    fn golden(back_end: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("golden")
+            .join(back_end)
+    }
+
+    #[test]
+    fn vhdl() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&project()?, tmpdir.path())?;
+        check_dir(tmpdir.path(), golden("vhdl"))
+    }
+
+    /// All VHDL design files, in both standards. Both check against the same golden files, since
+    /// the output does not depend on the standard, apart from the identifiers it rejects.
+    #[test]
+    fn vhdl_standards() -> Result<()> {
+        for standard in &["93", "2008"] {
+            let config = GenerationConfig::from_toml(&format!(
+                "[vhdl]
+standard = \"{}\"
+split_files = true
+field_slices = true
+toplevel = true
+toplevel_prefix = true
+stubs = true
+record_defaults = \"0\"",
+                standard
+            ))?;
+            let tmpdir = tempfile::tempdir()?;
+            VHDLBackEnd::from(config.vhdl).generate(&project()?, tmpdir.path())?;
+            check_dir(tmpdir.path(), golden("vhdl_standards"))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn chisel() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        ChiselBackEnd::default().generate(&project()?, tmpdir.path())?;
+        check_dir(tmpdir.path(), golden("chisel"))
+    }
+}
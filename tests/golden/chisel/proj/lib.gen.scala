@@ -0,0 +1,58 @@
+package lib
+
+import chisel3._
+import chisel3.util._
+
+class bits_b extends Bundle {
+  val c = UInt(2.W)
+  val d = UInt(3.W)
+}
+
+class stream_a_data extends Bundle {
+  val op = UInt(4.W)
+  val data = UInt(8.W)
+}
+
+class stream_a extends Bundle {
+  val data = new stream_a_data
+  val last = UInt(1.W)
+  val stai = UInt(1.W)
+  val endi = UInt(1.W)
+  val strb = UInt(2.W)
+  val lane_valid = UInt(2.W)
+  val lane_count = UInt(2.W)
+  val user = UInt(3.W)
+}
+
+class stream_b extends Bundle {
+  val data = UInt(32.W)
+}
+
+class union_a_data extends Bundle {
+  val tag = UInt(1.W)
+  val x = UInt(8.W)
+}
+
+class union_a extends Bundle {
+  val data = new union_a_data
+}
+
+abstract class bits extends Module {
+  val io = IO(new Bundle {
+    val a = Input(UInt(1.W))
+    val b = new bits_b
+  })
+}
+
+abstract class stream extends Module {
+  val io = IO(new Bundle {
+    val a = Flipped(Decoupled(new stream_a))
+    val b = Decoupled(new stream_b)
+  })
+}
+
+abstract class union extends Module {
+  val io = IO(new Bundle {
+    val a = Flipped(Decoupled(new union_a))
+  })
+}
@@ -0,0 +1,41 @@
+package lib is
+
+component bits_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a : in std_logic_vector(0 downto 0);
+    b_c : out std_logic_vector(1 downto 0);
+    b_d : out std_logic_vector(2 downto 0)
+  );
+end component;
+
+component stream_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(23 downto 0);
+    a_last : in std_logic_vector(0 downto 0);
+    a_stai : in std_logic_vector(0 downto 0);
+    a_endi : in std_logic_vector(0 downto 0);
+    a_strb : in std_logic_vector(1 downto 0);
+    a_user : in std_logic_vector(2 downto 0);
+    b_valid : out std_logic;
+    b_ready : in std_logic;
+    b_data : out std_logic_vector(31 downto 0)
+  );
+end component;
+
+component union_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(8 downto 0)
+  );
+end component;
+
+end lib;
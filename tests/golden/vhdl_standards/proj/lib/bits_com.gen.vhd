@@ -0,0 +1,15 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.lib.all;
+
+entity bits_com is
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a : in std_logic_vector(0 downto 0);
+    b_c : out std_logic_vector(1 downto 0);
+    b_d : out std_logic_vector(2 downto 0)
+  );
+end bits_com;
@@ -0,0 +1,15 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.lib.all;
+
+entity union_com is
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(8 downto 0)
+  );
+end union_com;
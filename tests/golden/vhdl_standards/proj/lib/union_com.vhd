@@ -0,0 +1,13 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.lib.all;
+
+architecture behavioral of union_com is
+  -- BEGIN USER declarations
+  -- END USER declarations
+begin
+  -- BEGIN USER body
+  -- END USER body
+end behavioral;
@@ -0,0 +1,73 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+package lib_fields is
+
+  constant stream_a_element_width : natural := 12;
+  constant stream_a_op_high : natural := 3;
+  constant stream_a_op_low : natural := 0;
+  function f_get_stream_a_op(data : std_logic_vector; lane : natural := 0) return std_logic_vector;
+  constant stream_a_data_high : natural := 11;
+  constant stream_a_data_low : natural := 4;
+  function f_get_stream_a_data(data : std_logic_vector; lane : natural := 0) return std_logic_vector;
+  function f_stream_a_lane_valid(stai : std_logic_vector; endi : std_logic_vector; strb : std_logic_vector) return std_logic_vector;
+  function f_stream_a_lane_count(stai : std_logic_vector; endi : std_logic_vector; strb : std_logic_vector) return natural;
+  constant union_a_element_width : natural := 9;
+  constant union_a_tag_high : natural := 0;
+  constant union_a_tag_low : natural := 0;
+  function f_get_union_a_tag(data : std_logic_vector; lane : natural := 0) return std_logic_vector;
+  constant union_a_union_high : natural := 8;
+  constant union_a_union_low : natural := 1;
+  function f_get_union_a_union(data : std_logic_vector; lane : natural := 0) return std_logic_vector;
+
+end lib_fields;
+
+package body lib_fields is
+
+  function f_get_stream_a_op(data : std_logic_vector; lane : natural := 0) return std_logic_vector is
+  begin
+    return data(data'low + lane * stream_a_element_width + stream_a_op_high downto data'low + lane * stream_a_element_width + stream_a_op_low);
+  end function;
+
+  function f_get_stream_a_data(data : std_logic_vector; lane : natural := 0) return std_logic_vector is
+  begin
+    return data(data'low + lane * stream_a_element_width + stream_a_data_high downto data'low + lane * stream_a_element_width + stream_a_data_low);
+  end function;
+
+  function f_stream_a_lane_valid(stai : std_logic_vector; endi : std_logic_vector; strb : std_logic_vector) return std_logic_vector is
+    variable result : std_logic_vector(1 downto 0);
+  begin
+    for i in 0 to 1 loop
+      if strb(strb'low + i) = '1' and i >= to_integer(unsigned(stai)) and i <= to_integer(unsigned(endi)) then
+        result(i) := '1';
+      else
+        result(i) := '0';
+      end if;
+    end loop;
+    return result;
+  end function;
+
+  function f_stream_a_lane_count(stai : std_logic_vector; endi : std_logic_vector; strb : std_logic_vector) return natural is
+    constant lanes : std_logic_vector(1 downto 0) := f_stream_a_lane_valid(stai, endi, strb);
+    variable result : natural := 0;
+  begin
+    for i in lanes'range loop
+      if lanes(i) = '1' then
+        result := result + 1;
+      end if;
+    end loop;
+    return result;
+  end function;
+
+  function f_get_union_a_tag(data : std_logic_vector; lane : natural := 0) return std_logic_vector is
+  begin
+    return data(data'low + lane * union_a_element_width + union_a_tag_high downto data'low + lane * union_a_element_width + union_a_tag_low);
+  end function;
+
+  function f_get_union_a_union(data : std_logic_vector; lane : natural := 0) return std_logic_vector is
+  begin
+    return data(data'low + lane * union_a_element_width + union_a_union_high downto data'low + lane * union_a_element_width + union_a_union_low);
+  end function;
+
+end lib_fields;
@@ -0,0 +1,69 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.lib.all;
+
+entity lib_toplevel is
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    bits_a : in std_logic_vector(0 downto 0);
+    bits_b_c : out std_logic_vector(1 downto 0);
+    bits_b_d : out std_logic_vector(2 downto 0);
+    stream_a_valid : in std_logic;
+    stream_a_ready : out std_logic;
+    stream_a_data : in std_logic_vector(23 downto 0);
+    stream_a_last : in std_logic_vector(0 downto 0);
+    stream_a_stai : in std_logic_vector(0 downto 0);
+    stream_a_endi : in std_logic_vector(0 downto 0);
+    stream_a_strb : in std_logic_vector(1 downto 0);
+    stream_a_user : in std_logic_vector(2 downto 0);
+    stream_b_valid : out std_logic;
+    stream_b_ready : in std_logic;
+    stream_b_data : out std_logic_vector(31 downto 0);
+    union_a_valid : in std_logic;
+    union_a_ready : out std_logic;
+    union_a_data : in std_logic_vector(8 downto 0)
+  );
+end lib_toplevel;
+
+architecture structural of lib_toplevel is
+begin
+
+  bits_inst : bits_com
+    port map(
+      clk => clk,
+      rst => rst,
+      a => bits_a,
+      b_c => bits_b_c,
+      b_d => bits_b_d
+    );
+
+  stream_inst : stream_com
+    port map(
+      clk => clk,
+      rst => rst,
+      a_valid => stream_a_valid,
+      a_ready => stream_a_ready,
+      a_data => stream_a_data,
+      a_last => stream_a_last,
+      a_stai => stream_a_stai,
+      a_endi => stream_a_endi,
+      a_strb => stream_a_strb,
+      a_user => stream_a_user,
+      b_valid => stream_b_valid,
+      b_ready => stream_b_ready,
+      b_data => stream_b_data
+    );
+
+  union_inst : union_com
+    port map(
+      clk => clk,
+      rst => rst,
+      a_valid => union_a_valid,
+      a_ready => union_a_ready,
+      a_data => union_a_data
+    );
+
+end structural;
@@ -0,0 +1,56 @@
+/// Integration tests using the simulation model generator.
+extern crate tydi;
+
+/// The generated model of `streamlet()`.
+#[allow(dead_code, clippy::all)]
+mod model {
+    include!("sim/model.rs");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::model::*;
+    use tydi::generator::sim::DeclareModel;
+
+    fn streamlet() -> tydi::design::Streamlet {
+        tydi::parser::nom::streamlet(
+            "Streamlet x (a : in Stream<Group<op: Bits<4>, type: Bits<70>>, t=2, d=1, c=8, u=Bits<3>>, b : out Stream<Null, x=true>)",
+        )
+        .unwrap()
+        .1
+    }
+
+    #[test]
+    fn model() {
+        assert_eq!(
+            streamlet().declare_model().unwrap(),
+            include_str!("sim/model.rs")
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let transfer = XATransfer {
+            data: vec![
+                XAElement {
+                    op: 0b1010,
+                    r#type: vec![true; 70],
+                },
+                XAElement {
+                    op: 0b0101,
+                    r#type: vec![false; 70],
+                },
+            ],
+            last: 1,
+            stai: 1,
+            endi: 1,
+            strb: 0b10,
+            user: XAUser { value: 0b110 },
+        };
+        let bits = transfer.encode();
+        assert_eq!(bits.len(), XATransfer::BITS);
+        assert_eq!(&bits[0..4], &[false, true, false, true]);
+        assert_eq!(&bits[74..78], &[true, false, true, false]);
+        assert_eq!(XATransfer::decode(&bits), transfer);
+    }
+}
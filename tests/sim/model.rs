@@ -0,0 +1,168 @@
+/// Appends the `width` least significant bits of `value` to `bits`.
+#[allow(dead_code)]
+fn put(bits: &mut Vec<bool>, value: u64, width: usize) {
+    bits.extend((0..width).map(|i| (value >> i) & 1 == 1));
+}
+
+/// Appends the first `width` bits of `value` to `bits`, padded with zeros.
+#[allow(dead_code)]
+fn put_wide(bits: &mut Vec<bool>, value: &[bool], width: usize) {
+    bits.extend((0..width).map(|i| value.get(i).copied().unwrap_or(false)));
+}
+
+/// Returns the value of `width` bits of `bits` starting at `offset`.
+#[allow(dead_code)]
+fn get(bits: &[bool], offset: usize, width: usize) -> u64 {
+    (0..width).fold(0, |acc, i| acc | ((bits[offset + i] as u64) << i))
+}
+
+/// Returns `width` bits of `bits` starting at `offset`.
+#[allow(dead_code)]
+fn get_wide(bits: &[bool], offset: usize, width: usize) -> Vec<bool> {
+    bits[offset..offset + width].to_vec()
+}
+
+
+/// An element of physical stream `x_a`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XAElement {
+    /// Field `op`, 4 bit(s).
+    pub op: u64,
+    /// Field `type`, 70 bit(s).
+    pub r#type: Vec<bool>,
+}
+
+impl XAElement {
+    /// The number of bits of an encoded XAElement.
+    pub const BITS: usize = 74;
+
+    /// Appends the bits of this XAElement to `bits`.
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {
+        put(bits, self.op, 4);
+        put_wide(bits, &self.r#type, 70);
+    }
+
+    /// Decodes a XAElement from `bits`, starting at `offset`.
+    pub fn decode_from(bits: &[bool], offset: usize) -> Self {
+        XAElement {
+            op: get(bits, offset + 0, 4),
+            r#type: get_wide(bits, offset + 4, 70),
+        }
+    }
+}
+
+/// The user signals of physical stream `x_a`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XAUser {
+    /// The value, 3 bit(s).
+    pub value: u64,
+}
+
+impl XAUser {
+    /// The number of bits of an encoded XAUser.
+    pub const BITS: usize = 3;
+
+    /// Appends the bits of this XAUser to `bits`.
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {
+        put(bits, self.value, 3);
+    }
+
+    /// Decodes a XAUser from `bits`, starting at `offset`.
+    pub fn decode_from(bits: &[bool], offset: usize) -> Self {
+        XAUser {
+            value: get(bits, offset + 0, 3),
+        }
+    }
+}
+
+/// A transfer on physical stream `x_a`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XATransfer {
+    /// The element lanes. Missing lanes are encoded as default elements.
+    pub data: Vec<XAElement>,
+    /// The `last` signal, 1 bit(s).
+    pub last: u64,
+    /// The `stai` signal, 1 bit(s).
+    pub stai: u64,
+    /// The `endi` signal, 1 bit(s).
+    pub endi: u64,
+    /// The `strb` signal, 2 bit(s).
+    pub strb: u64,
+    /// The user signals.
+    pub user: XAUser,
+}
+
+impl XATransfer {
+    /// The number of element lanes.
+    pub const LANES: usize = 2;
+    /// The number of bits of an encoded transfer, excluding valid and ready.
+    pub const BITS: usize = 156;
+
+    /// Encodes this transfer into the bits of the signals of the stream, least significant bit
+    /// first, in the order data, last, stai, endi, strb, user.
+    pub fn encode(&self) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(Self::BITS);
+        self.encode_into(&mut bits);
+        bits
+    }
+
+    /// Appends the bits of this transfer to `bits`.
+    #[allow(unused_variables)]
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {
+        for lane in 0..Self::LANES {
+            self.data.get(lane).cloned().unwrap_or_default().encode_into(bits);
+        }
+        put(bits, self.last, 1);
+        put(bits, self.stai, 1);
+        put(bits, self.endi, 1);
+        put(bits, self.strb, 2);
+        self.user.encode_into(bits);
+    }
+
+    /// Decodes a transfer from the bits of the signals of the stream, as encoded by `encode`.
+    #[allow(unused_variables)]
+    pub fn decode(bits: &[bool]) -> Self {
+        XATransfer {
+            data: (0..Self::LANES)
+                .map(|lane| XAElement::decode_from(bits, lane * XAElement::BITS))
+                .collect(),
+            last: get(bits, 148, 1),
+            stai: get(bits, 149, 1),
+            endi: get(bits, 150, 1),
+            strb: get(bits, 151, 2),
+            user: XAUser::decode_from(bits, 153),
+        }
+    }
+}
+
+/// A transfer on physical stream `x_b`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XBTransfer {
+}
+
+impl XBTransfer {
+    /// The number of element lanes.
+    pub const LANES: usize = 1;
+    /// The number of bits of an encoded transfer, excluding valid and ready.
+    pub const BITS: usize = 0;
+
+    /// Encodes this transfer into the bits of the signals of the stream, least significant bit
+    /// first, in the order data, last, stai, endi, strb, user.
+    pub fn encode(&self) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(Self::BITS);
+        self.encode_into(&mut bits);
+        bits
+    }
+
+    /// Appends the bits of this transfer to `bits`.
+    #[allow(unused_variables)]
+    pub fn encode_into(&self, bits: &mut Vec<bool>) {
+    }
+
+    /// Decodes a transfer from the bits of the signals of the stream, as encoded by `encode`.
+    #[allow(unused_variables)]
+    pub fn decode(bits: &[bool]) -> Self {
+        XBTransfer {
+        }
+    }
+}
@@ -196,6 +196,214 @@ component test
   );
 end component;
 
+end test;"
+        );
+    }
+
+    #[test]
+    fn streamlet_stream_user() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet test (a : in Stream<Bits<4>, u=Group<b:Bits<1>, c:Bits<2>>>)",
+        )
+        .unwrap();
+        let lib = tydi::design::library::Library::from_builder(
+            Name::try_new("test").unwrap(),
+            UniquelyNamedBuilder::new().with_items(vec![streamlet]),
+        );
+
+        let pkg: tydi::generator::common::Package = lib.unwrap().fancy();
+        assert_eq!(
+            pkg.declare().unwrap(),
+            "package test is
+
+component test_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(3 downto 0);
+    a_user : in std_logic_vector(2 downto 0)
+  );
+end component;
+
+record test_a_user_dn_type
+  b : std_logic_vector(0 downto 0);
+  c : std_logic_vector(1 downto 0);
+end record;
+
+record test_a_dn_type
+  valid : std_logic;
+  data : std_logic_vector(3 downto 0);
+  user : test_a_user_dn_type;
+end record;
+
+record test_a_up_type
+  ready : std_logic;
+end record;
+
+component test
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_dn : in test_a_dn_type;
+    a_up : out test_a_up_type
+  );
+end component;
+
+end test;"
+        );
+    }
+
+    #[test]
+    fn streamlet_stream_reverse() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet test (a : in Stream<Group<c: Bits<2>, d: Stream<Bits<3>, r=Reverse>>>)",
+        )
+        .unwrap();
+        assert_eq!(
+            streamlet.canonical(None).declare().unwrap(),
+            "component test
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(1 downto 0);
+    a_d_valid : out std_logic;
+    a_d_ready : in std_logic;
+    a_d_data : out std_logic_vector(2 downto 0)
+  );
+end component;"
+        );
+        assert_eq!(
+            streamlet.fancy(None).unwrap().declare().unwrap(),
+            "component test
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_dn : in test_a_dn_type;
+    a_up : out test_a_up_type;
+    a_d_dn : out test_a_d_dn_type;
+    a_d_up : in test_a_d_up_type
+  );
+end component;"
+        );
+    }
+
+    #[test]
+    fn streamlet_stream_keep() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet test (a : in Stream<Null, x=true>, b : in Stream<Null>)",
+        )
+        .unwrap();
+        let lib = tydi::design::library::Library::from_builder(
+            Name::try_new("test").unwrap(),
+            UniquelyNamedBuilder::new().with_items(vec![streamlet]),
+        );
+
+        let pkg: tydi::generator::common::Package = lib.unwrap().fancy();
+        assert_eq!(
+            pkg.declare().unwrap(),
+            "package test is
+
+component test_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic
+  );
+end component;
+
+record test_a_dn_type
+  valid : std_logic;
+end record;
+
+record test_a_up_type
+  ready : std_logic;
+end record;
+
+component test
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_dn : in test_a_dn_type;
+    a_up : out test_a_up_type
+  );
+end component;
+
+end test;"
+        );
+    }
+
+    #[test]
+    fn library_named_types() {
+        let (_, (types, streamlets)) = tydi::parser::nom::list_of_declarations(
+            "Type pixels = Stream<Bits<8>>
+            Streamlet a (i : in pixels)
+            Streamlet b (o : out pixels)",
+        )
+        .unwrap();
+        let lib = tydi::design::library::Library::from_builder(
+            Name::try_new("test").unwrap(),
+            UniquelyNamedBuilder::new().with_items(streamlets),
+        )
+        .unwrap()
+        .with_named_types(types);
+
+        let pkg: tydi::generator::common::Package = lib.unwrap().fancy();
+        assert_eq!(
+            pkg.declare().unwrap(),
+            "package test is
+
+component a_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    i_valid : in std_logic;
+    i_ready : out std_logic;
+    i_data : in std_logic_vector(7 downto 0)
+  );
+end component;
+
+record pixels_dn_type
+  valid : std_logic;
+  data : std_logic_vector(7 downto 0);
+end record;
+
+record pixels_up_type
+  ready : std_logic;
+end record;
+
+component a
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    i_dn : in pixels_dn_type;
+    i_up : out pixels_up_type
+  );
+end component;
+
+component b_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    o_valid : out std_logic;
+    o_ready : in std_logic;
+    o_data : out std_logic_vector(7 downto 0)
+  );
+end component;
+
+component b
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    o_dn : out pixels_dn_type;
+    o_up : in pixels_up_type
+  );
+end component;
+
 end test;"
         );
     }
@@ -243,14 +451,8 @@ record test_a_c_up_type
   ready : std_logic;
 end record;
 
-record test_d_dn_type
-  valid : std_logic;
-  data : std_logic_vector(0 downto 0);
-end record;
-
-record test_d_up_type
-  ready : std_logic;
-end record;
+subtype test_d_dn_type is test_a_c_dn_type;
+subtype test_d_up_type is test_a_c_up_type;
 
 component test
   port(